@@ -11,7 +11,7 @@ use serde::Deserialize;
 
 // v5新機能のuse文追加（既存コードに影響なし）
 #[cfg(feature = "http-endpoints")]
-use crate::image::{ImageProcessor, FitMode, ProcessedImage};
+use crate::image::{ImageProcessor, FitMode, PaletteSize, ProcessedImage};
 #[cfg(feature = "http-endpoints")]
 use actix_multipart::{Multipart, Field};
 #[cfg(feature = "http-endpoints")]
@@ -23,16 +23,16 @@ use std::sync::Arc;
 #[cfg(feature = "http-endpoints")]
 #[derive(Debug, Clone)]
 struct ImageTile {
-    pub x: u8,
-    pub y: u8,
-    pub width: u8,
-    pub height: u8,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
     pub rgb565_data: Vec<u16>,
 }
 
-use crate::bluetooth::BluetoothManager;
+use crate::bluetooth::{BluetoothManager, DeviceCapabilities, DevicePin, SendReport};
 use crate::error::{NotifError, Result};
-use crate::protocol::{Command, RGB, Size};
+use crate::protocol::{Command, DeviceSettings, RGB, Size};
 use super::models::{v1, v2, ApiResponse, ApiError, parse_color_name};
 
 /// v1 /send ハンドラーの共通処理
@@ -42,8 +42,12 @@ pub async fn process_v1_send<M: BluetoothManager>(
 ) -> HttpResponse {
     info!("Processing v1 send request: {:?}", params);
     
+    // 送信先の画面サイズに合わせて折り返す
+    let selector = v2::DeviceSelector::parse(params.device.clone());
+    let capabilities = selector_capabilities(bt_manager.get_ref(), &selector).await;
+    
     // パラメータを解析してコマンドを生成
-    let commands = match build_v1_commands(&params) {
+    let commands = match build_v1_commands(&params, capabilities.grid_size()) {
         Ok(cmds) => cmds,
         Err(e) => {
             error!("Failed to build commands: {}", e);
@@ -52,35 +56,123 @@ pub async fn process_v1_send<M: BluetoothManager>(
     };
     
     // デバイス選択とコマンド送信
-    let result = match params.device.as_deref() {
-        Some("all") | None => {
-            // 全デバイスに送信
-            bt_manager.send_command_to_all(Command::Batch { commands }).await
-        }
-        Some(device_spec) => {
-            // 特定のデバイスに送信
-            if let Ok(num) = device_spec.parse::<usize>() {
-                bt_manager.send_command_by_number(num, Command::Batch { commands }).await
-            } else {
-                bt_manager.send_command_to_device(device_spec, Command::Batch { commands }).await
-            }
+    let report = match send_to_selector(bt_manager.get_ref(), &selector, Command::Batch { commands }).await {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Failed to send command: {}", e);
+            return HttpResponse::InternalServerError().json(v1::SendResponse::error(e.to_string()));
         }
     };
     
-    match result {
-        Ok(_) => {
+    match report.failed().first() {
+        None => {
             info!("Command sent successfully");
-            HttpResponse::Ok().json(v1::SendResponse::ok())
+            HttpResponse::Ok().json(v1::SendResponse::ok().with_devices(&report))
         }
-        Err(e) => {
-            error!("Failed to send command: {}", e);
-            HttpResponse::InternalServerError().json(v1::SendResponse::error(e.to_string()))
+        Some((device_id, e)) => {
+            error!("Failed to send command to {}: {}", device_id, e);
+            HttpResponse::InternalServerError()
+                .json(v1::SendResponse::error(e.to_string()).with_devices(&report))
+        }
+    }
+}
+
+/// 送信先セレクターを対象のデバイス名に解決（エイリアスはデバイス名に変換）
+/// 
+/// 番号指定で該当デバイスがない場合と、未定義のグループを指定した場合はErr
+pub(crate) async fn resolve_selector<M: BluetoothManager>(
+    bt_manager: &M,
+    selector: &v2::DeviceSelector,
+) -> Result<Vec<String>> {
+    match selector {
+        v2::DeviceSelector::All(_) => Ok(bt_manager.list_connected_devices().await
+            .into_iter()
+            .map(|device_info| device_info.name)
+            .collect()),
+        v2::DeviceSelector::Number(num) => bt_manager.get_device_name_by_number(*num).await
+            .map(|device_id| vec![device_id])
+            .ok_or_else(|| NotifError::DeviceNotFound(format!("Device #{}", num))),
+        v2::DeviceSelector::Id(id) => Ok(vec![bt_manager.resolve_device(id).await]),
+        v2::DeviceSelector::Group(group) => bt_manager.group_members(group).await
+            .ok_or_else(|| NotifError::DeviceNotFound(format!("Group {}", group))),
+    }
+}
+
+/// 送信先セレクターの対象デバイスに共通の機能（対象が接続されていなければ既定値）
+pub(crate) async fn selector_capabilities<M: BluetoothManager>(
+    bt_manager: &M,
+    selector: &v2::DeviceSelector,
+) -> DeviceCapabilities {
+    match selector {
+        v2::DeviceSelector::All(_) => bt_manager.target_capabilities(None).await,
+        v2::DeviceSelector::Number(num) => bt_manager.target_capabilities(Some(*num)).await,
+        _ => {
+            let device_names = resolve_selector(bt_manager, selector).await.unwrap_or_default();
+            bt_manager.list_connected_devices().await
+                .into_iter()
+                .filter(|info| device_names.contains(&info.name))
+                .map(|info| info.capabilities)
+                .reduce(|common, capabilities| common.intersect(&capabilities))
+                .unwrap_or_default()
         }
     }
 }
 
+/// 送信先セレクターに従ってコマンドを送信し、デバイスごとの結果を返す
+/// 
+/// 該当デバイス・グループがない場合と、全デバイス指定で接続中のデバイスがない場合はErr
+pub(crate) async fn send_to_selector<M: BluetoothManager>(
+    bt_manager: &M,
+    selector: &v2::DeviceSelector,
+    command: Command,
+) -> Result<SendReport> {
+    if let v2::DeviceSelector::All(_) = selector {
+        return bt_manager.send_command_to_all(command).await;
+    }
+    
+    let device_names = resolve_selector(bt_manager, selector).await?;
+    Ok(send_to_devices(bt_manager, device_names, command).await)
+}
+
+/// 指定したデバイスへ並行してコマンドを送信し、デバイスごとの結果を返す
+pub(crate) async fn send_to_devices<M: BluetoothManager>(
+    bt_manager: &M,
+    device_names: Vec<String>,
+    command: Command,
+) -> SendReport {
+    let sends = device_names
+        .into_iter()
+        .map(|device_id| {
+            let command = command.clone();
+            async move {
+                let result = bt_manager.send_command_to_device(&device_id, command).await;
+                (device_id, result)
+            }
+        });
+    let results = futures::future::join_all(sends).await;
+    SendReport { results: results.into_iter().collect() }
+}
+
+/// 失敗したデバイスを含む送信結果のエラーレスポンス
+fn send_report_error(report: &SendReport, code: Option<&str>, context: &str) -> HttpResponse {
+    let (code, message) = match report.failed().first() {
+        Some((device_id, e)) => (
+            code.unwrap_or(e.error_code()).to_string(),
+            format!("{}{} ({})", context, e, device_id),
+        ),
+        None => (code.unwrap_or("COMMAND_FAILED").to_string(), context.to_string()),
+    };
+    HttpResponse::InternalServerError().json(ApiResponse::<()>::error(ApiError {
+        code,
+        message,
+        details: Some(serde_json::json!({ "devices": report })),
+    }))
+}
+
 /// v1コマンドをビルド（v2互換の折り返し処理付き）
-fn build_v1_commands(params: &v1::SendQuery) -> Result<Vec<Command>> {
+/// 
+/// `grid`は送信先の画面のグリッド数（幅, 高さ）
+fn build_v1_commands(params: &v1::SendQuery, grid: (u8, u8)) -> Result<Vec<Command>> {
     let mut commands = Vec::new();
     
     // 背景色でクリア
@@ -100,7 +192,7 @@ fn build_v1_commands(params: &v1::SendQuery) -> Result<Vec<Command>> {
             .unwrap_or(Size::Medium);
         
         // v1 API用のテキスト処理（折り返しあり）
-        let text_commands = build_v1_text_commands(text, size, color)?;
+        let text_commands = build_v1_text_commands(text, size, color, grid)?;
         commands.extend(text_commands);
     }
     
@@ -173,28 +265,23 @@ pub async fn process_v2_draw<M: BluetoothManager>(
     
     // デバイス選択とコマンド送信
     let device_selector = v2::DeviceSelector::parse(request.device);
-    let result = match device_selector {
-        v2::DeviceSelector::All(_) => {
-            bt_manager.send_command_to_all(command).await
-        }
-        v2::DeviceSelector::Number(num) => {
-            bt_manager.send_command_by_number(num, command).await
-        }
-        v2::DeviceSelector::Id(id) => {
-            bt_manager.send_command_to_device(&id, command).await
-        }
-    };
+    let result = send_to_selector(bt_manager.get_ref(), &device_selector, command).await;
     
     let execution_time_ms = start_time.elapsed().as_millis() as u64;
     
     match result {
-        Ok(_) => {
+        Ok(report) if report.all_succeeded() => {
             info!("Draw command executed in {}ms", execution_time_ms);
             HttpResponse::Ok().json(serde_json::json!({
                 "status": "success",
-                "execution_time_ms": execution_time_ms
+                "execution_time_ms": execution_time_ms,
+                "devices": report
             }))
         }
+        Ok(report) => {
+            error!("Draw command failed on {} device(s)", report.failed().len());
+            send_report_error(&report, None, "")
+        }
         Err(e) => {
             error!("Failed to execute draw command: {}", e);
             HttpResponse::InternalServerError().json(ApiResponse::<()>::error(ApiError {
@@ -211,8 +298,8 @@ fn convert_draw_command(draw_cmd: &v2::DrawCommand) -> Result<Command> {
     match draw_cmd {
         v2::DrawCommand::Text { x, y, text, color, size, .. } => {
             Ok(Command::Text {
-                x: (*x).clamp(0, 255) as u8,
-                y: (*y).clamp(0, 255) as u8,
+                x: (*x).clamp(0, u16::MAX as i32) as u16,
+                y: (*y).clamp(0, u16::MAX as i32) as u16,
                 size: size.to_size(),
                 color: color.to_rgb(),
                 text: text.clone(),
@@ -225,29 +312,29 @@ fn convert_draw_command(draw_cmd: &v2::DrawCommand) -> Result<Command> {
         }
         v2::DrawCommand::Line { x1, y1, x2, y2, color, width } => {
             Ok(Command::Line {
-                x1: (*x1).clamp(0, 255) as u8,
-                y1: (*y1).clamp(0, 255) as u8,
-                x2: (*x2).clamp(0, 255) as u8,
-                y2: (*y2).clamp(0, 255) as u8,
+                x1: (*x1).clamp(0, u16::MAX as i32) as u16,
+                y1: (*y1).clamp(0, u16::MAX as i32) as u16,
+                x2: (*x2).clamp(0, u16::MAX as i32) as u16,
+                y2: (*y2).clamp(0, u16::MAX as i32) as u16,
                 width: *width,
                 color: color.to_rgb(),
             })
         }
         v2::DrawCommand::Rect { x, y, width, height, color, filled } => {
             Ok(Command::Rect {
-                x: (*x).clamp(0, 255) as u8,
-                y: (*y).clamp(0, 255) as u8,
-                width: (*width).clamp(0, 255) as u8,
-                height: (*height).clamp(0, 255) as u8,
+                x: (*x).clamp(0, u16::MAX as i32) as u16,
+                y: (*y).clamp(0, u16::MAX as i32) as u16,
+                width: (*width).clamp(0, u16::MAX as u32) as u16,
+                height: (*height).clamp(0, u16::MAX as u32) as u16,
                 fill: *filled,
                 color: color.to_rgb(),
             })
         }
         v2::DrawCommand::Circle { x, y, radius, color, filled } => {
             Ok(Command::Circle {
-                x: (*x).clamp(0, 255) as u8,
-                y: (*y).clamp(0, 255) as u8,
-                radius: (*radius).clamp(0, 255) as u8,
+                x: (*x).clamp(0, u16::MAX as i32) as u16,
+                y: (*y).clamp(0, u16::MAX as i32) as u16,
+                radius: (*radius).clamp(0, u16::MAX as u32) as u16,
                 color: color.to_rgb(),
                 filled: *filled,
             })
@@ -258,10 +345,10 @@ fn convert_draw_command(draw_cmd: &v2::DrawCommand) -> Result<Command> {
                 .map_err(|e| NotifError::InvalidParameter(format!("Invalid base64 image: {}", e)))?;
             
             Ok(Command::Image {
-                x: (*x).clamp(0, 255) as u8,
-                y: (*y).clamp(0, 255) as u8,
-                width: width.unwrap_or(128).clamp(1, 255) as u8,
-                height: height.unwrap_or(128).clamp(1, 255) as u8,
+                x: (*x).clamp(0, u16::MAX as i32) as u16,
+                y: (*y).clamp(0, u16::MAX as i32) as u16,
+                width: width.unwrap_or(128).clamp(1, u16::MAX as u32) as u16,
+                height: height.unwrap_or(128).clamp(1, u16::MAX as u32) as u16,
                 format: 1, // デフォルトでRawRgb形式
                 data: image_data,
             })
@@ -270,8 +357,8 @@ fn convert_draw_command(draw_cmd: &v2::DrawCommand) -> Result<Command> {
             // 絵文字文字列を最初の文字のUnicodeコードポイントに変換
             let code = emoji.chars().next().unwrap_or('\0') as u32;
             Ok(Command::Emoji {
-                x: (*x).clamp(0, 255) as u8,
-                y: (*y).clamp(0, 255) as u8,
+                x: (*x).clamp(0, u16::MAX as i32) as u16,
+                y: (*y).clamp(0, u16::MAX as i32) as u16,
                 size: *size,
                 code,
             })
@@ -326,6 +413,7 @@ pub async fn process_v2_health<M: BluetoothManager>(
             adapter: None, // プラットフォーム固有
             devices_connected: stats.connected_devices,
             devices_available: stats.total_devices,
+            devices: stats.devices,
         },
         memory: memory_info,
         api: v2::ApiStatistics {
@@ -333,12 +421,248 @@ pub async fn process_v2_health<M: BluetoothManager>(
             requests_per_minute: calculate_rpm(stats.total_commands_sent, stats.uptime_seconds),
             errors_total: stats.total_errors,
             average_response_time_ms: stats.average_response_time_ms,
+            bytes_sent: stats.bytes_sent,
+            latency_ms: stats.latency_ms,
+            by_command: stats.by_command,
         },
     };
     
     HttpResponse::Ok().json(ApiResponse::success(response))
 }
 
+/// v2 /api/devices/{device}/settings ハンドラーの共通処理
+pub async fn process_v2_device_settings<M: BluetoothManager>(
+    device: String,
+    request: DeviceSettings,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    info!("Processing v2 device settings request: device={}, {:?}", device, request);
+    
+    let settings = match request.into_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }));
+        }
+    };
+    
+    let device_names = match resolve_selector(bt_manager.get_ref(), &v2::DeviceSelector::parse(Some(device))).await {
+        Ok(device_names) => device_names,
+        Err(e) => {
+            return HttpResponse::NotFound().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }));
+        }
+    };
+    
+    for device_name in &device_names {
+        if let Err(e) = bt_manager.apply_settings_to_device(device_name, settings.clone()).await {
+            error!("Failed to apply settings to {}: {}", device_name, e);
+            let mut response = match e {
+                NotifError::DeviceNotFound(_) => HttpResponse::NotFound(),
+                NotifError::NotImplemented(_) => HttpResponse::NotImplemented(),
+                _ => HttpResponse::InternalServerError(),
+            };
+            return response.json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }));
+        }
+    }
+    
+    HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "devices": device_names,
+        "applied": settings.len()
+    })))
+}
+
+/// v2 /api/devices/{device}/screen.png ハンドラーの共通処理
+/// 
+/// 送信済みコマンドから描画した、デバイスに表示中の画面をPNGで返す
+pub async fn process_v2_device_screen<M: BluetoothManager>(
+    device: String,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    debug!("Processing v2 device screen request: device={}", device);
+    
+    let not_found = |message: String| HttpResponse::NotFound().json(ApiResponse::<()>::error(ApiError {
+        code: "DEVICE_NOT_FOUND".to_string(),
+        message,
+        details: None,
+    }));
+    
+    let selector = v2::DeviceSelector::parse(Some(device));
+    if let v2::DeviceSelector::All(_) | v2::DeviceSelector::Group(_) = selector {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(ApiError {
+            code: "INVALID_PARAMETER".to_string(),
+            message: "Specify a single device for the screen".to_string(),
+            details: None,
+        }));
+    }
+    let device_name = match resolve_selector(bt_manager.get_ref(), &selector).await {
+        Ok(device_names) => device_names.into_iter().next().unwrap_or_default(),
+        Err(e) => return not_found(e.to_string()),
+    };
+    
+    let Some(screen) = bt_manager.get_screen(&device_name).await else {
+        return not_found(format!("No screen for device {}", device_name));
+    };
+    
+    match screen.to_png() {
+        Ok(png) => HttpResponse::Ok()
+            .content_type("image/png")
+            .insert_header(("Cache-Control", "no-store"))
+            .body(png),
+        Err(e) => {
+            error!("Failed to encode screen of {}: {}", device_name, e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }))
+        }
+    }
+}
+
+/// v2 /api/devices/{device}/assignment ハンドラーの共通処理
+/// 
+/// デバイスの番号・エイリアスを固定して状態ファイルに保存する（未接続のデバイスは名前で指定）
+pub async fn process_v2_device_assignment<M: BluetoothManager>(
+    device: String,
+    request: DevicePin,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    info!("Processing v2 device assignment request: device={}, {:?}", device, request);
+    
+    let selector = v2::DeviceSelector::parse(Some(device));
+    if let v2::DeviceSelector::All(_) | v2::DeviceSelector::Group(_) = selector {
+        return HttpResponse::BadRequest().json(ApiResponse::<()>::error(ApiError {
+            code: "INVALID_PARAMETER".to_string(),
+            message: "Specify a single device to assign".to_string(),
+            details: None,
+        }));
+    }
+    let device_name = match resolve_selector(bt_manager.get_ref(), &selector).await {
+        Ok(device_names) => device_names.into_iter().next().unwrap_or_default(),
+        Err(e) => {
+            return HttpResponse::NotFound().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }));
+        }
+    };
+    
+    match bt_manager.pin_device(&device_name, request).await {
+        Ok(assignment) => HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+            "device": device_name,
+            "assignment": assignment,
+        }))),
+        Err(e) => {
+            error!("Failed to assign {}: {}", device_name, e);
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }))
+        }
+    }
+}
+
+/// v2 GET /api/groups ハンドラーの共通処理
+/// 
+/// グループごとに設定したメンバーと、解決したデバイス名・接続状態を返す
+pub async fn process_v2_groups<M: BluetoothManager>(
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    debug!("Processing v2 groups request");
+    
+    let connected: Vec<String> = bt_manager.list_connected_devices().await
+        .into_iter()
+        .filter(|info| info.connected)
+        .map(|info| info.name)
+        .collect();
+    
+    let mut groups = serde_json::Map::new();
+    for (group, members) in bt_manager.list_groups().await {
+        let devices = bt_manager.group_members(&group).await.unwrap_or_default();
+        let online = devices.iter().filter(|name| connected.contains(name)).count();
+        groups.insert(group, serde_json::json!({
+            "members": members,
+            "devices": devices,
+            "connected": online,
+        }));
+    }
+    
+    HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+        "total": groups.len(),
+        "groups": groups,
+    })))
+}
+
+/// v2 PUT /api/groups/{group} ハンドラーの共通処理
+/// 
+/// グループのメンバーを置き換えて状態ファイルに保存する（空のメンバーで削除）
+pub async fn process_v2_group_update<M: BluetoothManager>(
+    group: String,
+    request: v2::GroupRequest,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    info!("Processing v2 group update request: group={}, {:?}", group, request);
+    
+    match bt_manager.set_group(&group, request.members).await {
+        Ok(()) => HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+            "group": group,
+            "devices": bt_manager.group_members(&group).await.unwrap_or_default(),
+        }))),
+        Err(e) => {
+            error!("Failed to update group {}: {}", group, e);
+            HttpResponse::BadRequest().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }))
+        }
+    }
+}
+
+/// v2 DELETE /api/groups/{group} ハンドラーの共通処理
+pub async fn process_v2_group_delete<M: BluetoothManager>(
+    group: String,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    info!("Processing v2 group delete request: group={}", group);
+    
+    if bt_manager.group_members(&group).await.is_none() {
+        return HttpResponse::NotFound().json(ApiResponse::<()>::error(ApiError {
+            code: "DEVICE_NOT_FOUND".to_string(),
+            message: format!("Group {} not found", group),
+            details: None,
+        }));
+    }
+    
+    match bt_manager.set_group(&group, Vec::new()).await {
+        Ok(()) => HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({
+            "group": group,
+            "deleted": true,
+        }))),
+        Err(e) => {
+            error!("Failed to delete group {}: {}", group, e);
+            HttpResponse::InternalServerError().json(ApiResponse::<()>::error(ApiError {
+                code: e.error_code().to_string(),
+                message: e.to_string(),
+                details: None,
+            }))
+        }
+    }
+}
+
 /// v2 /api/batch ハンドラーの共通処理
 pub async fn process_v2_batch<M: BluetoothManager + 'static>(
     request: v2::BatchRequest,
@@ -418,7 +742,7 @@ pub async fn process_v2_batch<M: BluetoothManager + 'static>(
 }
 
 /// v1 API用のテキストコマンドを構築（v2互換の折り返し処理付き）
-fn build_v1_text_commands(text: &str, size: Size, color: RGB) -> Result<Vec<Command>> {
+fn build_v1_text_commands(text: &str, size: Size, color: RGB, grid: (u8, u8)) -> Result<Vec<Command>> {
     use crate::text::{parse_text_with_emoji, TextSegment};
     
     let mut commands = Vec::new();
@@ -427,19 +751,15 @@ fn build_v1_text_commands(text: &str, size: Size, color: RGB) -> Result<Vec<Comm
     let text = text.replace("\\n", "\n");
     let lines: Vec<&str> = text.split('\n').collect();
     
-    // v2互換のグリッド座標系（32x32）での文字サイズを計算
-    let (ascii_width_grids, y_spacing_grids) = match size {
-        Size::Small => (2, 4),   // サイズ1: ASCII 2グリッド、漢字 4グリッド、4グリッド行間
-        Size::Medium => (3, 6),  // サイズ2: ASCII 3グリッド、漢字 6グリッド、6グリッド行間
-        Size::Large => (4, 8),   // サイズ3: ASCII 4グリッド、漢字 8グリッド、8グリッド行間
-        Size::XLarge => (5, 10), // サイズ4: ASCII 5グリッド、漢字 10グリッド、10グリッド行間（40px）
-    };
+    // v2互換のグリッド座標系（1グリッド4ピクセル、128pxで32x32）での文字サイズを計算
+    let (grid_width, grid_height) = grid;
+    let (ascii_width_grids, y_spacing_grids) = (size.char_grids(), size.line_grids());
     
     let mut current_y = 0u8;
     
     for line in lines.iter() {
-        // 画面外チェック（32グリッドの高さ制限）
-        if current_y + y_spacing_grids > 32 {
+        // 画面外チェック（グリッドの高さ制限）
+        if current_y + y_spacing_grids > grid_height {
             break;
         }
         
@@ -469,13 +789,13 @@ fn build_v1_text_commands(text: &str, size: Size, color: RGB) -> Result<Vec<Comm
                                 ascii_width_grids * 2  // 全角文字はASCIIの2倍
                             };
                             
-                            // 現在の行に収まらない場合（32グリッド幅制限）
-                            if current_x + char_width > 32 {
+                            // 現在の行に収まらない場合（グリッド幅制限）
+                            if current_x + char_width > grid_width {
                                 // これまでのテキストを送信
                                 if !line_text.is_empty() {
                                     commands.push(Command::Text {
-                                        x: line_start_x,
-                                        y: current_y,
+                                        x: line_start_x as u16,
+                                        y: current_y as u16,
                                         size,
                                         color,
                                         text: line_text.clone(),
@@ -488,7 +808,7 @@ fn build_v1_text_commands(text: &str, size: Size, color: RGB) -> Result<Vec<Comm
                                 current_y += y_spacing_grids;
                                 line_start_x = 0;
                                 
-                                if current_y + y_spacing_grids > 32 {
+                                if current_y + y_spacing_grids > grid_height {
                                     return Ok(commands);  // 画面外なので終了
                                 }
                                 
@@ -503,10 +823,10 @@ fn build_v1_text_commands(text: &str, size: Size, color: RGB) -> Result<Vec<Comm
                         }
                         
                         // 残りのテキストを送信
-                        if !line_text.is_empty() && current_y + y_spacing_grids <= 32 {
+                        if !line_text.is_empty() && current_y + y_spacing_grids <= grid_height {
                             commands.push(Command::Text {
-                                x: line_start_x,
-                                y: current_y,
+                                x: line_start_x as u16,
+                                y: current_y as u16,
                                 size,
                                 color,
                                 text: line_text,
@@ -519,34 +839,29 @@ fn build_v1_text_commands(text: &str, size: Size, color: RGB) -> Result<Vec<Comm
                     let emoji_width = ascii_width_grids * 2;
                     
                     // 絵文字の高さ（フォントサイズに応じた実際の高さ）
-                    let emoji_height = match size {
-                        Size::Small => 4,   // 1行分の高さ
-                        Size::Medium => 6,  // 1行分の高さ
-                        Size::Large => 8,   // 1行分の高さ
-                        Size::XLarge => 10, // 1行分の高さ（40px対応）
-                    };
+                    let emoji_height = y_spacing_grids;  // 1行分の高さ
                     
                     info!("Processing emoji U+{:04X} at current_x={}, current_y={}, emoji_width={}, emoji_height={}", 
                           code, current_x, current_y, emoji_width, emoji_height);
                     
                     // 絵文字が現在の行に収まらない場合は改行
-                    if current_x + emoji_width > 32 && current_x > 0 {
+                    if current_x + emoji_width > grid_width && current_x > 0 {
                         info!("Emoji doesn't fit in current line, wrapping to next line");
                         current_x = 0;
                         current_y += y_spacing_grids;
                         
-                        if current_y + emoji_height > 32 {
-                            info!("Emoji Y coordinate {} + {} > 32, skipping", current_y, emoji_height);
+                        if current_y + emoji_height > grid_height {
+                            info!("Emoji Y coordinate {} + {} > {}, skipping", current_y, emoji_height, grid_height);
                             break;
                         }
                     }
                     
                     // 絵文字が画面内に収まるかチェック（修正版）
-                    if current_x + emoji_width <= 32 && current_y + emoji_height <= 32 {
+                    if current_x + emoji_width <= grid_width && current_y + emoji_height <= grid_height {
                         info!("Emoji fits in screen, creating command at ({},{})", current_x, current_y);
                         commands.push(Command::Emoji {
-                            x: current_x,
-                            y: current_y,
+                            x: current_x as u16,
+                            y: current_y as u16,
                             size: size.to_byte(),
                             code,
                         });
@@ -660,6 +975,12 @@ pub async fn process_v2_draw_query<M: BluetoothManager>(
         }));
     }
     
+    // 送信先の画面サイズに合わせたグリッドで領域を制限
+    let device_selector = v2::DeviceSelector::parse(request.device.clone());
+    let capabilities = selector_capabilities(bt_manager.get_ref(), &device_selector).await;
+    let (grid_width, grid_height) = capabilities.grid_size();
+    let (grid_width, grid_height) = (grid_width as i32, grid_height as i32);
+    
     // コマンドリストを構築
     let mut commands = Vec::new();
     
@@ -688,7 +1009,7 @@ pub async fn process_v2_draw_query<M: BluetoothManager>(
         
         let (row1, col1, row2, col2) = (coords[0], coords[1], coords[2], coords[3]);
         
-        // グリッド座標のまま使用（v2 APIは32x32グリッド座標系）
+        // グリッド座標のまま使用（v2 APIのグリッド座標系、128pxの画面で32x32）
         let x1 = col1;
         let y1 = row1;
         let width = col2 - col1 + 1;
@@ -697,10 +1018,10 @@ pub async fn process_v2_draw_query<M: BluetoothManager>(
         // 背景色の描画
         if let Some(ref bg_color) = region.bg {
             let color = parse_color_name(bg_color);
-            let rect_x = x1.max(0).min(31) as u8;
-            let rect_y = y1.max(0).min(31) as u8;
-            let rect_width = width.max(1).min(32) as u8;
-            let rect_height = height.max(1).min(32) as u8;
+            let rect_x = x1.clamp(0, grid_width - 1) as u16;
+            let rect_y = y1.clamp(0, grid_height - 1) as u16;
+            let rect_width = width.clamp(1, grid_width) as u16;
+            let rect_height = height.clamp(1, grid_height) as u16;
             
             info!("Creating Rect command: x={}, y={}, width={}, height={}, color=({},{},{}), fill=true",
                   rect_x, rect_y, rect_width, rect_height, color.r, color.g, color.b);
@@ -742,13 +1063,7 @@ pub async fn process_v2_draw_query<M: BluetoothManager>(
             let wrapped_lines = wrap_text_with_emoji(text, text_area_width, font_size);
             
             // 行の高さ（グリッド単位）
-            let line_height = match font_size {
-                1 => 4,   // size=1: 4グリッド
-                2 => 6,   // size=2: 6グリッド
-                3 => 8,   // size=3: 8グリッド
-                4 => 10,  // size=4: 10グリッド
-                _ => 6,   // デフォルト
-            };
+            let line_height = size.line_grids() as i32;
             
             // 各行を描画
             for (line_index, line) in wrapped_lines.iter().enumerate() {
@@ -777,9 +1092,7 @@ pub async fn process_v2_draw_query<M: BluetoothManager>(
         }
     }
     
-    // デバイス選択とコマンド送信
-    let device_selector = v2::DeviceSelector::parse(request.device);
-    
+    // コマンド送信
     info!("Sending {} commands to device as batch", commands.len());
     for (i, cmd) in commands.iter().enumerate() {
         info!("Command {}: {:?}", i, cmd);
@@ -791,24 +1104,18 @@ pub async fn process_v2_draw_query<M: BluetoothManager>(
     let start_time = Instant::now();
     
     // Batchコマンドを1回で送信
-    let result = match &device_selector {
-        v2::DeviceSelector::All(_) => {
-            bt_manager.send_command_to_all(batch_command).await
-        }
-        v2::DeviceSelector::Number(num) => {
-            bt_manager.send_command_by_number(*num, batch_command).await
-        }
-        v2::DeviceSelector::Id(id) => {
-            bt_manager.send_command_to_device(id, batch_command).await
-        }
-    };
+    let result = send_to_selector(bt_manager.get_ref(), &device_selector, batch_command).await;
     
     let execution_time_ms = start_time.elapsed().as_millis() as u64;
     
     match result {
-        Ok(_) => {
+        Ok(report) if report.all_succeeded() => {
             info!("Draw batch command executed successfully in {}ms", execution_time_ms);
-            HttpResponse::Ok().json(ApiResponse::<()>::success(()))
+            HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({ "devices": report })))
+        }
+        Ok(report) => {
+            warn!("Draw batch command failed on {} device(s)", report.failed().len());
+            send_report_error(&report, Some("COMMAND_FAILED"), "Failed to execute draw command: ")
         }
         Err(e) => {
             warn!("Draw batch command failed: {}", e);
@@ -845,6 +1152,12 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
         }));
     }
     
+    // 送信先の画面サイズに合わせたグリッドで領域を制限
+    let device_selector = v2::DeviceSelector::parse(request.device.clone());
+    let capabilities = selector_capabilities(bt_manager.get_ref(), &device_selector).await;
+    let (grid_width, grid_height) = capabilities.grid_size();
+    let (grid_width, grid_height) = (grid_width as i32, grid_height as i32);
+    
     // コマンドリストを構築
     let mut commands = Vec::new();
     
@@ -873,7 +1186,7 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
         
         let (row1, col1, row2, col2) = (coords[0], coords[1], coords[2], coords[3]);
         
-        // グリッド座標のまま使用（v2 APIは32x32グリッド座標系）
+        // グリッド座標のまま使用（v2 APIのグリッド座標系、128pxの画面で32x32）
         let x1 = col1;
         let y1 = row1;
         let width = col2 - col1 + 1;
@@ -882,10 +1195,10 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
         // 背景色の描画
         if let Some(ref bg_color) = region.bg {
             let color = parse_color_name(bg_color);
-            let rect_x = x1.max(0).min(31) as u8;
-            let rect_y = y1.max(0).min(31) as u8;
-            let rect_width = width.max(1).min(32) as u8;
-            let rect_height = height.max(1).min(32) as u8;
+            let rect_x = x1.clamp(0, grid_width - 1) as u16;
+            let rect_y = y1.clamp(0, grid_height - 1) as u16;
+            let rect_width = width.clamp(1, grid_width) as u16;
+            let rect_height = height.clamp(1, grid_height) as u16;
             
             info!("Creating Rect command: x={}, y={}, width={}, height={}, color=({},{},{}), fill=true",
                   rect_x, rect_y, rect_width, rect_height, color.r, color.g, color.b);
@@ -927,13 +1240,7 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
             let wrapped_lines = wrap_text_with_emoji(text, text_area_width, font_size);
             
             // 行の高さ（グリッド単位）
-            let line_height = match font_size {
-                1 => 4,   // size=1: 4グリッド
-                2 => 6,   // size=2: 6グリッド
-                3 => 8,   // size=3: 8グリッド
-                4 => 10,  // size=4: 10グリッド
-                _ => 6,   // デフォルト
-            };
+            let line_height = size.line_grids() as i32;
             
             // 各行を描画
             for (line_index, line) in wrapped_lines.iter().enumerate() {
@@ -962,9 +1269,7 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
         }
     }
     
-    // デバイス選択とコマンド送信
-    let device_selector = v2::DeviceSelector::parse(request.device);
-    
+    // コマンド送信
     info!("Sending {} commands to device as batch", commands.len());
     for (i, cmd) in commands.iter().enumerate() {
         info!("Command {}: {:?}", i, cmd);
@@ -976,24 +1281,18 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
     let start_time = Instant::now();
     
     // Batchコマンドを1回で送信
-    let result = match &device_selector {
-        v2::DeviceSelector::All(_) => {
-            bt_manager.send_command_to_all(batch_command).await
-        }
-        v2::DeviceSelector::Number(num) => {
-            bt_manager.send_command_by_number(*num, batch_command).await
-        }
-        v2::DeviceSelector::Id(id) => {
-            bt_manager.send_command_to_device(id, batch_command).await
-        }
-    };
+    let result = send_to_selector(bt_manager.get_ref(), &device_selector, batch_command).await;
     
     let execution_time_ms = start_time.elapsed().as_millis() as u64;
     
     match result {
-        Ok(_) => {
+        Ok(report) if report.all_succeeded() => {
             info!("Draw batch command executed successfully in {}ms", execution_time_ms);
-            HttpResponse::Ok().json(ApiResponse::<()>::success(()))
+            HttpResponse::Ok().json(ApiResponse::success(serde_json::json!({ "devices": report })))
+        }
+        Ok(report) => {
+            warn!("Draw batch command failed on {} device(s)", report.failed().len());
+            send_report_error(&report, Some("COMMAND_FAILED"), "Failed to execute draw command: ")
         }
         Err(e) => {
             warn!("Draw batch command failed: {}", e);
@@ -1014,29 +1313,47 @@ pub async fn process_v2_draw_post<M: BluetoothManager>(
 #[cfg(feature = "http-endpoints")]
 #[derive(Debug, Clone, Deserialize)]
 pub struct ImageUploadParams {
+    /// 送信先（デバイス番号、0は全デバイス、`group:名前`、デバイス名・エイリアス）
     #[serde(default = "default_device")]
-    pub device: u8,
+    pub device: String,
     #[serde(default)]
-    pub x: u8,
+    pub x: u16,
     #[serde(default)]
-    pub y: u8,
+    pub y: u16,
     #[serde(default)]
     pub fit: FitMode,
+    /// シーケンス番号付きで送信し、欠落タイルのみ再送する
+    #[serde(default)]
+    pub sequenced: bool,
+    /// 減色後の色数（2/4/16/256）。指定時はインデックスカラーで転送
+    #[serde(default)]
+    pub colors: Option<PaletteSize>,
+}
+
+#[cfg(feature = "http-endpoints")]
+fn default_device() -> String {
+    "1".to_string()
 }
 
+/// 画像・アセットエンドポイントのデバイス指定を送信先セレクターに変換（0は全デバイス）
 #[cfg(feature = "http-endpoints")]
-fn default_device() -> u8 {
-    1
+fn image_selector(device: &str) -> v2::DeviceSelector {
+    match device.trim() {
+        "0" => v2::DeviceSelector::All("all".to_string()),
+        device => v2::DeviceSelector::parse(Some(device.to_string())),
+    }
 }
 
 #[cfg(feature = "http-endpoints")]
 impl Default for ImageUploadParams {
     fn default() -> Self {
         Self {
-            device: 1,
+            device: default_device(),
             x: 0,
             y: 0,
             fit: FitMode::Contain,
+            sequenced: false,
+            colors: None,
         }
     }
 }
@@ -1088,10 +1405,10 @@ fn split_image_to_tiles(
             }
             
             tiles.push(ImageTile {
-                x: start_x as u8,
-                y: start_y as u8,
-                width: actual_width as u8,
-                height: actual_height as u8,
+                x: start_x,
+                y: start_y,
+                width: actual_width,
+                height: actual_height,
                 rgb565_data: tile_data,
             });
         }
@@ -1105,21 +1422,102 @@ fn split_image_to_tiles(
     tiles
 }
 
-/// BLE制限対応: タイルを順次送信（v4のBluetooth実装をそのまま使用）
-/// テスト用: まず1タイルのみ送信して動作確認
+/// 1回の書き込みで送るタイルフレームの上限（BLE制限512バイトに対して安全側）
+#[cfg(feature = "http-endpoints")]
+const TILE_FRAME_LIMIT: usize = 500;
+
+/// 溜まったタイルを1フレーム（複数ならBatch）で送信し、デバイスごとの結果を返す
+/// `sent`はこのフレームを含めた送信済みタイル数
+#[cfg(feature = "http-endpoints")]
+async fn send_tile_frame<M: BluetoothManager>(
+    frame_tiles: Vec<Command>,
+    sent: usize,
+    total: usize,
+    device_names: &[String],
+    bt_manager: &M
+) -> SendReport {
+    let tile_count = frame_tiles.len();
+    let frame = Command::batched(frame_tiles);
+    
+    // 複数デバイスには並行して送信
+    let report = send_to_devices(bt_manager, device_names.to_vec(), frame).await;
+    
+    // 転送の進捗を通知（失敗したデバイスは除く）
+    for device_name in report.succeeded() {
+        bt_manager.publish_event(crate::bluetooth::NotifEvent::image_progress(device_name, sent, total));
+    }
+    
+    // タイル送信結果をログに記録（送信パターン調査用）
+    for (device_name, e) in report.failed() {
+        error!("タイル{}/{}送信失敗 ({}): {}", sent, total, device_name, e);
+    }
+    debug!("タイル{}/{}送信成功 ({}タイル/フレーム) to {:?}", sent, total, tile_count, report.succeeded());
+    
+    // BLE安定性のためのフレーム間待機（10ms）
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    report
+}
+
+/// タイルを1回の書き込み（TILE_FRAME_LIMIT以下）ごとにまとめる
+/// 
+/// RLE・インデックスカラーを展開できないデバイスにはマネージャーが生のRGB565に戻して送るため、
+/// 送信先の機能で変換した後のサイズで見積もる
+#[cfg(feature = "http-endpoints")]
+fn pack_tile_frames(
+    tile_commands: &[Command],
+    capabilities: &DeviceCapabilities
+) -> std::result::Result<Vec<Vec<Command>>, NotifError> {
+    let mut frames = Vec::new();
+    let mut pending_frame = Vec::new();
+    let mut pending_bytes = 0;
+    
+    for (index, image_command) in tile_commands.iter().enumerate() {
+        // BLE制限確認（安全のため500バイト以下で確認）
+        let total_size = capabilities.adapt_command(image_command.clone())?.encode()?.len();
+        if total_size > TILE_FRAME_LIMIT {
+            warn!("タイル{}のデータサイズ{}バイトがBLE制限を超過、送信中止", index + 1, total_size);
+            return Err(NotifError::Bluetooth(format!("タイルサイズ{}バイトがBLE制限を超過", total_size)));
+        }
+        
+        // まとめると制限を超える場合は、溜まっているタイルを先にフレームにする（Batchヘッダー4バイト）
+        if !pending_frame.is_empty() && 4 + pending_bytes + total_size > TILE_FRAME_LIMIT {
+            frames.push(std::mem::take(&mut pending_frame));
+            pending_bytes = 0;
+        }
+        pending_bytes += total_size;
+        pending_frame.push(image_command.clone());
+    }
+    
+    if !pending_frame.is_empty() {
+        frames.push(pending_frame);
+    }
+    Ok(frames)
+}
+
+/// BLE制限対応: タイルを順次送信し、デバイスごとの結果を返す（v4のBluetooth実装をそのまま使用）
+/// 
+/// 途中で失敗したデバイスには以降のタイルを送らず、他のデバイスへの送信は続ける
 #[cfg(feature = "http-endpoints")]
 async fn send_image_tiles<M: BluetoothManager>(
     tiles: Vec<ImageTile>,
-    device: u8,
-    base_x: u8,
-    base_y: u8,
+    selector: &v2::DeviceSelector,
+    capabilities: &DeviceCapabilities,
+    base_x: u16,
+    base_y: u16,
+    sequenced: bool,
     bt_manager: &M
-) -> std::result::Result<(), NotifError> {
+) -> std::result::Result<SendReport, NotifError> {
+    // 送信先を先に確定（グループは各メンバーへ送る）
+    let device_names = resolve_selector(bt_manager, selector).await?;
+    if device_names.is_empty() {
+        return Err(NotifError::DeviceNotConnected("No devices connected".to_string()));
+    }
+    
     let total_tiles = tiles.len();
     // v5修正: 全128タイル送信（16x8ピクセル×128 = 128x128ピクセル全体）
     let tiles_to_send = total_tiles;  // 全タイル送信
-    info!("タイル送信開始: {}個のタイル、デバイス={}, 開始位置=({},{}) - {}タイル送信", 
-          total_tiles, device, base_x, base_y, tiles_to_send);
+    info!("タイル送信開始: {}個のタイル、デバイス={:?}, 開始位置=({},{}) - {}タイル送信", 
+          total_tiles, device_names, base_x, base_y, tiles_to_send);
     
     // 送信速度測定用のタイマー開始
     let transmission_start = std::time::Instant::now();
@@ -1129,80 +1527,79 @@ async fn send_image_tiles<M: BluetoothManager>(
     
     // v5修正: 全タイル送信（256バイト×128）
     for (index, tile) in tiles.iter().take(tiles_to_send).enumerate() {
-        let tile_bytes = crate::image::rgb565::rgb565_to_bytes(&tile.rgb565_data);
+        // 圧縮で小さくなる場合はRLE、そうでなければ生のRGB565
+        let (format, tile_bytes) = crate::image::rgb565::encode_tile(&tile.rgb565_data);
         let tile_data_size = tile_bytes.len();
-        let tile_bytes_len = tile_bytes.len();  // 長さを先に保存
+        
+        let (x, y) = (base_x.saturating_add(tile.x), base_y.saturating_add(tile.y));
         
         // デバッグ: 最初のタイルの詳細情報
         if index == 0 {
             info!("最初のタイル送信開始: タイル座標=({},{}), base座標=({},{}), 実際の送信座標=({},{})", 
-                  tile.x, tile.y, base_x, base_y, base_x + tile.x, base_y + tile.y);
+                  tile.x, tile.y, base_x, base_y, x, y);
         }
         
-        debug!("タイル送信 {}/{}: 位置=({},{}), サイズ={}x{}, データサイズ={}バイト", 
+        debug!("タイル送信 {}/{}: 位置=({},{}), サイズ={}x{}, フォーマット={}, データサイズ={}バイト", 
                index + 1, tiles_to_send, 
-               base_x + tile.x, base_y + tile.y, 
-               tile.width, tile.height, tile_data_size);
-        
-        // BLE制限確認（安全のため500バイト以下で確認）
-        let total_size = tile_data_size + 8; // データ + Command::Imageヘッダー
-        if total_size > 500 {
-            warn!("タイル{}のデータサイズ{}バイトがBLE制限を超過、送信中止", index + 1, total_size);
-            return Err(NotifError::Bluetooth(format!("タイルサイズ{}バイトがBLE制限を超過", total_size)));
-        }
+               x, y, 
+               tile.width, tile.height, format, tile_data_size);
         
         // v5修正: 各タイルの正しい位置に表示
         let image_command = crate::protocol::Command::Image {
-            x: base_x + tile.x,
-            y: base_y + tile.y,
+            x,
+            y,
             width: tile.width,
             height: tile.height,
-            format: 2, // RGB565 - AtomS3ファームウェアではIMG_RAW_RGB565=0x02
+            format,
             data: tile_bytes,
         };
         
         // v5追加: 再接続用にコマンドを保存
-        tile_commands.push(image_command.clone());
-        
-        // v4のBluetooth実装をそのまま使用（変更禁止）
-        let result = if device == 0 {
-            bt_manager.send_command_to_all(image_command).await
-        } else {
-            bt_manager.send_command_by_number(device as usize, image_command).await
-        };
-        
-        match result {
-            Ok(_) => {
-                // タイル送信成功をログに記録（送信パターン調査用）
-                if index == 0 || index == tiles_to_send - 1 || index % 10 == 0 {
-                    info!("タイル{}/{}送信成功 ({}バイト) to device {}", 
-                         index + 1, tiles_to_send, tile_bytes_len + 8, device);
-                } else {
-                    debug!("タイル{}送信成功", index + 1);
+        tile_commands.push(image_command);
+    }
+    
+    // 圧縮で小さくなったタイルは1回の書き込みにまとめて送る（シーケンス送信時はサイズ確認のみ）
+    let frames = pack_tile_frames(&tile_commands, capabilities)?;
+    
+    let mut report = SendReport::default();
+    if sequenced {
+        // シーケンス番号付き送信: 欠落したタイルだけを再送
+        for device_name in &device_names {
+            let result = bt_manager.send_sequenced_to_device(device_name, tile_commands.clone()).await;
+            match &result {
+                Ok(_) => {
+                    info!("シーケンス送信完了: {}タイル to {}", tile_commands.len(), device_name);
+                    bt_manager.publish_event(crate::bluetooth::NotifEvent::image_progress(device_name, tiles_to_send, tiles_to_send));
                 }
+                Err(e) => error!("シーケンス送信失敗 ({}): {}", device_name, e),
             }
-            Err(e) => {
-                error!("タイル{}送信失敗: {}", index + 1, e);
-                return Err(e);
+            report.results.insert(device_name.clone(), result);
+        }
+    } else {
+        // 失敗したデバイスは送信先から外し、残りのデバイスへ送り続ける
+        let mut remaining = device_names.clone();
+        let mut sent = 0;
+        for frame_tiles in frames {
+            if remaining.is_empty() {
+                break;
+            }
+            sent += frame_tiles.len();
+            let frame_report = send_tile_frame(frame_tiles, sent, tiles_to_send, &remaining, bt_manager).await;
+            for (device_name, result) in frame_report.results {
+                if result.is_err() {
+                    remaining.retain(|name| *name != device_name);
+                    report.results.insert(device_name, result);
+                }
             }
         }
-        
-        // BLE安定性のためのタイル間待機（10ms）
-        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        for device_name in remaining {
+            report.results.insert(device_name, Ok(()));
+        }
     }
     
-    // v5追加: 全タイル送信成功後、再接続用に保存
-    if device == 0 {
-        // 全デバイスの場合は各デバイスに保存
-        let devices = bt_manager.list_connected_devices().await;
-        for device_info in devices {
-            bt_manager.save_image_tiles(&device_info.name, tile_commands.clone()).await;
-        }
-    } else {
-        // 特定デバイスの場合はそのデバイスだけに保存
-        if let Some(device_name) = bt_manager.get_device_name_by_number(device as usize).await {
-            bt_manager.save_image_tiles(&device_name, tile_commands).await;
-        }
+    // v5追加: 全タイルを送信できたデバイスに、再接続用に保存
+    for device_name in report.succeeded() {
+        bt_manager.save_image_tiles(device_name, tile_commands.clone()).await;
     }
     
     // 送信時間を計測して速度を計算
@@ -1216,7 +1613,21 @@ async fn send_image_tiles<M: BluetoothManager>(
     
     info!("{}タイル送信完了: {}/{}タイル, 時間: {}ms, 速度: {:.1}タイル/秒", 
           tiles_to_send, tiles_to_send, total_tiles, elapsed_ms, tiles_per_sec);
-    Ok(())
+    Ok(report)
+}
+
+/// 一部のデバイスへの画像送信に失敗した場合のエラーレスポンス（デバイスごとの結果を含む）
+#[cfg(feature = "http-endpoints")]
+fn image_report_error(report: &SendReport) -> HttpResponse {
+    let error = match report.failed().first() {
+        Some((device_id, e)) => format!("デバイスへの送信に失敗しました: {} ({})", e, device_id),
+        None => "デバイスへの送信に失敗しました".to_string(),
+    };
+    HttpResponse::InternalServerError().json(serde_json::json!({
+        "success": false,
+        "error": error,
+        "devices": report
+    }))
 }
 
 /// フォームアップロード型画像送信
@@ -1252,7 +1663,7 @@ pub async fn upload_image<M: BluetoothManager>(
             "device" => {
                 let data = read_field_data(&mut field).await?;
                 let device_str = String::from_utf8_lossy(&data);
-                params.device = device_str.trim().parse().unwrap_or(1);
+                params.device = device_str.trim().to_string();
                 debug!("Device parameter: {}", params.device);
             }
             "x" => {
@@ -1279,6 +1690,18 @@ pub async fn upload_image<M: BluetoothManager>(
                 };
                 debug!("Fit mode: {:?}", params.fit);
             }
+            "sequenced" => {
+                let data = read_field_data(&mut field).await?;
+                let sequenced_str = String::from_utf8_lossy(&data);
+                params.sequenced = matches!(sequenced_str.trim(), "true" | "1");
+                debug!("Sequenced transfer: {}", params.sequenced);
+            }
+            "colors" => {
+                let data = read_field_data(&mut field).await?;
+                let colors_str = String::from_utf8_lossy(&data);
+                params.colors = Some(colors_str.parse().map_err(actix_web::error::ErrorBadRequest)?);
+                debug!("Palette size: {:?}", params.colors);
+            }
             _ => {
                 debug!("Unknown field ignored: {}", field_name);
             }
@@ -1290,12 +1713,17 @@ pub async fn upload_image<M: BluetoothManager>(
         actix_web::error::ErrorBadRequest("No image file provided")
     })?;
     
-    info!("Starting image processing: {} bytes, target 128x128, fit mode: {:?}", 
-          image_data.len(), params.fit);
+    // 送信先の画面サイズ・色深度に合わせる（全デバイス・グループは共通の範囲）
+    let selector = image_selector(&params.device);
+    let capabilities = selector_capabilities(bt_manager.get_ref(), &selector).await;
+    let target_size = capabilities.image_size();
+    
+    info!("Starting image processing: {} bytes, target {}x{}, fit mode: {:?}", 
+          image_data.len(), target_size.0, target_size.1, params.fit);
     
     // 画像処理
     let processor = ImageProcessor::new();
-    let processed = match processor.process_image(image_data, (128, 128), params.fit) {
+    let processed = match processor.process_image(image_data, target_size, params.fit) {
         Ok(processed) => {
             info!("Image processed successfully: {}x{} in {}ms", 
                   processed.width, processed.height, processed.processing_time_ms);
@@ -1310,6 +1738,12 @@ pub async fn upload_image<M: BluetoothManager>(
         }
     };
     
+    // 色数指定時、または低色深度のデバイスでは減色（タイルはインデックスカラーで転送される）
+    let processed = match params.colors.or(capabilities.palette_size()) {
+        Some(colors) => processor.quantize(processed, colors),
+        None => processed,
+    };
+    
     // BLE制限対応: 大きな画像をタイルに分割して送信
     let original_size = processed.rgb565_data.len() * 2; // 16bit = 2byte
     info!("BLE最適化開始: 元画像サイズ={}バイト、画像サイズ={}x{}", 
@@ -1332,16 +1766,18 @@ pub async fn upload_image<M: BluetoothManager>(
     let tiles_to_send = tiles.len();  // 全タイル送信
     let send_result = send_image_tiles(
         tiles.clone(),
-        params.device,
+        &selector,
+        &capabilities,
         params.x,
         params.y,
+        params.sequenced,
         bt_manager.get_ref()
     ).await;
     
     let total_time = start_time.elapsed().as_millis() as u64;
     
     match send_result {
-        Ok(_) => {
+        Ok(report) if report.all_succeeded() => {
             info!("画像タイル送信成功: {}/{}個のタイル、合計{}ms", tiles_to_send, tiles.len(), total_time);
             Ok(HttpResponse::Ok().json(serde_json::json!({
                 "success": true,
@@ -1363,10 +1799,15 @@ pub async fn upload_image<M: BluetoothManager>(
                         "max_tile_bytes": 16 * 8 * 2 + 8,  // 256バイト+ヘッダー
                         "ble_limit_compliant": true,
                         "transmission_time_ms": 10
-                    }
+                    },
+                    "devices": report
                 }
             })))
         }
+        Ok(report) => {
+            warn!("画像タイル送信が{}台のデバイスで失敗", report.failed().len());
+            Ok(image_report_error(&report))
+        }
         Err(e) => {
             error!("Failed to send image to device: {}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -1390,11 +1831,15 @@ pub async fn post_image<M: BluetoothManager>(
     info!("POST画像受信: サイズ={}バイト, デバイス={}, 位置=({},{})", 
           body.len(), query.device, query.x, query.y);
     
+    // 送信先の画面サイズ・色深度に合わせる（全デバイス・グループは共通の範囲）
+    let selector = image_selector(&query.device);
+    let capabilities = selector_capabilities(bt_manager.get_ref(), &selector).await;
+    
     // 画像処理
     let processor = crate::image::ImageProcessor::new();
     let processed = match processor.process_image(
         body.to_vec(),
-        (128, 128),  // AtomS3画面サイズ固定
+        capabilities.image_size(),
         query.fit,
     ) {
         Ok(img) => img,
@@ -1407,6 +1852,12 @@ pub async fn post_image<M: BluetoothManager>(
         }
     };
     
+    // 色数指定時、または低色深度のデバイスでは減色（タイルはインデックスカラーで転送される）
+    let processed = match query.colors.or(capabilities.palette_size()) {
+        Some(colors) => processor.quantize(processed, colors),
+        None => processed,
+    };
+    
     info!("画像処理完了: 処理後サイズ={}x{} ({:.1}ms)", 
           processed.width, processed.height, 
           processed.processing_time_ms);
@@ -1423,16 +1874,18 @@ pub async fn post_image<M: BluetoothManager>(
     let tiles_to_send = tiles.len();
     let send_result = send_image_tiles(
         tiles.clone(),
-        query.device,
+        &selector,
+        &capabilities,
         query.x,
         query.y,
+        query.sequenced,
         bt_manager.get_ref()
     ).await;
     
     let total_time = start_time.elapsed().as_millis() as u64;
     
     match send_result {
-        Ok(_) => {
+        Ok(report) if report.all_succeeded() => {
             info!("画像タイル送信成功: {}/{}個のタイル、合計{}ms", tiles_to_send, tiles.len(), total_time);
             HttpResponse::Ok().json(serde_json::json!({
                 "success": true,
@@ -1452,10 +1905,15 @@ pub async fn post_image<M: BluetoothManager>(
                         "tile_size": "16x8",
                         "max_tile_bytes": 16 * 8 * 2 + 8,
                         "ble_limit_compliant": true
-                    }
+                    },
+                    "devices": report
                 }
             }))
         }
+        Ok(report) => {
+            warn!("画像タイル送信が{}台のデバイスで失敗", report.failed().len());
+            image_report_error(&report)
+        }
         Err(e) => {
             error!("画像送信失敗: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
@@ -1466,6 +1924,139 @@ pub async fn post_image<M: BluetoothManager>(
     }
 }
 
+/// アセット登録クエリパラメータ
+#[cfg(feature = "http-endpoints")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetUploadParams {
+    #[serde(default = "default_asset_size")]
+    pub width: u8,
+    #[serde(default = "default_asset_size")]
+    pub height: u8,
+    #[serde(default)]
+    pub fit: FitMode,
+    /// 減色後の色数（2/4/16/256）
+    #[serde(default)]
+    pub colors: Option<PaletteSize>,
+}
+
+#[cfg(feature = "http-endpoints")]
+fn default_asset_size() -> u8 {
+    32
+}
+
+/// アセット描画クエリパラメータ
+#[cfg(feature = "http-endpoints")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetDrawParams {
+    /// 送信先（デバイス番号、0は全デバイス、`group:名前`、デバイス名・エイリアス）
+    #[serde(default = "default_device")]
+    pub device: String,
+    #[serde(default)]
+    pub x: u16,
+    #[serde(default)]
+    pub y: u16,
+}
+
+/// 画像をアセットとして登録
+/// POST /api/assets/{id}
+#[cfg(feature = "http-endpoints")]
+pub async fn register_asset<M: BluetoothManager>(
+    path: web::Path<u16>,
+    body: web::Bytes,
+    query: web::Query<AssetUploadParams>,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    let id = path.into_inner();
+    info!("アセット登録: id={}, サイズ={}バイト, {}x{}", id, body.len(), query.width, query.height);
+    
+    let processor = ImageProcessor::new();
+    let processed = match processor.process_image(
+        body.to_vec(),
+        (query.width.max(1) as u16, query.height.max(1) as u16),
+        query.fit,
+    ) {
+        Ok(img) => img,
+        Err(e) => {
+            error!("画像処理エラー: {}", e);
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "success": false,
+                "error": format!("画像処理に失敗しました: {}", e)
+            }));
+        }
+    };
+    
+    let processed = match query.colors {
+        Some(colors) => processor.quantize(processed, colors),
+        None => processed,
+    };
+    
+    // タイルと同じく最も小さくなるフォーマットで保持
+    let (format, data) = crate::image::rgb565::encode_tile(&processed.rgb565_data);
+    let asset = crate::protocol::Asset {
+        width: processed.width as u8,
+        height: processed.height as u8,
+        format,
+        data,
+    };
+    let asset_bytes = asset.data.len();
+    
+    match bt_manager.register_asset(id, asset).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "asset_id": id,
+            "size": [processed.width, processed.height],
+            "format": format,
+            "bytes": asset_bytes
+        })),
+        Err(e) => {
+            error!("アセット登録失敗: {}", e);
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "success": false,
+                "error": format!("アセットの登録に失敗しました: {}", e)
+            }))
+        }
+    }
+}
+
+/// 登録済みアセットを描画（未転送のデバイスには先に転送）
+/// POST /api/assets/{id}/draw
+#[cfg(feature = "http-endpoints")]
+pub async fn draw_asset<M: BluetoothManager>(
+    path: web::Path<u16>,
+    query: web::Query<AssetDrawParams>,
+    bt_manager: web::Data<M>,
+) -> HttpResponse {
+    let id = path.into_inner();
+    info!("アセット描画: id={}, デバイス={}, 位置=({},{})", id, query.device, query.x, query.y);
+    
+    let device_names = match resolve_selector(bt_manager.get_ref(), &image_selector(&query.device)).await {
+        Ok(device_names) => device_names,
+        Err(e) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "success": false,
+                "error": format!("デバイスが見つかりません: {}", e)
+            }));
+        }
+    };
+    
+    for device_name in &device_names {
+        if let Err(e) = bt_manager.draw_asset(device_name, id, query.x, query.y).await {
+            error!("アセット描画失敗 ({}): {}", device_name, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": format!("アセットの描画に失敗しました: {}", e)
+            }));
+        }
+    }
+    
+    HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "asset_id": id,
+        "devices": device_names,
+        "position": [query.x, query.y]
+    }))
+}
+
 /// フィールドデータを読み取る補助関数
 #[cfg(feature = "http-endpoints")]
 async fn read_field_data(field: &mut Field) -> std::result::Result<Vec<u8>, actix_web::Error> {
@@ -1491,11 +2082,108 @@ mod image_upload_tests {
     #[test]
     fn test_image_upload_params_default() {
         let params = ImageUploadParams::default();
-        assert_eq!(params.device, 1);
+        assert_eq!(params.device, "1");
         assert_eq!(params.x, 0);
         assert_eq!(params.y, 0);
     }
 
+    #[test]
+    fn test_image_selector() {
+        assert!(matches!(image_selector("0"), v2::DeviceSelector::All(_)));
+        assert!(matches!(image_selector(" 2 "), v2::DeviceSelector::Number(2)));
+        assert!(matches!(image_selector("group:desk"), v2::DeviceSelector::Group(ref group) if group == "desk"));
+        assert!(matches!(image_selector("kitchen"), v2::DeviceSelector::Id(_)));
+    }
+
+    /// 128x128画像をタイル分割し、send_image_tilesと同じ形式のImageコマンドにする
+    fn tile_commands(rgb565_data: &[u16]) -> Vec<Command> {
+        split_image_to_tiles(rgb565_data, 128, 128, 16)
+            .into_iter()
+            .map(|tile| {
+                let (format, data) = crate::image::rgb565::encode_tile(&tile.rgb565_data);
+                Command::Image { x: tile.x, y: tile.y, width: tile.width, height: tile.height, format, data }
+            })
+            .collect()
+    }
+
+    /// 各フレームを送信先の機能で変換した後のサイズがBLE制限内に収まることを確認
+    fn assert_frames_fit(frames: &[Vec<Command>], capabilities: &DeviceCapabilities) {
+        for frame in frames {
+            let adapted = capabilities.adapt_command(Command::batched(frame.clone())).unwrap();
+            let size = adapted.encode().unwrap().len();
+            assert!(size <= TILE_FRAME_LIMIT, "frame of {} tiles is {} bytes", frame.len(), size);
+        }
+    }
+
+    #[test]
+    fn test_tile_frames_fit_after_rle_expansion() {
+        // 単色画像はRLEで数バイトに縮むが、RLE非対応デバイスには生のRGB565で届く
+        let commands = tile_commands(&[0xF800; 128 * 128]);
+        assert!(commands.iter().all(|command| matches!(
+            command,
+            Command::Image { format, .. } if *format != crate::protocol::image_format::RAW_RGB565
+        )));
+        
+        let legacy = DeviceCapabilities::default();
+        let frames = pack_tile_frames(&commands, &legacy).unwrap();
+        assert_eq!(frames.iter().map(Vec::len).sum::<usize>(), commands.len());
+        assert_frames_fit(&frames, &legacy);
+        
+        // RLE対応デバイスには複数タイルを1フレームにまとめて送る
+        let capabilities = DeviceCapabilities { rle_images: true, ..DeviceCapabilities::default() };
+        let packed = pack_tile_frames(&commands, &capabilities).unwrap();
+        assert!(packed.len() < frames.len());
+        assert_frames_fit(&packed, &capabilities);
+    }
+
+    #[test]
+    fn test_tile_frames_fit_after_indexed_expansion() {
+        // 2色の縦縞はRLEでは縮まないが、インデックスカラーなら1ピクセル1ビットになる
+        let pixels: Vec<u16> = (0..128 * 128).map(|i| if i % 2 == 0 { 0xFFFF } else { 0x001F }).collect();
+        let commands = tile_commands(&pixels);
+        assert!(commands.iter().all(|command| matches!(
+            command,
+            Command::Image { format: crate::protocol::image_format::INDEXED, .. }
+        )));
+        
+        // インデックスカラー非対応デバイスには生のRGB565に戻して送る
+        let legacy = DeviceCapabilities { rle_images: true, indexed_images: false, ..DeviceCapabilities::default() };
+        let frames = pack_tile_frames(&commands, &legacy).unwrap();
+        assert_eq!(frames.iter().map(Vec::len).sum::<usize>(), commands.len());
+        assert_frames_fit(&frames, &legacy);
+        
+        let capabilities = DeviceCapabilities { indexed_images: true, ..legacy };
+        let packed = pack_tile_frames(&commands, &capabilities).unwrap();
+        assert!(packed.len() < frames.len());
+        assert_frames_fit(&packed, &capabilities);
+    }
+
+    #[tokio::test]
+    async fn test_image_tiles_continue_past_failed_device() {
+        use crate::bluetooth::mock::fixtures::{connected_manager, devices};
+        
+        let devices = devices(2);
+        let manager = connected_manager(&devices).await;
+        devices.iter().for_each(|device| device.clear_commands());
+        devices[0].fail_next_sends(1);
+        
+        let tiles = split_image_to_tiles(&[0xF800; 128 * 128], 128, 128, 16);
+        let capabilities = DeviceCapabilities::default();
+        let report = send_image_tiles(tiles, &image_selector("0"), &capabilities, 0, 0, false, &manager).await.unwrap();
+        
+        // 失敗したデバイスには以降のタイルを送らず、他のデバイスには全タイルを送る
+        assert_eq!(report.succeeded(), vec!["notif_atoms3_2"]);
+        assert_eq!(report.failed().len(), 1);
+        assert!(devices[0].commands().is_empty());
+        let received: usize = devices[1].commands().iter()
+            .map(|command| match command {
+                Command::Batch { commands } => commands.len(),
+                _ => 1,
+            })
+            .sum();
+        assert_eq!(received, 128);
+    }
+
     #[test]
     fn test_fit_mode_parsing() {
         // FitMode文字列パースのテスト（手動実装版）
@@ -1517,4 +2205,30 @@ mod image_upload_tests {
             assert_eq!(result, expected);
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+#[cfg(feature = "http-endpoints")]
+mod text_layout_tests {
+    use super::*;
+    use crate::display::Framebuffer;
+
+    #[test]
+    fn test_v1_text_renders_at_grid_positions() {
+        let commands = build_v1_text_commands("II\nII", Size::Medium, RGB::white(), (32, 32)).unwrap();
+        let mut framebuffer = Framebuffer::default();
+        framebuffer.apply_all(&commands).unwrap();
+        
+        let lit_rows: Vec<u16> = (0..128)
+            .filter(|&y| (0..128).any(|x| framebuffer.pixel(x, y) == Some(0xFFFF)))
+            .collect();
+        
+        // Mediumの行送りは6グリッド（24px）で、行同士は重ならない
+        assert_eq!(lit_rows.first(), Some(&0));
+        assert!(lit_rows.contains(&24));
+        assert!(!(14..24).any(|y| lit_rows.contains(&y)));
+        
+        // 2文字目は3グリッド（12px）右に描かれる
+        assert_eq!(framebuffer.pixel(4, 24 + 6), Some(0xFFFF));
+        assert_eq!(framebuffer.pixel(12 + 4, 24 + 6), Some(0xFFFF));
+    }
+}
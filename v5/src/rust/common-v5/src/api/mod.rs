@@ -14,6 +14,12 @@ pub use handlers::{
     process_v2_devices,
     process_v2_health,
     process_v2_batch,
+    process_v2_device_settings,
+    process_v2_device_screen,
+    process_v2_device_assignment,
+    process_v2_groups,
+    process_v2_group_update,
+    process_v2_group_delete,
     ImageUploadParams,
 };
 
@@ -22,4 +28,8 @@ pub use handlers::{
 pub use handlers::{
     upload_image,
     post_image,
+    register_asset,
+    draw_asset,
+    AssetUploadParams,
+    AssetDrawParams,
 };
\ No newline at end of file
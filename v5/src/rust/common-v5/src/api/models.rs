@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use crate::protocol::{Command, RGB, Size};
-use crate::bluetooth::DeviceInfo;
+use crate::bluetooth::{CommandStatistics, DeviceInfo, LatencyPercentiles, SendReport, TrafficStatistics};
 
 /// v1互換APIモデル
 pub mod v1 {
@@ -25,6 +25,9 @@ pub mod v1 {
         pub status: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub error: Option<String>,
+        /// デバイスごとの送信結果（`{"succeeded": [...], "failed": {...}}`）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub devices: Option<serde_json::Value>,
     }
     
     impl SendResponse {
@@ -32,6 +35,7 @@ pub mod v1 {
             SendResponse {
                 status: "ok".to_string(),
                 error: None,
+                devices: None,
             }
         }
         
@@ -39,8 +43,15 @@ pub mod v1 {
             SendResponse {
                 status: "error".to_string(),
                 error: Some(msg),
+                devices: None,
             }
         }
+        
+        /// デバイスごとの送信結果を付加
+        pub fn with_devices(mut self, report: &SendReport) -> Self {
+            self.devices = serde_json::to_value(report).ok();
+            self
+        }
     }
     
     /// /status エンドポイントのレスポンス
@@ -76,7 +87,7 @@ pub mod v1 {
 /// v2 APIモデル
 pub mod v2 {
     use super::*;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     
     /// bool値を柔軟に解析するヘルパー関数
     /// true: "true", "1", "yes", "on"（大文字小文字区別なし）
@@ -96,6 +107,7 @@ pub mod v2 {
         All(String),           // "all"
         Number(usize),         // 数値
         Id(String),           // デバイスID
+        Group(String),        // "group:名前"
     }
     
     impl DeviceSelector {
@@ -103,7 +115,9 @@ pub mod v2 {
             match s.as_deref() {
                 None | Some("all") => DeviceSelector::All("all".to_string()),
                 Some(s) => {
-                    if let Ok(num) = s.parse::<usize>() {
+                    if let Some(group) = s.strip_prefix(crate::bluetooth::numbering::GROUP_PREFIX) {
+                        DeviceSelector::Group(group.to_string())
+                    } else if let Ok(num) = s.parse::<usize>() {
                         DeviceSelector::Number(num)
                     } else {
                         DeviceSelector::Id(s.to_string())
@@ -346,6 +360,14 @@ pub mod v2 {
         pub connected: usize,
     }
     
+    /// /api/groups/{group} リクエスト
+    #[derive(Debug, Deserialize, Serialize)]
+    pub struct GroupRequest {
+        /// デバイス名・エイリアス・番号（空の場合はグループを削除）
+        #[serde(default)]
+        pub members: Vec<String>,
+    }
+    
     /// /api/health レスポンス
     #[derive(Debug, Serialize, Deserialize)]
     pub struct HealthResponse {
@@ -365,6 +387,9 @@ pub mod v2 {
         pub adapter: Option<String>,
         pub devices_connected: usize,
         pub devices_available: usize,
+        /// デバイスごとの送信統計（切断したデバイスも含む）
+        #[serde(default)]
+        pub devices: BTreeMap<String, TrafficStatistics>,
     }
     
     /// メモリ情報
@@ -381,6 +406,13 @@ pub mod v2 {
         pub requests_per_minute: f64,
         pub errors_total: u64,
         pub average_response_time_ms: f64,
+        #[serde(default)]
+        pub bytes_sent: u64,
+        #[serde(default)]
+        pub latency_ms: LatencyPercentiles,
+        /// コマンドの種類ごとの送信統計
+        #[serde(default)]
+        pub by_command: BTreeMap<String, CommandStatistics>,
     }
     
     /// バッチ操作リクエスト
@@ -0,0 +1,111 @@
+//! アセット（画像スプライト）のサーバー側レジストリ
+//! 
+//! 登録済みアセットと、各デバイスがどのアセットを保持しているかを管理する
+
+use std::collections::{HashMap, HashSet};
+
+use crate::protocol::Asset;
+
+/// アセットレジストリ
+#[derive(Debug, Default)]
+pub struct AssetRegistry {
+    /// アセットID -> アセット
+    assets: HashMap<u16, Asset>,
+    
+    /// デバイス名 -> 転送済みアセットID
+    held: HashMap<String, HashSet<u16>>,
+}
+
+impl AssetRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// アセットを登録（同じIDの既存アセットは置き換え、各デバイスの保持状態を無効化）
+    pub fn register(&mut self, id: u16, asset: Asset) {
+        if self.assets.get(&id) != Some(&asset) {
+            for ids in self.held.values_mut() {
+                ids.remove(&id);
+            }
+        }
+        self.assets.insert(id, asset);
+    }
+    
+    /// アセットを削除
+    pub fn remove(&mut self, id: u16) -> Option<Asset> {
+        for ids in self.held.values_mut() {
+            ids.remove(&id);
+        }
+        self.assets.remove(&id)
+    }
+    
+    /// アセットを取得
+    pub fn get(&self, id: u16) -> Option<&Asset> {
+        self.assets.get(&id)
+    }
+    
+    /// 登録済みアセットID一覧（昇順）
+    pub fn ids(&self) -> Vec<u16> {
+        let mut ids: Vec<u16> = self.assets.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+    
+    /// デバイスがアセットを保持しているか
+    pub fn is_held(&self, device_id: &str, id: u16) -> bool {
+        self.held.get(device_id).is_some_and(|ids| ids.contains(&id))
+    }
+    
+    /// デバイスへの転送完了を記録
+    pub fn mark_held(&mut self, device_id: &str, id: u16) {
+        self.held.entry(device_id.to_string()).or_default().insert(id);
+    }
+    
+    /// デバイスが保持していたアセット（再接続時の再転送用、ID昇順）
+    pub fn held_assets(&self, device_id: &str) -> Vec<(u16, Asset)> {
+        let mut assets: Vec<(u16, Asset)> = self.held.get(device_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.assets.get(id).map(|asset| (*id, asset.clone())))
+            .collect();
+        assets.sort_by_key(|(id, _)| *id);
+        assets
+    }
+    
+    /// デバイスの保持状態をクリア（デバイス側キャッシュが失われた場合）
+    pub fn forget_device(&mut self, device_id: &str) {
+        self.held.remove(device_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn asset(fill: u8) -> Asset {
+        Asset { width: 8, height: 8, format: 2, data: vec![fill; 128] }
+    }
+    
+    #[test]
+    fn test_register_invalidates_changed_assets() {
+        let mut registry = AssetRegistry::new();
+        registry.register(1, asset(0));
+        registry.register(2, asset(1));
+        registry.mark_held("notif_atoms3_1", 1);
+        registry.mark_held("notif_atoms3_1", 2);
+        
+        // 同じ内容の再登録では保持状態を維持
+        registry.register(1, asset(0));
+        assert!(registry.is_held("notif_atoms3_1", 1));
+        
+        // 内容が変わった場合は再転送が必要
+        registry.register(2, asset(9));
+        assert!(!registry.is_held("notif_atoms3_1", 2));
+        
+        let held = registry.held_assets("notif_atoms3_1");
+        assert_eq!(held, vec![(1, asset(0))]);
+        
+        registry.forget_device("notif_atoms3_1");
+        assert!(registry.held_assets("notif_atoms3_1").is_empty());
+    }
+}
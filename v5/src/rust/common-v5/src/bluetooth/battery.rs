@@ -0,0 +1,76 @@
+//! バッテリー残量の監視
+//! 
+//! 各接続から届くバッテリーレベル（Battery Service 0x180F）をマネージャーが集約し、
+//! しきい値を下回ったとき・回復したときにイベントを発行する
+
+use serde::{Deserialize, Serialize};
+
+/// 低バッテリーとみなす既定のしきい値（%）
+pub const DEFAULT_LOW_BATTERY_THRESHOLD: u8 = 20;
+
+/// デバイスを特定したバッテリーイベント
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceBatteryEvent {
+    /// デバイス名
+    pub device_id: String,
+    
+    /// バッテリーレベル（%）
+    pub level: u8,
+    
+    /// しきい値以下になったか（falseは回復）
+    pub low: bool,
+    
+    /// 受信時刻（RFC3339）
+    pub timestamp: String,
+}
+
+impl DeviceBatteryEvent {
+    pub fn new(device_id: &str, level: u8, low: bool) -> Self {
+        DeviceBatteryEvent {
+            device_id: device_id.to_string(),
+            level,
+            low,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// デバイスごとの低バッテリー状態
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct BatteryMonitor {
+    low: bool,
+}
+
+impl BatteryMonitor {
+    /// 新しいレベルを記録し、低バッテリー状態が変わった場合は新しい状態を返す
+    /// 
+    /// しきい値が0の場合は常に低バッテリーではない
+    pub(crate) fn update(&mut self, level: u8, threshold: u8) -> Option<bool> {
+        let low = threshold > 0 && level <= threshold;
+        if low == self.low {
+            return None;
+        }
+        self.low = low;
+        Some(low)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_low_battery_crossing() {
+        let mut monitor = BatteryMonitor::default();
+        assert_eq!(monitor.update(100, 20), None);
+        assert_eq!(monitor.update(21, 20), None);
+        assert_eq!(monitor.update(20, 20), Some(true));
+        
+        // 低バッテリーの間は再通知しない
+        assert_eq!(monitor.update(10, 20), None);
+        assert_eq!(monitor.update(80, 20), Some(false));
+        
+        // しきい値0は無効
+        assert_eq!(monitor.update(0, 0), None);
+    }
+}
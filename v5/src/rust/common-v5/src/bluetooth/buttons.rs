@@ -0,0 +1,183 @@
+//! デバイスのボタンイベントとアクション割り当て
+//! 
+//! 各接続から届くボタンイベントをマネージャーが集約し、
+//! 設定で割り当てたアクション（表示の消去、Webhook呼び出し）を実行する
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::error::Result;
+use crate::protocol::{ButtonEvent, ButtonPress};
+use super::traits::BluetoothManager;
+
+/// デバイスを特定したボタンイベント
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceButtonEvent {
+    /// デバイス名
+    pub device_id: String,
+    
+    /// ボタン番号
+    pub button: u8,
+    
+    /// 押下の種類
+    pub press: ButtonPress,
+    
+    /// 受信時刻（RFC3339）
+    pub timestamp: String,
+}
+
+impl DeviceButtonEvent {
+    pub fn new(device_id: &str, event: ButtonEvent) -> Self {
+        DeviceButtonEvent {
+            device_id: device_id.to_string(),
+            button: event.button,
+            press: event.press,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// ボタンに割り当てるアクション
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ButtonAction {
+    /// 表示中の通知を消去（再接続時にも復元しない）
+    Dismiss,
+    
+    /// イベントをJSONでPOSTする
+    Webhook { url: String },
+}
+
+/// 押下の種類ごとのアクション割り当て
+/// 
+/// TOML例:
+/// ```toml
+/// [buttons.short]
+/// action = "dismiss"
+/// 
+/// [buttons.long]
+/// action = "webhook"
+/// url = "http://localhost:8000/hook"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ButtonBindings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub short: Option<ButtonAction>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub long: Option<ButtonAction>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub double: Option<ButtonAction>,
+}
+
+impl ButtonBindings {
+    /// 押下の種類に割り当てられたアクション
+    pub fn action_for(&self, press: ButtonPress) -> Option<&ButtonAction> {
+        match press {
+            ButtonPress::Short => self.short.as_ref(),
+            ButtonPress::Long => self.long.as_ref(),
+            ButtonPress::Double => self.double.as_ref(),
+        }
+    }
+    
+    /// 割り当てが1つもないか
+    pub fn is_empty(&self) -> bool {
+        self.short.is_none() && self.long.is_none() && self.double.is_none()
+    }
+}
+
+/// ボタンイベントを購読し、割り当てたアクションを実行するタスクを開始
+pub fn spawn_button_actions<M: BluetoothManager + 'static>(manager: Arc<M>, bindings: ButtonBindings) {
+    if bindings.is_empty() {
+        return;
+    }
+    
+    let mut events = manager.subscribe_button_events();
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Dropped {} button events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            
+            let Some(action) = bindings.action_for(event.press) else {
+                debug!("No action bound for {:?} press on {}", event.press, event.device_id);
+                continue;
+            };
+            
+            info!("Button {:?} on {}: {:?}", event.press, event.device_id, action);
+            if let Err(e) = run_action(manager.as_ref(), action, &event).await {
+                warn!("Button action {:?} failed for {}: {}", action, event.device_id, e);
+            }
+        }
+    });
+}
+
+/// アクションを実行
+async fn run_action<M: BluetoothManager>(manager: &M, action: &ButtonAction, event: &DeviceButtonEvent) -> Result<()> {
+    match action {
+        ButtonAction::Dismiss => manager.dismiss_device(&event.device_id).await,
+        ButtonAction::Webhook { url } => call_webhook(url, event).await,
+    }
+}
+
+/// WebhookにイベントをPOST
+#[cfg(feature = "http-endpoints")]
+async fn call_webhook(url: &str, event: &DeviceButtonEvent) -> Result<()> {
+    use crate::error::NotifError;
+    
+    let body = serde_json::to_vec(event)?;
+    let response = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .timeout(std::time::Duration::from_secs(5))
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| NotifError::Other(format!("Webhook request failed: {}", e)))?;
+    
+    if !response.status().is_success() {
+        return Err(NotifError::Other(format!("Webhook returned {}", response.status())));
+    }
+    Ok(())
+}
+
+/// WebhookにイベントをPOST（http-endpoints無効時は未対応）
+#[cfg(not(feature = "http-endpoints"))]
+async fn call_webhook(url: &str, _event: &DeviceButtonEvent) -> Result<()> {
+    Err(crate::error::NotifError::NotImplemented(format!(
+        "Webhook {} requires the http-endpoints feature", url
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_button_bindings_from_toml() {
+        let bindings: ButtonBindings = toml::from_str(r#"
+            [short]
+            action = "dismiss"
+            
+            [double]
+            action = "webhook"
+            url = "http://localhost:8000/hook"
+        "#).unwrap();
+        
+        assert_eq!(bindings.action_for(ButtonPress::Short), Some(&ButtonAction::Dismiss));
+        assert_eq!(bindings.action_for(ButtonPress::Long), None);
+        assert_eq!(
+            bindings.action_for(ButtonPress::Double),
+            Some(&ButtonAction::Webhook { url: "http://localhost:8000/hook".to_string() })
+        );
+        assert!(ButtonBindings::default().is_empty());
+    }
+}
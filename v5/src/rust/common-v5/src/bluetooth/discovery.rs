@@ -0,0 +1,221 @@
+//! デバイスの探索
+//! 
+//! 起動後に電源が入ったデバイスも拾えるよう、一定間隔でバックグラウンドスキャンを行い、
+//! 接続数の上限まで未接続の対象デバイスに接続する（既定では無効、RESCAN_INTERVALで有効化）。
+//! 共有のオフィス等で他人のデバイスに接続しないよう、名前のパターンとアドレスで対象を絞り込む
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::traits::DeviceInfo;
+
+/// 探索ポリシー
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiscoveryPolicy {
+    /// 1回のスキャン時間
+    pub scan_timeout: Duration,
+    
+    /// バックグラウンドスキャンの間隔（Noneは起動時のスキャンのみ）
+    pub rescan_interval: Option<Duration>,
+    
+    /// 同時に接続するデバイス数の上限（0は無制限）
+    pub max_connections: usize,
+}
+
+impl Default for DiscoveryPolicy {
+    fn default() -> Self {
+        DiscoveryPolicy {
+            scan_timeout: Duration::from_secs(10),
+            rescan_interval: None,
+            max_connections: 10,
+        }
+    }
+}
+
+impl DiscoveryPolicy {
+    /// 新しいデバイスに接続できる残り数（上限なしはNone）
+    pub fn remaining_slots(&self, connected: usize) -> Option<usize> {
+        (self.max_connections > 0).then(|| self.max_connections.saturating_sub(connected))
+    }
+}
+
+/// 自動接続の対象にするデバイスの条件
+/// 
+/// TOML例:
+/// ```toml
+/// [bluetooth]
+/// device_name_patterns = ["notif_atoms3", "desk_*_display"]
+/// allow_addresses = ["AA:BB:CC:DD:EE:01", "AA:BB:CC:DD:EE:02"]
+/// deny_addresses = ["AA:BB:CC:DD:EE:99"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceFilter {
+    /// 名前のパターン（`*`・`?`を含む場合はglob、含まない場合はプレフィックス）
+    /// 
+    /// 空の場合はマネージャーのデバイス名プレフィックスを使う
+    #[serde(default)]
+    pub name_patterns: Vec<String>,
+    
+    /// 接続を許可するアドレス（空の場合は全て許可）
+    #[serde(default)]
+    pub allow_addresses: Vec<String>,
+    
+    /// 接続しないアドレス（許可リストより優先）
+    #[serde(default)]
+    pub deny_addresses: Vec<String>,
+}
+
+impl DeviceFilter {
+    /// 自動接続の対象か（アドレスは大文字・小文字を区別しない）
+    pub fn matches(&self, default_prefix: &str, device_info: &DeviceInfo) -> bool {
+        let listed = |addresses: &[String]| addresses.iter()
+            .any(|address| address.trim().eq_ignore_ascii_case(&device_info.address));
+        
+        if listed(&self.deny_addresses) {
+            return false;
+        }
+        if !self.allow_addresses.is_empty() && !listed(&self.allow_addresses) {
+            return false;
+        }
+        
+        if self.name_patterns.is_empty() {
+            return device_info.name.starts_with(default_prefix);
+        }
+        self.name_patterns.iter().any(|pattern| name_matches(pattern, &device_info.name))
+    }
+    
+    /// スキャナーに渡すプレフィックス（全パターンに共通する固定部分）
+    pub fn scan_prefix(&self, default_prefix: &str) -> String {
+        let mut literals = self.name_patterns.iter()
+            .map(|pattern| pattern.split(['*', '?']).next().unwrap_or_default());
+        let Some(first) = literals.next() else {
+            return default_prefix.to_string();
+        };
+        
+        let mut common = first.to_string();
+        for literal in literals {
+            while !literal.starts_with(common.as_str()) {
+                common.pop();
+            }
+        }
+        common
+    }
+}
+
+/// 名前がパターンに一致するか（ワイルドカードがなければプレフィックス一致）
+fn name_matches(pattern: &str, name: &str) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return name.starts_with(pattern);
+    }
+    
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    
+    // `*`の直後の位置と、その時点の名前の位置を覚えて後戻りする
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    backtrack = Some((star_p, star_n + 1));
+                    p = star_p;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// デバイスの接続イベント（起動時・バックグラウンドスキャン・手動接続のいずれでも発生）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceConnectionEvent {
+    /// デバイス名
+    pub device_id: String,
+    
+    /// 割り当てられたデバイス番号
+    pub number: usize,
+    
+    /// デバイスのアドレス
+    pub address: String,
+    
+    /// 接続時刻（RFC3339）
+    pub timestamp: String,
+}
+
+impl DeviceConnectionEvent {
+    pub fn new(device_id: &str, number: usize, address: &str) -> Self {
+        DeviceConnectionEvent {
+            device_id: device_id.to_string(),
+            number,
+            address: address.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_remaining_slots() {
+        let policy = DiscoveryPolicy { max_connections: 2, ..DiscoveryPolicy::default() };
+        assert_eq!(policy.remaining_slots(0), Some(2));
+        assert_eq!(policy.remaining_slots(3), Some(0));
+        
+        let unlimited = DiscoveryPolicy { max_connections: 0, ..policy };
+        assert_eq!(unlimited.remaining_slots(100), None);
+    }
+    
+    #[test]
+    fn test_device_filter() {
+        let device = |name: &str, address: &str| DeviceInfo {
+            name: name.to_string(),
+            address: address.to_string(),
+            connected: false,
+            number: None,
+            alias: None,
+            signal_strength: None,
+            battery_level: None,
+            capabilities: Default::default(),
+            firmware_version: None,
+            reconnect: None,
+            statistics: None,
+        };
+        
+        // パターン未指定はプレフィックスのみ
+        let filter = DeviceFilter::default();
+        assert!(filter.matches("notif_", &device("notif_atoms3_1", "AA:00")));
+        assert!(!filter.matches("notif_", &device("other", "AA:00")));
+        assert_eq!(filter.scan_prefix("notif_"), "notif_");
+        
+        let filter = DeviceFilter {
+            name_patterns: vec!["notif_atoms3".to_string(), "notif_*_desk".to_string(), "notif_?x".to_string()],
+            allow_addresses: Vec::new(),
+            deny_addresses: vec!["aa:00".to_string()],
+        };
+        assert!(filter.matches("", &device("notif_atoms3_9", "AA:01")));
+        assert!(filter.matches("", &device("notif_kitchen_desk", "AA:01")));
+        assert!(filter.matches("", &device("notif_1x", "AA:01")));
+        assert!(!filter.matches("", &device("notif_kitchen_desk2", "AA:01")));
+        assert!(!filter.matches("", &device("notif_12x", "AA:01")));
+        assert!(!filter.matches("", &device("notif_atoms3_1", "AA:00")));
+        assert_eq!(filter.scan_prefix("ignored"), "notif_");
+        
+        // 許可リストがあればそれ以外は対象外
+        let filter = DeviceFilter { allow_addresses: vec!["AA:01".to_string()], ..filter };
+        assert!(filter.matches("", &device("notif_atoms3_1", "aa:01")));
+        assert!(!filter.matches("", &device("notif_atoms3_2", "AA:02")));
+    }
+}
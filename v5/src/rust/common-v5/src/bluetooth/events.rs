@@ -0,0 +1,178 @@
+//! マネージャーのイベントバス
+//! 
+//! 接続・切断・再接続・コマンドの成否・画像転送の進捗・ボタン・バッテリーを
+//! 1つのbroadcastチャネルに流し、SSE・Webhook・メトリクス・MCP通知などが
+//! 同じ購読元からまとめて受け取れるようにする
+
+use serde::{Deserialize, Serialize};
+
+use super::battery::DeviceBatteryEvent;
+use super::buttons::DeviceButtonEvent;
+use super::discovery::DeviceConnectionEvent;
+
+/// マネージャーが発行するイベント
+/// 
+/// JSONでは `{"event": "command_failed", "device_id": "notif_1", ...}` の形になる
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NotifEvent {
+    /// デバイスが接続された（起動時・バックグラウンドスキャン・手動接続）
+    Connected(DeviceConnectionEvent),
+    
+    /// デバイスが切断された
+    Disconnected {
+        device_id: String,
+        
+        /// 明示的な切断・削除によるものか（falseは電源断・電波切れの検出）
+        requested: bool,
+        
+        timestamp: String,
+    },
+    
+    /// 自動・手動の再接続に成功した
+    Reconnected {
+        device_id: String,
+        timestamp: String,
+    },
+    
+    /// 再接続に失敗した
+    ReconnectFailed {
+        device_id: String,
+        
+        /// 連続失敗回数
+        attempts: u32,
+        
+        error: String,
+        
+        /// 連続失敗が上限に達し、自動再接続を停止したか
+        gave_up: bool,
+        
+        timestamp: String,
+    },
+    
+    /// コマンドの送信に成功した
+    CommandSucceeded {
+        device_id: String,
+        
+        /// コマンドの種類（統計と同じ`text`、`image`等）
+        command: String,
+        
+        /// 送信したバイト数
+        bytes: u64,
+        
+        /// キュー待ちを含まない送信時間
+        latency_ms: u64,
+        
+        timestamp: String,
+    },
+    
+    /// コマンドの送信に失敗した
+    CommandFailed {
+        device_id: String,
+        command: String,
+        error: String,
+        timestamp: String,
+    },
+    
+    /// 画像タイルの転送が進んだ
+    ImageProgress {
+        device_id: String,
+        
+        /// 送信済みのタイル数
+        sent: usize,
+        
+        /// 全タイル数
+        total: usize,
+        
+        timestamp: String,
+    },
+    
+    /// ボタンが押された
+    Button(DeviceButtonEvent),
+    
+    /// 低バッテリーになった・回復した
+    Battery(DeviceBatteryEvent),
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+impl NotifEvent {
+    pub fn disconnected(device_id: &str, requested: bool) -> Self {
+        NotifEvent::Disconnected { device_id: device_id.to_string(), requested, timestamp: now() }
+    }
+    
+    pub fn reconnected(device_id: &str) -> Self {
+        NotifEvent::Reconnected { device_id: device_id.to_string(), timestamp: now() }
+    }
+    
+    pub fn reconnect_failed(device_id: &str, attempts: u32, error: &str, gave_up: bool) -> Self {
+        NotifEvent::ReconnectFailed {
+            device_id: device_id.to_string(),
+            attempts,
+            error: error.to_string(),
+            gave_up,
+            timestamp: now(),
+        }
+    }
+    
+    pub fn command_succeeded(device_id: &str, command: &str, bytes: u64, latency_ms: u64) -> Self {
+        NotifEvent::CommandSucceeded {
+            device_id: device_id.to_string(),
+            command: command.to_string(),
+            bytes,
+            latency_ms,
+            timestamp: now(),
+        }
+    }
+    
+    pub fn command_failed(device_id: &str, command: &str, error: &str) -> Self {
+        NotifEvent::CommandFailed {
+            device_id: device_id.to_string(),
+            command: command.to_string(),
+            error: error.to_string(),
+            timestamp: now(),
+        }
+    }
+    
+    pub fn image_progress(device_id: &str, sent: usize, total: usize) -> Self {
+        NotifEvent::ImageProgress { device_id: device_id.to_string(), sent, total, timestamp: now() }
+    }
+    
+    /// イベントの対象デバイス
+    pub fn device_id(&self) -> &str {
+        match self {
+            NotifEvent::Connected(event) => &event.device_id,
+            NotifEvent::Button(event) => &event.device_id,
+            NotifEvent::Battery(event) => &event.device_id,
+            NotifEvent::Disconnected { device_id, .. }
+            | NotifEvent::Reconnected { device_id, .. }
+            | NotifEvent::ReconnectFailed { device_id, .. }
+            | NotifEvent::CommandSucceeded { device_id, .. }
+            | NotifEvent::CommandFailed { device_id, .. }
+            | NotifEvent::ImageProgress { device_id, .. } => device_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_event_json_shape() {
+        let event = NotifEvent::command_failed("notif_1", "text", "Timeout");
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "command_failed");
+        assert_eq!(json["device_id"], "notif_1");
+        assert_eq!(json["command"], "text");
+        
+        // 既存のイベント型はそのままのフィールドで埋め込む
+        let event = NotifEvent::Connected(DeviceConnectionEvent::new("notif_2", 2, "AA:00"));
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!((json["event"].as_str(), json["number"].as_u64()), (Some("connected"), Some(2)));
+        assert_eq!(serde_json::from_value::<NotifEvent>(json).unwrap(), event);
+        assert_eq!(event.device_id(), "notif_2");
+    }
+}
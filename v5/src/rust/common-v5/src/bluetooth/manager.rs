@@ -1,23 +1,39 @@
 //! 共通Bluetoothマネージャー実装
 
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, Mutex, RwLock};
 use tracing::{debug, error, info, trace, warn};
 use std::time::{Duration, Instant};
 
+use crate::display::Framebuffer;
 use crate::error::{NotifError, Result};
-use crate::protocol::Command;
-use super::traits::{BluetoothManager, Connection, DeviceInfo, DeviceStatistics, Scanner};
+use crate::protocol::{Asset, Command, DeviceSetting};
+use super::assets::AssetRegistry;
+use super::battery::{BatteryMonitor, DeviceBatteryEvent, DEFAULT_LOW_BATTERY_THRESHOLD};
+use super::buttons::DeviceButtonEvent;
+use super::discovery::{DeviceConnectionEvent, DeviceFilter, DiscoveryPolicy};
+use super::events::NotifEvent;
+use super::virtual_display::VirtualConnection;
+use super::numbering::{DeviceAssignment, DeviceNumbering, DevicePin};
+use super::reconnect::{ReconnectPolicy, ReconnectState, ReconnectTracker};
+use super::stats::{command_kind, encoded_bytes, TrafficCounters};
+use super::worker::{DeviceWorker, Job};
+use super::traits::{AckPolicy, BluetoothManager, Connection, DeviceCapabilities, DeviceInfo, DeviceStatistics, Scanner, SendReport};
 
 /// マルチデバイス管理の共通実装
 pub struct CommonBluetoothManager {
-    /// デバイス名 -> 接続のマップ
-    connections: Arc<RwLock<HashMap<String, Box<dyn Connection>>>>,
+    /// デバイス名 -> 送信ワーカーのマップ（接続は各ワーカーが所有）
+    connections: Arc<RwLock<HashMap<String, DeviceWorker>>>,
     
-    /// 接続順番号 -> デバイス名のマップ（1から始まる）
-    device_order: Arc<RwLock<Vec<String>>>,
+    /// デバイス名 -> 番号・エイリアス（切断後も保持し、状態ファイルに保存）
+    numbering: Arc<RwLock<DeviceNumbering>>,
+    
+    /// 番号・エイリアスの状態ファイル（Noneは保存しない）
+    state_file: Arc<RwLock<Option<PathBuf>>>,
     
     /// デバイス名のプレフィックス
     device_name_prefix: String,
@@ -36,6 +52,238 @@ pub struct CommonBluetoothManager {
     
     /// v5追加: 最後に送信した画像の全タイル（再接続時の復元用）
     last_image_tiles: Arc<RwLock<HashMap<String, Vec<crate::protocol::Command>>>>,
+    
+    /// 応答確認ポリシー（新規接続にも適用）
+    ack_policy: Arc<RwLock<Option<AckPolicy>>>,
+    
+    /// アセットレジストリ（デバイスごとの転送状況を含む）
+    assets: Arc<RwLock<AssetRegistry>>,
+    
+    /// 全デバイスのボタンイベント
+    button_events: broadcast::Sender<DeviceButtonEvent>,
+    
+    /// 全デバイスの低バッテリー・回復イベント
+    battery_events: broadcast::Sender<DeviceBatteryEvent>,
+    
+    /// 低バッテリーとみなすしきい値（%、0は通知しない）
+    low_battery_threshold: Arc<RwLock<u8>>,
+    
+    /// デバイス名 -> 表示中の画面（送信済みコマンドから描画したシャドウ）
+    screens: Arc<RwLock<HashMap<String, Framebuffer>>>,
+    
+    /// 再接続ポリシー（初回接続のリトライにも使う）
+    reconnect_policy: Arc<RwLock<ReconnectPolicy>>,
+    
+    /// デバイス名 -> 再接続の進行状況（再接続に失敗しているデバイスのみ）
+    reconnects: Arc<RwLock<HashMap<String, ReconnectTracker>>>,
+    
+    /// 1フレームの送信タイムアウト（Noneは無制限）
+    command_timeout: Arc<RwLock<Option<Duration>>>,
+    
+    /// 探索ポリシー（スキャン時間・バックグラウンドスキャンの間隔・接続数の上限）
+    discovery: Arc<RwLock<DiscoveryPolicy>>,
+    
+    /// 自動接続の対象にするデバイスの条件（名前のパターン・アドレスの許可/拒否リスト）
+    device_filter: Arc<RwLock<DeviceFilter>>,
+    
+    /// スキャンの排他（起動時・バックグラウンド・手動のスキャンを重ねない）
+    scan_lock: Mutex<()>,
+    
+    /// 全デバイスの接続イベント
+    connection_events: broadcast::Sender<DeviceConnectionEvent>,
+    
+    /// イベントバス（接続・切断・再接続・コマンドの成否・画像転送の進捗等）
+    events: broadcast::Sender<NotifEvent>,
+    
+    /// keepaliveタスクを開始済みか（スキャンごとに重複して開始しない）
+    keepalive_started: Arc<AtomicBool>,
+}
+
+/// シーケンス送信時の最大再送ラウンド数
+const SEQUENCED_MAX_ROUNDS: u32 = 3;
+
+/// 統計上のシーケンス送信の種類（まとめて1件として数える）
+const SEQUENCED_KIND: &str = "sequenced";
+
+/// 統計上のデバイス設定の種類
+const SETTING_KIND: &str = "setting";
+
+/// ボタンイベントのバッファ数（購読側が遅れた場合は古いものから破棄）
+const BUTTON_EVENT_CAPACITY: usize = 64;
+
+/// 接続イベントのバッファ数
+const CONNECTION_EVENT_CAPACITY: usize = 16;
+
+/// バッテリーイベントのバッファ数
+const BATTERY_EVENT_CAPACITY: usize = 16;
+
+/// イベントバスのバッファ数（画像転送ではタイルごとにイベントが出るため多めに持つ）
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// アセットをデバイスへ転送し、送信したコマンド列を返す
+async fn upload_asset(connection: &mut dyn Connection, id: u16, asset: &Asset) -> Result<Vec<Command>> {
+    let commands = asset.upload_commands(id)?;
+    for command in &commands {
+        connection.send_command(command.clone()).await?;
+    }
+    Ok(commands)
+}
+
+/// 送信済みコマンドをシャドウフレームバッファに反映
+async fn render_screen(
+    screens: &RwLock<HashMap<String, Framebuffer>>,
+    device_id: &str,
+    capabilities: &DeviceCapabilities,
+    commands: &[Command],
+) {
+    let mut screens = screens.write().await;
+    let screen = screens.entry(device_id.to_string()).or_insert_with(|| {
+        let (width, height) = capabilities.screen_size();
+        Framebuffer::new(width, height)
+    });
+    
+    for command in commands {
+        // 描画できないコマンドがあっても送信結果には影響させない
+        if let Err(e) = screen.apply(command) {
+            debug!("Failed to render command for {} screen: {}", device_id, e);
+        }
+    }
+}
+
+/// デバイスが保持していたアセットを再転送（再接続でデバイス側キャッシュが失われるため）
+/// 
+/// 後のDrawAssetを描画できるよう、シャドウにも転送内容を反映する
+async fn restore_assets(
+    assets: &RwLock<AssetRegistry>,
+    screens: &RwLock<HashMap<String, Framebuffer>>,
+    device_id: &str,
+    connection: &mut dyn Connection,
+) {
+    let held = assets.read().await.held_assets(device_id);
+    if held.is_empty() {
+        return;
+    }
+    
+    info!("Restoring {} assets for {}", held.len(), device_id);
+    let capabilities = connection.get_device_info().await.capabilities;
+    for (id, asset) in held {
+        match upload_asset(connection, id, &asset).await {
+            Ok(commands) => render_screen(screens, device_id, &capabilities, &commands).await,
+            Err(e) => {
+                // 次回の描画時に改めて転送する
+                warn!("Failed to restore asset {} for {}: {}", id, device_id, e);
+                assets.write().await.forget_device(device_id);
+                break;
+            }
+        }
+    }
+}
+
+/// アセットを直接描画する際のタイルサイズ（生のRGB565でも256バイト）
+const ASSET_TILE_WIDTH: u16 = 16;
+const ASSET_TILE_HEIGHT: u16 = 8;
+
+/// アセット非対応デバイス向けに、アセットを1フレームに収まるImageコマンドのタイルに分割
+fn asset_image_tiles(asset: &Asset, x: u16, y: u16) -> Result<Vec<Command>> {
+    let (width, height) = (asset.width as u16, asset.height as u16);
+    let pixels = crate::display::decode_image(width, height, asset.format, &asset.data)?;
+    
+    let mut tiles = Vec::new();
+    for tile_y in (0..height).step_by(ASSET_TILE_HEIGHT as usize) {
+        for tile_x in (0..width).step_by(ASSET_TILE_WIDTH as usize) {
+            let tile_width = ASSET_TILE_WIDTH.min(width - tile_x);
+            let tile_height = ASSET_TILE_HEIGHT.min(height - tile_y);
+            let tile_pixels: Vec<u16> = (tile_y..tile_y + tile_height)
+                .flat_map(|row| {
+                    let start = row as usize * width as usize + tile_x as usize;
+                    pixels[start..start + tile_width as usize].iter().copied()
+                })
+                .collect();
+            
+            let (format, data) = crate::image::rgb565::encode_tile(&tile_pixels);
+            tiles.push(Command::Image {
+                x: x.saturating_add(tile_x),
+                y: y.saturating_add(tile_y),
+                width: tile_width,
+                height: tile_height,
+                format,
+                data,
+            });
+        }
+    }
+    Ok(tiles)
+}
+
+/// 再接続後に最後の表示を再送し、シャドウにも反映する
+/// 
+/// 通常の送信と同じくデバイスの機能に合わせて変換する（制限を超えるコマンドは接続側でフレームに分割される）
+async fn restore_display(
+    connection: &mut dyn Connection,
+    screens: &RwLock<HashMap<String, Framebuffer>>,
+    device_id: &str,
+    commands: Vec<Command>,
+) -> Result<()> {
+    let capabilities = connection.get_device_info().await.capabilities;
+    for command in commands {
+        let command = capabilities.adapt_command(command)?;
+        connection.send_command(command.clone()).await?;
+        render_screen(screens, device_id, &capabilities, std::slice::from_ref(&command)).await;
+    }
+    debug!("Restored display for {}", device_id);
+    Ok(())
+}
+
+/// 切断の検出を再接続状態に記録し、最初の検出時のみ切断イベントを発行
+async fn detect_disconnect(
+    device_id: &str,
+    reconnects: &RwLock<HashMap<String, ReconnectTracker>>,
+    events: &broadcast::Sender<NotifEvent>,
+) {
+    let mut reconnects = reconnects.write().await;
+    if !reconnects.contains_key(device_id) {
+        reconnects.insert(device_id.to_string(), ReconnectTracker::default());
+        let _ = events.send(NotifEvent::disconnected(device_id, false));
+    }
+}
+
+/// 切断された接続の再接続を試み、結果を再接続状態に記録する
+/// 
+/// バックオフ中・自動再接続の停止後は試行せずfalseを返す
+async fn try_reconnect(
+    connection: &mut dyn Connection,
+    device_id: &str,
+    policy: ReconnectPolicy,
+    reconnects: &RwLock<HashMap<String, ReconnectTracker>>,
+    assets: &RwLock<AssetRegistry>,
+    screens: &RwLock<HashMap<String, Framebuffer>>,
+    events: &broadcast::Sender<NotifEvent>,
+) -> bool {
+    detect_disconnect(device_id, reconnects, events).await;
+    if !reconnects.read().await.get(device_id).is_none_or(|tracker| tracker.is_due(Instant::now())) {
+        return false;
+    }
+    
+    match connection.reconnect().await {
+        Ok(()) => {
+            info!("Reconnected to {}", device_id);
+            reconnects.write().await.remove(device_id);
+            restore_assets(assets, screens, device_id, connection).await;
+            let _ = events.send(NotifEvent::reconnected(device_id));
+            true
+        }
+        Err(e) => {
+            let mut reconnects = reconnects.write().await;
+            let tracker = reconnects.entry(device_id.to_string()).or_default();
+            let gave_up = tracker.record_failure(&policy, &e.to_string(), Instant::now());
+            if gave_up {
+                error!("Giving up reconnecting to {}: {}", device_id, e);
+            } else {
+                warn!("Failed to reconnect to {}: {}", device_id, e);
+            }
+            let _ = events.send(NotifEvent::reconnect_failed(device_id, tracker.attempts(), &e.to_string(), gave_up));
+            false
+        }
+    }
 }
 
 /// 内部統計情報
@@ -45,6 +293,10 @@ struct Statistics {
     total_errors: u64,
     total_response_time_ms: u64,
     command_count: u64,
+    /// 全体のコマンドの種類ごとの集計
+    traffic: TrafficCounters,
+    /// デバイス名 -> 集計（切断後も保持）
+    devices: HashMap<String, TrafficCounters>,
 }
 
 impl CommonBluetoothManager {
@@ -55,7 +307,8 @@ impl CommonBluetoothManager {
     {
         CommonBluetoothManager {
             connections: Arc::new(RwLock::new(HashMap::new())),
-            device_order: Arc::new(RwLock::new(Vec::new())),
+            numbering: Arc::new(RwLock::new(DeviceNumbering::default())),
+            state_file: Arc::new(RwLock::new(None)),
             device_name_prefix,
             auto_reconnect: Arc::new(RwLock::new(true)),
             statistics: Arc::new(RwLock::new(Statistics {
@@ -64,33 +317,166 @@ impl CommonBluetoothManager {
                 total_errors: 0,
                 total_response_time_ms: 0,
                 command_count: 0,
+                traffic: TrafficCounters::default(),
+                devices: HashMap::new(),
             })),
             scanner_factory: Arc::new(scanner_factory),
             last_commands: Arc::new(RwLock::new(HashMap::new())),
             last_image_tiles: Arc::new(RwLock::new(HashMap::new())),
+            ack_policy: Arc::new(RwLock::new(None)),
+            assets: Arc::new(RwLock::new(AssetRegistry::new())),
+            button_events: broadcast::channel(BUTTON_EVENT_CAPACITY).0,
+            battery_events: broadcast::channel(BATTERY_EVENT_CAPACITY).0,
+            low_battery_threshold: Arc::new(RwLock::new(DEFAULT_LOW_BATTERY_THRESHOLD)),
+            screens: Arc::new(RwLock::new(HashMap::new())),
+            reconnect_policy: Arc::new(RwLock::new(ReconnectPolicy::default())),
+            reconnects: Arc::new(RwLock::new(HashMap::new())),
+            command_timeout: Arc::new(RwLock::new(None)),
+            discovery: Arc::new(RwLock::new(DiscoveryPolicy::default())),
+            device_filter: Arc::new(RwLock::new(DeviceFilter::default())),
+            scan_lock: Mutex::new(()),
+            connection_events: broadcast::channel(CONNECTION_EVENT_CAPACITY).0,
+            events: broadcast::channel(EVENT_BUS_CAPACITY).0,
+            keepalive_started: Arc::new(AtomicBool::new(false)),
         }
     }
     
-    /// デバイスを追加
-    pub async fn add_device(&self, device_name: String, mut connection: Box<dyn Connection>) -> Result<()> {
-        let mut connections = self.connections.write().await;
-        let mut device_order = self.device_order.write().await;
-        
-        // デバイス番号を計算（1から開始）
-        let device_number = if connections.contains_key(&device_name) {
-            // 既存デバイスの場合、現在の位置を維持
-            device_order.iter().position(|name| name == &device_name)
-                .map(|pos| pos + 1)
-                .unwrap_or(device_order.len() + 1)
-        } else {
-            // 新しいデバイスの場合
-            device_order.len() + 1
+    /// 再接続ポリシーを設定（初回接続のリトライとkeepaliveでの再接続に適用）
+    pub async fn set_reconnect_policy(&self, policy: ReconnectPolicy) {
+        *self.reconnect_policy.write().await = policy;
+        info!("Reconnect policy set to: {:?}", policy);
+    }
+    
+    /// 探索ポリシーを設定（次回のスキャンから適用）
+    pub async fn set_discovery_policy(&self, policy: DiscoveryPolicy) {
+        *self.discovery.write().await = policy;
+        info!("Discovery policy set to: {:?}", policy);
+    }
+    
+    /// 自動接続の対象にするデバイスの条件を設定（接続済みのデバイスは切断しない）
+    pub async fn set_device_filter(&self, filter: DeviceFilter) {
+        info!("Device filter set to: {:?}", filter);
+        *self.device_filter.write().await = filter;
+    }
+    
+    /// バックグラウンドスキャンを開始（探索ポリシーで間隔が指定されている場合のみ）
+    /// 
+    /// 起動時のスキャンの後に呼ぶ。マネージャーが破棄されるとタスクも終了する
+    pub async fn start_rescan(self: &Arc<Self>) {
+        let Some(interval) = self.discovery.read().await.rescan_interval else {
+            info!("Background rescan disabled");
+            return;
         };
         
-        // 既に存在する場合は上書き
-        if !connections.contains_key(&device_name) {
-            device_order.push(device_name.clone());
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // 最初のtickは即座に完了するので、起動時のスキャンから1周期空ける
+            ticker.tick().await;
+            
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.rescan().await;
+            }
+        });
+        info!("Background rescan started ({}s interval)", interval.as_secs());
+    }
+    
+    /// 接続数に空きがあればスキャンし、新しく見つかったデバイスに接続
+    async fn rescan(&self) {
+        let connected: Vec<String> = self.connections.read().await.keys().cloned().collect();
+        if self.discovery.read().await.remaining_slots(connected.len()) == Some(0) {
+            debug!("Skipping rescan: {} device(s) already connected", connected.len());
+            return;
+        }
+        
+        match self.scan_and_connect_all().await {
+            Ok(devices) => {
+                let new_devices: Vec<&String> = devices.iter()
+                    .filter(|device_name| !connected.contains(device_name))
+                    .collect();
+                if !new_devices.is_empty() {
+                    info!("Background rescan connected: {:?}", new_devices);
+                }
+            }
+            Err(e) => warn!("Background rescan failed: {}", e),
+        }
+    }
+    
+    /// 低バッテリーとみなすしきい値を設定（%、0は通知しない）
+    pub async fn set_low_battery_threshold(&self, threshold: u8) {
+        *self.low_battery_threshold.write().await = threshold;
+        info!("Low battery threshold set to: {}%", threshold);
+    }
+    
+    /// 番号・エイリアスの状態ファイルを設定し、保存されている割り当てを読み込む
+    pub async fn set_state_file(&self, path: Option<PathBuf>) -> Result<()> {
+        if let Some(path) = &path {
+            let numbering = DeviceNumbering::load(path)?;
+            *self.numbering.write().await = numbering;
+            info!("Loaded device numbering from {}", path.display());
+        }
+        *self.state_file.write().await = path;
+        Ok(())
+    }
+    
+    /// 番号・エイリアスを状態ファイルに保存（失敗しても動作は続ける）
+    async fn save_numbering(&self) {
+        let Some(path) = self.state_file.read().await.clone() else {
+            return;
+        };
+        
+        let numbering = self.numbering.read().await.clone();
+        if let Err(e) = numbering.save(&path) {
+            warn!("Failed to save device numbering to {}: {}", path.display(), e);
+        }
+    }
+    
+    /// 1フレームの送信タイムアウトを設定（接続済みデバイスと今後の接続に適用、Noneは無制限）
+    pub async fn set_command_timeout(&self, timeout: Option<Duration>) {
+        *self.command_timeout.write().await = timeout;
+        
+        let workers: Vec<DeviceWorker> = self.connections.read().await.values().cloned().collect();
+        for worker in workers {
+            let _ = worker.run(move |connection| Box::pin(async move {
+                connection.set_command_timeout(timeout);
+                Ok(())
+            })).await;
         }
+        info!("Command timeout set to: {:?}", timeout);
+    }
+    
+    /// 応答確認ポリシーを設定（接続済みデバイスと今後の接続に適用）
+    pub async fn set_ack_policy(&self, policy: Option<AckPolicy>) {
+        *self.ack_policy.write().await = policy;
+        
+        let workers: Vec<DeviceWorker> = self.connections.read().await.values().cloned().collect();
+        for worker in workers {
+            let _ = worker.run(move |connection| Box::pin(async move {
+                connection.set_ack_policy(policy);
+                Ok(())
+            })).await;
+        }
+        info!("Ack policy set to: {:?}", policy);
+    }
+    
+    /// デバイスを追加
+    pub async fn add_device(&self, device_name: String, mut connection: Box<dyn Connection>) -> Result<()> {
+        // デバイス番号を取得（既知のデバイスは前回と同じ番号、新しいデバイスには空き番号）
+        // 初期表示の送信中は他のデバイスを待たせないよう、ロックはここだけで保持する
+        let address = connection.get_device_info().await.address;
+        let (device_number, changed) = self.numbering.write().await.assign(&device_name, Some(&address));
+        if changed {
+            self.save_numbering().await;
+        }
+        
+        // 応答確認ポリシーと送信タイムアウトを適用
+        connection.set_ack_policy(*self.ack_policy.read().await);
+        connection.set_command_timeout(*self.command_timeout.read().await);
         
         // 接続が安定するまで待つ（ATOMS3の初期化待ち）
         info!("Waiting for connection to stabilize...");
@@ -155,6 +541,12 @@ impl CommonBluetoothManager {
                     device_num_msg,
                 ],
             };
+            
+            // 接続時にデバイスの画面は初期化されるため、シャドウも作り直す
+            let capabilities = connection.get_device_info().await.capabilities;
+            self.screens.write().await.remove(&device_name);
+            self.update_screen(&device_name, &capabilities, std::slice::from_ref(&initial_display)).await;
+            
             let mut last_commands = self.last_commands.write().await;
             last_commands.insert(device_name.clone(), initial_display);
         }
@@ -185,51 +577,167 @@ impl CommonBluetoothManager {
         }
         */
         
-        connections.insert(device_name.clone(), connection);
+        // 以前保持していたアセットを再転送
+        restore_assets(&self.assets, &self.screens, &device_name, connection.as_mut()).await;
+        
+        // ボタンイベントをマネージャーに集約（接続が破棄されると終了）
+        if let Some(mut events) = connection.subscribe_events() {
+            let button_events = self.button_events.clone();
+            let bus = self.events.clone();
+            let device_id = device_name.clone();
+            tokio::spawn(async move {
+                loop {
+                    match events.recv().await {
+                        Ok(event) => {
+                            debug!("Button event from {}: {:?}", device_id, event);
+                            let event = DeviceButtonEvent::new(&device_id, event);
+                            let _ = bus.send(NotifEvent::Button(event.clone()));
+                            let _ = button_events.send(event);
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Dropped {} button events from {}", skipped, device_id);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+        
+        // バッテリーレベルを監視し、しきい値をまたいだらイベントを発行（接続が破棄されると終了）
+        if let Some(mut battery) = connection.subscribe_battery() {
+            let battery_events = self.battery_events.clone();
+            let bus = self.events.clone();
+            let threshold = self.low_battery_threshold.clone();
+            let device_id = device_name.clone();
+            tokio::spawn(async move {
+                let mut monitor = BatteryMonitor::default();
+                loop {
+                    let level = *battery.borrow_and_update();
+                    if let Some(level) = level {
+                        if let Some(low) = monitor.update(level, *threshold.read().await) {
+                            if low {
+                                warn!("Low battery on {}: {}%", device_id, level);
+                            } else {
+                                info!("Battery recovered on {}: {}%", device_id, level);
+                            }
+                            let event = DeviceBatteryEvent::new(&device_id, level, low);
+                            let _ = bus.send(NotifEvent::Battery(event.clone()));
+                            let _ = battery_events.send(event);
+                        }
+                    }
+                    
+                    if battery.changed().await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        
+        // 既に存在する場合は上書き
+        let worker = DeviceWorker::spawn(connection).await;
+        self.connections.write().await.insert(device_name.clone(), worker);
+        self.reconnects.write().await.remove(&device_name);
         info!("Added device: {} (position: {})", device_name, device_number);
         
+        // 購読者がいない場合の送信エラーは無視
+        let event = DeviceConnectionEvent::new(&device_name, device_number, &address);
+        self.publish_event(NotifEvent::Connected(event.clone()));
+        let _ = self.connection_events.send(event);
+        
         Ok(())
     }
     
+    /// 仮想ディスプレイを登録し、描画先のフレームバッファを返す
+    pub async fn add_virtual_display(&self, device_name: String) -> Result<Arc<RwLock<Framebuffer>>> {
+        let connection = VirtualConnection::new(&device_name);
+        let framebuffer = connection.framebuffer();
+        self.add_device(device_name, Box::new(connection)).await?;
+        Ok(framebuffer)
+    }
+    
     /// デバイスを削除
     pub async fn remove_device(&self, device_name: &str) -> Result<()> {
-        let mut connections = self.connections.write().await;
-        let mut device_order = self.device_order.write().await;
+        let removed = self.connections.write().await.remove(device_name);
         
-        if let Some(mut conn) = connections.remove(device_name) {
-            // 切断を試みる
-            let _ = conn.disconnect().await;
+        if let Some(worker) = removed {
+            // 切断を試みる（キューに残った送信の後）
+            let disconnected = worker.run(|connection| connection.disconnect()).await;
             
-            // 順序リストからも削除
-            device_order.retain(|name| name != device_name);
+            // 番号・エイリアスは再接続に備えて残す
+            self.screens.write().await.remove(device_name);
+            self.reconnects.write().await.remove(device_name);
             
+            if let Err(e) = disconnected {
+                warn!("Failed to disconnect {}: {}", device_name, e);
+            }
+            
+            // 切断に失敗してもマネージャーからは外れたため、購読者には切断として通知する
             info!("Removed device: {}", device_name);
+            self.publish_event(NotifEvent::disconnected(device_name, true));
             Ok(())
         } else {
             Err(NotifError::DeviceNotFound(device_name.to_string()))
         }
     }
     
-    /// 統計情報を更新
-    async fn update_statistics(&self, success: bool, response_time_ms: u64) {
-        let mut stats = self.statistics.write().await;
-        
-        if success {
+    /// デバイスの送信ワーカーを取得
+    async fn worker(&self, device_id: &str) -> Result<DeviceWorker> {
+        self.connections.read().await.get(device_id)
+            .cloned()
+            .ok_or_else(|| NotifError::DeviceNotFound(device_id.to_string()))
+    }
+    
+    /// 送信済みコマンドをシャドウフレームバッファに反映
+    async fn update_screen(&self, device_id: &str, capabilities: &DeviceCapabilities, commands: &[Command]) {
+        render_screen(&self.screens, device_id, capabilities, commands).await;
+    }
+    
+    /// 送信成功を統計に記録し、イベントを発行
+    async fn record_success(&self, device_id: &str, kind: &'static str, bytes: u64, response_time_ms: u64) {
+        {
+            let mut stats = self.statistics.write().await;
+            
             stats.total_commands_sent += 1;
             stats.total_response_time_ms += response_time_ms;
             stats.command_count += 1;
-        } else {
+            stats.traffic.record_success(kind, bytes, response_time_ms);
+            stats.devices.entry(device_id.to_string()).or_default().record_success(kind, bytes, response_time_ms);
+        }
+        self.publish_event(NotifEvent::command_succeeded(device_id, kind, bytes, response_time_ms));
+    }
+    
+    /// 送信失敗を統計に記録し、イベントを発行（未登録のデバイスは全体の統計のみ）
+    async fn record_error(&self, device_id: Option<&str>, kind: &'static str, error: &NotifError) {
+        {
+            let mut stats = self.statistics.write().await;
+            
             stats.total_errors += 1;
+            stats.traffic.record_error(kind);
+            if let Some(device_id) = device_id {
+                stats.devices.entry(device_id.to_string()).or_default().record_error(kind);
+            }
+        }
+        if let Some(device_id) = device_id {
+            self.publish_event(NotifEvent::command_failed(device_id, kind, &error.to_string()));
         }
     }
     
     // 5秒間隔のkeepaliveタスクを開始（内部メソッド）
     fn start_keepalive_task(&self) {
+        if self.keepalive_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        
         // info!("start_keepalive_task called");  // Keepaliveログ抑制
         let connections = self.connections.clone();
-        let device_order = self.device_order.clone();
         let last_commands = self.last_commands.clone();
         let last_image_tiles = self.last_image_tiles.clone();  // v5追加
+        let assets = self.assets.clone();
+        let screens = self.screens.clone();
+        let reconnect_policy = self.reconnect_policy.clone();
+        let auto_reconnect = self.auto_reconnect.clone();
+        let reconnects = self.reconnects.clone();
+        let events = self.events.clone();
         
         // info!("Spawning keepalive task...");  // Keepaliveログ抑制
         tokio::spawn(async move {
@@ -242,69 +750,57 @@ impl CommonBluetoothManager {
                 interval.tick().await;
                 // info!("Keepalive: After interval.tick() - checking device connections...");  // Keepaliveログ抑制
                 
-                // 全接続デバイスの接続状態をチェック
-                let devices = device_order.read().await.clone();
-                // info!("Keepalive: Checking {} devices", devices.len());  // Keepaliveログ抑制
-                for device_id in devices {
-                    // まず接続の存在をチェック
-                    let connection_exists = connections.read().await.contains_key(&device_id);
+                // 全接続デバイスの接続状態をチェック（各デバイスのワーカーで実行）
+                let workers: Vec<(String, DeviceWorker)> = connections.read().await
+                    .iter()
+                    .map(|(device_id, worker)| (device_id.clone(), worker.clone()))
+                    .collect();
+                // info!("Keepalive: Checking {} devices", workers.len());  // Keepaliveログ抑制
+                let policy = *reconnect_policy.read().await;
+                let auto_reconnect = *auto_reconnect.read().await;
+                for (device_id, worker) in workers {
+                    let last_commands = last_commands.clone();
+                    let last_image_tiles = last_image_tiles.clone();
+                    let assets = assets.clone();
+                    let screens = screens.clone();
+                    let reconnects = reconnects.clone();
+                    let events = events.clone();
                     
-                    if connection_exists {
-                        // 接続状態をチェック
-                        let is_connected = {
-                            let connections_guard = connections.read().await;
-                            if let Some(connection) = connections_guard.get(&device_id) {
-                                connection.is_connected().await
-                            } else {
-                                false
+                    let check: Job = Box::new(move |connection| Box::pin(async move {
+                        if connection.is_connected().await {
+                            // info!("Keepalive: Device {} is connected", device_id);  // Keepaliveログ抑制
+                            // 自力で復帰した場合も再接続状態を戻す
+                            if reconnects.write().await.remove(&device_id).is_some() {
+                                let _ = events.send(NotifEvent::reconnected(&device_id));
                             }
+                            return;
+                        }
+                        
+                        // 自動再接続が無効でも切断は通知する
+                        if !auto_reconnect {
+                            detect_disconnect(&device_id, &reconnects, &events).await;
+                            return;
+                        }
+                        
+                        // バックオフ中・停止後は次の周期まで待つ（再接続できた場合はアセットも復元済み）
+                        // warn!("Keepalive: Device {} disconnected, attempting reconnect", device_id);  // Keepaliveログ抑制
+                        if !try_reconnect(connection, &device_id, policy, &reconnects, &assets, &screens, &events).await {
+                            return;
+                        }
+                        
+                        // v5修正: 再接続後、画像タイルがある場合は全タイル再送信（画像でない場合は通常のコマンド復元）
+                        let restore = match last_image_tiles.read().await.get(&device_id) {
+                            Some(tiles) => tiles.clone(),
+                            None => last_commands.read().await.get(&device_id).cloned().into_iter().collect(),
                         };
                         
-                        if !is_connected {
-                            // warn!("Keepalive: Device {} disconnected, attempting reconnect", device_id);  // Keepaliveログ抑制
-                            
-                            // 再接続を試みる
-                            let mut connections_guard = connections.write().await;
-                            if let Some(connection) = connections_guard.get_mut(&device_id) {
-                                if let Err(e) = connection.reconnect().await {
-                                    // error!("Keepalive: Failed to reconnect {}: {}", device_id, e);  // Keepaliveログ抑制
-                                } else {
-                                    // info!("Keepalive: Successfully reconnected {}", device_id);  // Keepaliveログ抑制
-                                    
-                                    // v5修正: 再接続後、画像タイルがある場合は全タイル再送信
-                                    let last_image_tiles = {
-                                        let tiles_guard = last_image_tiles.read().await;
-                                        tiles_guard.get(&device_id).cloned()
-                                    };
-                                    
-                                    if let Some(tiles) = last_image_tiles {
-                                        // info!("Keepalive: Restoring {} image tiles for {}", tiles.len(), device_id);  // Keepaliveログ抑制
-                                        for tile_command in tiles {
-                                            if let Err(e) = connection.send_command(tile_command).await {
-                                                // warn!("Keepalive: Failed to restore image tile for {}: {}", device_id, e);  // Keepaliveログ抑制
-                                                break;
-                                            }
-                                        }
-                                    } else {
-                                        // 画像でない場合は通常のコマンド復元
-                                        let last_command = {
-                                            let last_commands_guard = last_commands.read().await;
-                                            last_commands_guard.get(&device_id).cloned()
-                                        };
-                                        
-                                        if let Some(command) = last_command {
-                                            // info!("Keepalive: Restoring last display for {}", device_id);  // Keepaliveログ抑制
-                                            if let Err(e) = connection.send_command(command).await {
-                                                // warn!("Keepalive: Failed to restore display for {}: {}", device_id, e);  // Keepaliveログ抑制
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        } else {
-                            // info!("Keepalive: Device {} is connected", device_id);  // Keepaliveログ抑制
+                        if let Err(e) = restore_display(connection, &screens, &device_id, restore).await {
+                            debug!("Failed to restore display for {}: {}", device_id, e);
                         }
-                    }
+                    }));
+                    
+                    // 送信が詰まっているデバイスは次の周期でチェック
+                    worker.try_enqueue(check);
                 }
             }
         });
@@ -323,13 +819,13 @@ impl BluetoothManager for CommonBluetoothManager {
         scanner: &dyn Scanner,
         device_info: &DeviceInfo,
     ) -> Result<Box<dyn Connection>> {
-        const MAX_RETRIES: u32 = 3;
-        const RETRY_DELAY_MS: u64 = 2000;
+        let policy = *self.reconnect_policy.read().await;
+        let max_attempts = policy.connect_attempts();
         
         let mut last_error = None;
         
-        for attempt in 1..=MAX_RETRIES {
-            info!("Connection attempt {}/{} for device: {}", attempt, MAX_RETRIES, device_info.name);
+        for attempt in 1..=max_attempts {
+            info!("Connection attempt {}/{} for device: {}", attempt, max_attempts, device_info.name);
             
             match scanner.connect(device_info).await {
                 Ok(connection) => {
@@ -340,10 +836,11 @@ impl BluetoothManager for CommonBluetoothManager {
                     last_error = Some(e);
                     warn!("Connection attempt {} failed for {}: {}", attempt, device_info.name, last_error.as_ref().unwrap());
                     
-                    // 最後の試行でない場合はリトライ間隔を待つ
-                    if attempt < MAX_RETRIES {
-                        info!("Waiting {}ms before retry attempt {}...", RETRY_DELAY_MS, attempt + 1);
-                        tokio::time::sleep(tokio::time::Duration::from_millis(RETRY_DELAY_MS)).await;
+                    // 最後の試行でない場合はバックオフ分待つ
+                    if attempt < max_attempts {
+                        let delay = policy.delay(attempt);
+                        info!("Waiting {}ms before retry attempt {}...", delay.as_millis(), attempt + 1);
+                        tokio::time::sleep(delay).await;
                     }
                 }
             }
@@ -354,19 +851,25 @@ impl BluetoothManager for CommonBluetoothManager {
     }
     
     async fn scan_and_connect_all(&self) -> Result<Vec<String>> {
-        info!("Scanning for all devices with prefix: {}", self.device_name_prefix);
+        // 複数のスキャンが同時にアダプターを使わないようにする
+        let _scan = self.scan_lock.lock().await;
+        let policy = *self.discovery.read().await;
+        let filter = self.device_filter.read().await.clone();
+        let scan_prefix = filter.scan_prefix(&self.device_name_prefix);
+        info!("Scanning for all devices with prefix: {}", scan_prefix);
         
         let scanner = self.create_scanner()?;
         let devices = scanner.scan(
-            &self.device_name_prefix,
-            std::time::Duration::from_secs(10),
+            &scan_prefix,
+            policy.scan_timeout,
         ).await?;
         
         let mut connected_devices = Vec::new();
         
         for device_info in devices {
-            // プレフィックスでフィルター
-            if !device_info.name.starts_with(&self.device_name_prefix) {
+            // 名前のパターンとアドレスの許可/拒否リストでフィルター
+            if !filter.matches(&self.device_name_prefix, &device_info) {
+                debug!("Skipping {} ({}): excluded by device filter", device_info.name, device_info.address);
                 continue;
             }
             
@@ -380,6 +883,13 @@ impl BluetoothManager for CommonBluetoothManager {
                 }
             }
             
+            // 接続数の上限に達している場合は接続しない
+            let connected = self.connections.read().await.len();
+            if policy.remaining_slots(connected) == Some(0) {
+                warn!("Skipping {}: max connections ({}) reached", device_info.name, policy.max_connections);
+                continue;
+            }
+            
             info!("Attempting to connect to device: {}", device_info.name);
             
             // リトライ機能付き接続
@@ -417,53 +927,90 @@ impl BluetoothManager for CommonBluetoothManager {
         device_id: &str,
         command: Command,
     ) -> Result<()> {
-        let start_time = Instant::now();
+        let kind = command_kind(&command);
+        let worker = match self.worker(device_id).await {
+            Ok(worker) => worker,
+            Err(e) => {
+                self.record_error(None, kind, &e).await;
+                return Err(e);
+            }
+        };
         
-        let mut connections = self.connections.write().await;
+        debug!("Sending command to device: {}", device_id);
         
-        if let Some(connection) = connections.get_mut(device_id) {
-            debug!("Sending command to device: {}", device_id);
-            
-            match connection.send_command(command.clone()).await {
-                Ok(_) => {
-                    let response_time = start_time.elapsed().as_millis() as u64;
-                    self.update_statistics(true, response_time).await;
-                    
-                    // 送信成功時、最後のコマンドを保存
-                    // v5修正: CMD_IMAGEは複数タイルに分割されるため保存しない（再接続時の問題を防ぐ）
-                    match &command {
-                        Command::Image { .. } => {
-                            // 画像タイルは保存しない（128個のタイルが個別に送信されるため）
-                        }
-                        _ => {
-                            let mut last_commands = self.last_commands.write().await;
-                            last_commands.insert(device_id.to_string(), command);
-                        }
-                    }
-                    
-                    Ok(())
-                }
+        // デバイスが描画できないプリミティブは変換または拒否
+        let capabilities = worker.info().await.capabilities;
+        let command = match capabilities.adapt_command(command) {
+            Ok(command) => command,
+            Err(e) => {
+                self.record_error(Some(device_id), kind, &e).await;
+                return Err(e);
+            }
+        };
+        
+        let auto_reconnect = *self.auto_reconnect.read().await;
+        let policy = *self.reconnect_policy.read().await;
+        let reconnects = self.reconnects.clone();
+        let assets = self.assets.clone();
+        let screens = self.screens.clone();
+        let events = self.events.clone();
+        let device = device_id.to_string();
+        let sent = command.clone();
+        let result = worker.run(move |connection| Box::pin(async move {
+            // キュー待ちを含めない送信時間
+            let start_time = Instant::now();
+            match connection.send_command(sent).await {
+                Ok(_) => Ok(start_time.elapsed().as_millis() as u64),
                 Err(e) => {
-                    self.update_statistics(false, 0).await;
-                    
-                    // 自動再接続を試みる
-                    if *self.auto_reconnect.read().await && !connection.is_connected().await {
-                        warn!("Device {} disconnected, attempting reconnect...", device_id);
-                        if let Err(reconnect_err) = connection.reconnect().await {
-                            error!("Failed to reconnect to {}: {}", device_id, reconnect_err);
+                    // 自動再接続を試みる（バックオフ中・停止後は試行しない）
+                    if !connection.is_connected().await {
+                        if auto_reconnect {
+                            warn!("Device {} disconnected, attempting reconnect...", device);
+                            try_reconnect(connection, &device, policy, &reconnects, &assets, &screens, &events).await;
+                        } else {
+                            detect_disconnect(&device, &reconnects, &events).await;
                         }
                     }
                     
                     Err(e)
                 }
             }
-        } else {
-            self.update_statistics(false, 0).await;
-            Err(NotifError::DeviceNotFound(device_id.to_string()))
+        })).await;
+        
+        match result {
+            Ok(response_time) => {
+                let bytes = encoded_bytes(std::slice::from_ref(&command));
+                self.record_success(device_id, kind, bytes, response_time).await;
+                self.update_screen(device_id, &capabilities, std::slice::from_ref(&command)).await;
+                
+                // 送信成功時、最後のコマンドを保存
+                // v5修正: CMD_IMAGEは複数タイルに分割されるため保存しない（再接続時の問題を防ぐ）
+                match &command {
+                    Command::Image { .. } => {
+                        // 画像タイルは保存しない（128個のタイルが個別に送信されるため）
+                    }
+                    Command::Batch { commands } if commands.iter().all(|c| matches!(c, Command::Image { .. })) => {
+                        // 圧縮タイルをまとめたBatchも同様に保存しない
+                    }
+                    Command::DefineAsset { .. } | Command::AssetData { .. } => {
+                        // アセット転送は表示状態ではないため保存しない
+                    }
+                    _ => {
+                        let mut last_commands = self.last_commands.write().await;
+                        last_commands.insert(device_id.to_string(), command);
+                    }
+                }
+                
+                Ok(())
+            }
+            Err(e) => {
+                self.record_error(Some(device_id), kind, &e).await;
+                Err(e)
+            }
         }
     }
     
-    async fn send_command_to_all(&self, command: Command) -> Result<()> {
+    async fn send_command_to_all(&self, command: Command) -> Result<SendReport> {
         let connections = self.connections.read().await;
         
         if connections.is_empty() {
@@ -474,22 +1021,21 @@ impl BluetoothManager for CommonBluetoothManager {
         let device_ids: Vec<String> = connections.keys().cloned().collect();
         drop(connections); // ロックを解放
         
-        // 各デバイスに順次送信
-        let mut any_error = None;
-        for device_id in device_ids {
-            if let Err(e) = self.send_command_to_device(&device_id, command.clone()).await {
-                if any_error.is_none() {
-                    any_error = Some(e);
-                }
+        // 各デバイスのワーカーに並行して送信（遅いデバイスが他を待たせない）
+        let sends = device_ids.into_iter().map(|device_id| {
+            let command = command.clone();
+            async move {
+                let result = self.send_command_to_device(&device_id, command).await;
+                (device_id, result)
             }
-        }
+        });
+        let results = futures::future::join_all(sends).await;
         
-        // エラーがあれば最初のエラーを返す
-        if let Some(e) = any_error {
-            return Err(e);
+        let report = SendReport { results: results.into_iter().collect() };
+        for (device_id, e) in report.failed() {
+            warn!("Failed to send command to {}: {}", device_id, e);
         }
-        
-        Ok(())
+        Ok(report)
     }
     
     async fn send_command_by_number(
@@ -497,40 +1043,41 @@ impl BluetoothManager for CommonBluetoothManager {
         number: usize,
         command: Command,
     ) -> Result<()> {
-        let device_order = self.device_order.read().await;
-        
-        if number == 0 || number > device_order.len() {
-            return Err(NotifError::DeviceNotFound(format!("Device #{}", number)));
-        }
-        
-        let device_name = &device_order[number - 1];
-        self.send_command_to_device(device_name, command).await
+        // 送信完了まで番号の割り当てをロックしない
+        let device_name = self.get_device_name_by_number(number).await
+            .ok_or_else(|| NotifError::DeviceNotFound(format!("Device #{}", number)))?;
+        self.send_command_to_device(&device_name, command).await
     }
     
     async fn list_connected_devices(&self) -> Vec<DeviceInfo> {
         let connections = self.connections.read().await;
-        let device_order = self.device_order.read().await;
+        let numbering = self.numbering.read().await;
+        
+        let reconnects = self.reconnects.read().await;
+        let stats = self.statistics.read().await;
+        let now = Instant::now();
         
         let mut devices = Vec::new();
         
-        for (index, device_name) in device_order.iter().enumerate() {
-            if let Some(connection) = connections.get(device_name) {
-                let mut info = connection.get_device_info().await;
-                info.number = Some(index + 1);
-                devices.push(info);
-            }
+        for (device_name, worker) in connections.iter() {
+            let mut info = worker.info().await;
+            let assignment = numbering.get(device_name);
+            info.number = assignment.map(|assignment| assignment.number);
+            info.alias = assignment.and_then(|assignment| assignment.alias.clone());
+            info.reconnect = Some(reconnects.get(device_name).map(|tracker| tracker.state(now)).unwrap_or(ReconnectState::Idle));
+            info.statistics = Some(stats.devices.get(device_name).map(TrafficCounters::statistics).unwrap_or_default());
+            devices.push(info);
         }
         
+        // 番号順
+        devices.sort_by_key(|info| info.number.unwrap_or(usize::MAX));
         devices
     }
     
     async fn is_device_connected(&self, device_id: &str) -> bool {
-        let connections = self.connections.read().await;
-        
-        if let Some(connection) = connections.get(device_id) {
-            connection.is_connected().await
-        } else {
-            false
+        match self.worker(device_id).await {
+            Ok(worker) => worker.info().await.connected,
+            Err(_) => false,
         }
     }
     
@@ -539,29 +1086,36 @@ impl BluetoothManager for CommonBluetoothManager {
     }
     
     async fn disconnect_all(&self) -> Result<()> {
-        let mut connections = self.connections.write().await;
-        let mut device_order = self.device_order.write().await;
+        let workers: Vec<(String, DeviceWorker)> = self.connections.write().await.drain().collect();
+        self.reconnects.write().await.clear();
         
-        for (device_name, mut connection) in connections.drain() {
+        for (device_name, worker) in workers {
             info!("Disconnecting device: {}", device_name);
-            if let Err(e) = connection.disconnect().await {
+            if let Err(e) = worker.run(|connection| connection.disconnect()).await {
                 warn!("Failed to disconnect {}: {}", device_name, e);
             }
+            self.publish_event(NotifEvent::disconnected(&device_name, true));
         }
         
-        device_order.clear();
-        
         Ok(())
     }
     
     async fn reconnect_device(&self, device_id: &str) -> Result<()> {
-        let mut connections = self.connections.write().await;
+        let worker = self.worker(device_id).await?;
+        let assets = self.assets.clone();
+        let screens = self.screens.clone();
+        let device = device_id.to_string();
         
-        if let Some(connection) = connections.get_mut(device_id) {
-            connection.reconnect().await
-        } else {
-            Err(NotifError::DeviceNotFound(device_id.to_string()))
-        }
+        worker.run(move |connection| Box::pin(async move {
+            connection.reconnect().await?;
+            restore_assets(&assets, &screens, &device, connection).await;
+            Ok(())
+        })).await?;
+        
+        // 手動で再接続できたら自動再接続の停止も解除
+        self.reconnects.write().await.remove(device_id);
+        self.publish_event(NotifEvent::reconnected(device_id));
+        Ok(())
     }
     
     async fn set_auto_reconnect(&self, enabled: bool) -> Result<()> {
@@ -571,8 +1125,15 @@ impl BluetoothManager for CommonBluetoothManager {
     }
     
     async fn get_statistics(&self) -> DeviceStatistics {
+        let workers: Vec<DeviceWorker> = self.connections.read().await.values().cloned().collect();
+        let mut connected_devices = 0;
+        for worker in &workers {
+            if worker.info().await.connected {
+                connected_devices += 1;
+            }
+        }
+        
         let stats = self.statistics.read().await;
-        let connections = self.connections.read().await;
         
         let average_response_time_ms = if stats.command_count > 0 {
             stats.total_response_time_ms as f64 / stats.command_count as f64
@@ -580,15 +1141,20 @@ impl BluetoothManager for CommonBluetoothManager {
             0.0
         };
         
+        let traffic = stats.traffic.statistics();
         DeviceStatistics {
-            total_devices: connections.len(),
-            connected_devices: connections.values()
-                .filter(|conn| futures::executor::block_on(conn.is_connected()))
-                .count(),
+            total_devices: workers.len(),
+            connected_devices,
             total_commands_sent: stats.total_commands_sent,
             total_errors: stats.total_errors,
             average_response_time_ms,
             uptime_seconds: stats.start_time.elapsed().as_secs(),
+            bytes_sent: traffic.total.bytes_sent,
+            latency_ms: traffic.total.latency_ms,
+            by_command: traffic.by_command,
+            devices: stats.devices.iter()
+                .map(|(device_id, counters)| (device_id.clone(), counters.statistics()))
+                .collect(),
         }
     }
     
@@ -607,12 +1173,555 @@ impl BluetoothManager for CommonBluetoothManager {
     }
     
     async fn get_device_name_by_number(&self, number: usize) -> Option<String> {
-        let device_order = self.device_order.read().await;
+        let device_name = self.numbering.read().await.name_for_number(number)?.to_string();
+        
+        // 番号が割り当てられていても登録されていないデバイスは対象外
+        self.connections.read().await.contains_key(&device_name).then_some(device_name)
+    }
+    
+    async fn resolve_device(&self, name_or_alias: &str) -> String {
+        self.numbering.read().await
+            .resolve_alias(name_or_alias)
+            .unwrap_or(name_or_alias)
+            .to_string()
+    }
+    
+    async fn pin_device(&self, device_id: &str, pin: DevicePin) -> Result<DeviceAssignment> {
+        let assignment = self.numbering.write().await.pin(device_id, &pin)?;
+        self.save_numbering().await;
+        info!("Pinned {}: {:?}", device_id, assignment);
+        Ok(assignment)
+    }
+    
+    async fn group_members(&self, group: &str) -> Option<Vec<String>> {
+        self.numbering.read().await.resolve_group(group)
+    }
+    
+    async fn set_group(&self, group: &str, members: Vec<String>) -> Result<()> {
+        self.numbering.write().await.set_group(group, members)?;
+        self.save_numbering().await;
+        info!("Updated device group {}", group);
+        Ok(())
+    }
+    
+    async fn list_groups(&self) -> BTreeMap<String, Vec<String>> {
+        self.numbering.read().await.groups().clone()
+    }
+    
+    async fn send_sequenced_to_device(
+        &self,
+        device_id: &str,
+        commands: Vec<Command>,
+    ) -> Result<()> {
+        if commands.len() > u16::MAX as usize + 1 {
+            return Err(NotifError::InvalidParameter(format!(
+                "Too many commands for sequenced transfer: {}", commands.len()
+            )));
+        }
+        
+        let worker = match self.worker(device_id).await {
+            Ok(worker) => worker,
+            Err(e) => {
+                self.record_error(None, SEQUENCED_KIND, &e).await;
+                return Err(e);
+            }
+        };
+        
+        let capabilities = worker.info().await.capabilities;
+        let commands = match commands.into_iter()
+            .map(|command| capabilities.adapt_command(command))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(commands) => commands,
+            Err(e) => {
+                self.record_error(Some(device_id), SEQUENCED_KIND, &e).await;
+                return Err(e);
+            }
+        };
+        
+        debug!("Sending {} sequenced commands to device: {}", commands.len(), device_id);
+        let sent = commands.clone();
+        let result = worker.run(move |connection| Box::pin(async move {
+            let start_time = Instant::now();
+            connection.send_sequenced(sent, SEQUENCED_MAX_ROUNDS).await?;
+            Ok(start_time.elapsed().as_millis() as u64)
+        })).await;
+        
+        match result {
+            Ok(response_time) => {
+                self.record_success(device_id, SEQUENCED_KIND, encoded_bytes(&commands), response_time).await;
+                self.update_screen(device_id, &capabilities, &commands).await;
+                Ok(())
+            }
+            Err(e) => {
+                self.record_error(Some(device_id), SEQUENCED_KIND, &e).await;
+                Err(e)
+            }
+        }
+    }
+    
+    async fn register_asset(&self, id: u16, asset: Asset) -> Result<()> {
+        // 転送できないサイズは登録時に拒否
+        asset.upload_commands(id)?;
+        
+        self.assets.write().await.register(id, asset);
+        info!("Registered asset {}", id);
+        Ok(())
+    }
+    
+    async fn draw_asset(&self, device_id: &str, id: u16, x: u16, y: u16) -> Result<()> {
+        let asset = self.assets.read().await.get(id).cloned()
+            .ok_or_else(|| NotifError::InvalidParameter(format!("Unknown asset: {}", id)))?;
+        
+        let supports_assets = self.worker(device_id).await?.info().await.capabilities.assets;
+        
+        // アセット非対応デバイスには画像として直接送信（1フレームに収まるようタイルに分割）
+        if !supports_assets {
+            for tile in asset_image_tiles(&asset, x, y)? {
+                self.send_command_to_device(device_id, tile).await?;
+            }
+            return Ok(());
+        }
+        
+        if !self.assets.read().await.is_held(device_id, id) {
+            debug!("Uploading asset {} to device: {}", id, device_id);
+            for command in asset.upload_commands(id)? {
+                self.send_command_to_device(device_id, command).await?;
+            }
+            
+            // 転送中に内容が差し替えられた場合は保持済みにしない
+            let mut assets = self.assets.write().await;
+            if assets.get(id) == Some(&asset) {
+                assets.mark_held(device_id, id);
+            }
+        }
+        
+        self.send_command_to_device(device_id, Command::DrawAsset { id, x, y }).await
+    }
+    
+    async fn apply_settings_to_device(&self, device_id: &str, settings: Vec<DeviceSetting>) -> Result<()> {
+        let worker = self.worker(device_id).await?;
+        let device = device_id.to_string();
+        let bytes = settings.iter()
+            .filter_map(|setting| setting.encode().ok())
+            .map(|data| data.len() as u64)
+            .sum();
+        
+        let result = worker.run(move |connection| Box::pin(async move {
+            let start_time = Instant::now();
+            for setting in settings {
+                debug!("Applying setting {:?} to device: {}", setting, device);
+                connection.apply_setting(setting).await?;
+            }
+            Ok(start_time.elapsed().as_millis() as u64)
+        })).await;
+        
+        match result {
+            Ok(response_time) => {
+                self.record_success(device_id, SETTING_KIND, bytes, response_time).await;
+                Ok(())
+            }
+            Err(e) => {
+                self.record_error(Some(device_id), SETTING_KIND, &e).await;
+                Err(e)
+            }
+        }
+    }
+    
+    fn subscribe_connection_events(&self) -> broadcast::Receiver<DeviceConnectionEvent> {
+        self.connection_events.subscribe()
+    }
+    
+    fn subscribe_button_events(&self) -> broadcast::Receiver<DeviceButtonEvent> {
+        self.button_events.subscribe()
+    }
+    
+    fn subscribe_battery_events(&self) -> broadcast::Receiver<DeviceBatteryEvent> {
+        self.battery_events.subscribe()
+    }
+    
+    fn subscribe_events(&self) -> broadcast::Receiver<NotifEvent> {
+        self.events.subscribe()
+    }
+    
+    fn publish_event(&self, event: NotifEvent) {
+        // 購読者がいない場合の送信エラーは無視
+        let _ = self.events.send(event);
+    }
+    
+    async fn get_screen(&self, device_id: &str) -> Option<Framebuffer> {
+        self.screens.read().await.get(device_id).cloned()
+    }
+    
+    async fn dismiss_device(&self, device_id: &str) -> Result<()> {
+        // 画像タイルは通常のコマンドより優先して復元されるため先に破棄
+        // （消去後の画面は最後のコマンドとして保存される）
+        self.last_image_tiles.write().await.remove(device_id);
+        
+        let clear = Command::Clear { color: crate::protocol::RGB::new(0, 0, 0) };
+        self.send_command_to_device(device_id, clear).await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bluetooth::mock::fixtures::{connected_manager, devices, manager};
+    use crate::protocol::{ButtonPress, RGB, Size};
+    
+    #[tokio::test]
+    async fn test_mock_manager_end_to_end() {
+        let devices = devices(1);
+        let manager = manager(&devices);
+        
+        let connected = manager.scan_and_connect_all().await.unwrap();
+        assert_eq!(connected, vec!["notif_atoms3_1".to_string()]);
+        assert_eq!(manager.get_device_name_by_number(1).await.as_deref(), Some("notif_atoms3_1"));
+        
+        devices[0].clear_commands();
+        let text = Command::Text { x: 0, y: 0, size: Size::Small, color: RGB::white(), text: "hi".to_string() };
+        manager.send_command_by_number(1, text.clone()).await.unwrap();
+        assert_eq!(devices[0].commands(), vec![text]);
+        
+        // 送信済みコマンドは画面のシャドウにも反映される
+        manager.send_command_by_number(1, Command::Clear { color: RGB::new(255, 0, 0) }).await.unwrap();
+        let screen = manager.get_screen("notif_atoms3_1").await.unwrap();
+        assert_eq!(screen.pixel(64, 64), Some(0xF800));
+        
+        let mut events = manager.subscribe_button_events();
+        devices[0].press_button(0, ButtonPress::Short);
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv()).await.unwrap().unwrap();
+        assert_eq!(event.device_id, "notif_atoms3_1");
+        assert_eq!(event.press, ButtonPress::Short);
         
-        if number == 0 || number > device_order.len() {
-            return None;
+        // 固定した番号・エイリアスで呼べる
+        let pin = DevicePin { number: Some(3), alias: Some("desk".to_string()) };
+        manager.pin_device("notif_atoms3_1", pin).await.unwrap();
+        assert_eq!(manager.get_device_name_by_number(3).await.as_deref(), Some("notif_atoms3_1"));
+        assert_eq!(manager.get_device_name_by_number(1).await, None);
+        assert_eq!(manager.resolve_device("desk").await, "notif_atoms3_1");
+        let devices = manager.list_connected_devices().await;
+        assert_eq!((devices[0].number, devices[0].alias.as_deref()), (Some(3), Some("desk")));
+    }
+    
+    #[tokio::test]
+    async fn test_draw_asset_without_asset_support_is_tiled() {
+        let devices = devices(1);
+        let manager = connected_manager(&devices).await;
+        
+        // 64x64の生RGB565（8KB）は1フレームに収まらない
+        let pixels: Vec<u8> = (0..64 * 64).flat_map(|i: u16| i.to_le_bytes()).collect();
+        let asset = Asset { width: 64, height: 64, format: crate::protocol::image_format::RAW_RGB565, data: pixels };
+        manager.register_asset(1, asset).await.unwrap();
+        
+        devices[0].clear_commands();
+        manager.draw_asset("notif_atoms3_1", 1, 8, 8).await.unwrap();
+        
+        let commands = devices[0].commands();
+        assert_eq!(commands.len(), 4 * 8);
+        for command in &commands {
+            assert!(matches!(command, Command::Image { .. }));
+            assert!(command.encode().unwrap().len() <= 500);
         }
         
-        Some(device_order[number - 1].clone())
+        // タイルを合わせるとアセットがそのまま描画される
+        let screen = manager.get_screen("notif_atoms3_1").await.unwrap();
+        assert_eq!(screen.pixel(8, 8), Some(0));
+        assert_eq!(screen.pixel(8 + 63, 8 + 63), Some(64 * 64 - 1));
+    }
+    
+    #[tokio::test]
+    async fn test_manager_group_members() {
+        let manager = connected_manager(&devices(2)).await;
+        
+        let pin = DevicePin { number: None, alias: Some("desk".to_string()) };
+        manager.pin_device("notif_atoms3_2", pin).await.unwrap();
+        manager.set_group("build-status", vec!["1".to_string(), "desk".to_string()]).await.unwrap();
+        assert_eq!(
+            manager.group_members("build-status").await.unwrap(),
+            vec!["notif_atoms3_1".to_string(), "notif_atoms3_2".to_string()]
+        );
+        assert_eq!(manager.list_groups().await["build-status"], vec!["1".to_string(), "desk".to_string()]);
+        assert_eq!(manager.group_members("kitchen").await, None);
+        
+        manager.set_group("build-status", Vec::new()).await.unwrap();
+        assert!(manager.list_groups().await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_restored_assets_render_on_screen() {
+        let capabilities = DeviceCapabilities { assets: true, ..DeviceCapabilities::default() };
+        let devices = devices(1);
+        devices[0].set_capabilities(capabilities);
+        let manager = connected_manager(&devices).await;
+        
+        let asset = Asset { width: 2, height: 2, format: crate::protocol::image_format::RAW_RGB565, data: vec![0xFF; 8] };
+        manager.register_asset(1, asset).await.unwrap();
+        manager.draw_asset("notif_atoms3_1", 1, 0, 0).await.unwrap();
+        
+        // 接続し直すとシャドウは作り直され、保持していたアセットは再転送される
+        let connection = crate::bluetooth::MockConnection::new(devices[0].clone());
+        manager.add_device("notif_atoms3_1".to_string(), Box::new(connection)).await.unwrap();
+        assert_ne!(manager.get_screen("notif_atoms3_1").await.unwrap().pixel(50, 50), Some(0xFFFF));
+        
+        // 再転送したアセットをシャドウも保持しているので、転送し直さずに描画できる
+        devices[0].clear_commands();
+        manager.draw_asset("notif_atoms3_1", 1, 50, 50).await.unwrap();
+        assert_eq!(devices[0].commands(), vec![Command::DrawAsset { id: 1, x: 50, y: 50 }]);
+        assert_eq!(manager.get_screen("notif_atoms3_1").await.unwrap().pixel(50, 50), Some(0xFFFF));
+    }
+    
+    #[tokio::test]
+    async fn test_rescan_connects_new_devices_up_to_limit() {
+        let devices = devices(3);
+        devices[1].set_advertising(false);
+        devices[2].set_advertising(false);
+        let manager = Arc::new(manager(&devices));
+        manager.set_discovery_policy(DiscoveryPolicy {
+            rescan_interval: Some(Duration::from_millis(100)),
+            max_connections: 2,
+            ..Default::default()
+        }).await;
+        assert_eq!(manager.scan_and_connect_all().await.unwrap().len(), 1);
+        
+        // 起動後に電源が入ったデバイスをバックグラウンドスキャンで拾う
+        let mut events = manager.subscribe_connection_events();
+        manager.start_rescan().await;
+        devices[1].set_advertising(true);
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+        assert_eq!((event.device_id.as_str(), event.number), ("notif_atoms3_2", 2));
+        
+        // 接続数の上限に達したら新しいデバイスには接続しない
+        devices[2].set_advertising(true);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!devices[2].is_connected());
+        assert_eq!(manager.list_connected_devices().await.len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_scan_skips_filtered_devices() {
+        let mut devices = devices(2);
+        devices.push(crate::bluetooth::MockDevice::new("desk_display"));
+        let manager = manager(&devices);
+        
+        manager.set_device_filter(DeviceFilter {
+            name_patterns: vec!["notif_atoms3_*".to_string(), "desk_*".to_string()],
+            deny_addresses: vec![devices[1].address().to_lowercase()],
+            ..Default::default()
+        }).await;
+        
+        let connected = manager.scan_and_connect_all().await.unwrap();
+        assert_eq!(connected, vec!["notif_atoms3_1".to_string(), "desk_display".to_string()]);
+        assert!(!devices[1].is_connected());
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_low_battery_events() {
+        let devices = devices(1);
+        devices[0].set_battery_level(Some(50));
+        let manager = connected_manager(&devices).await;
+        manager.set_low_battery_threshold(15).await;
+        
+        let mut events = manager.subscribe_battery_events();
+        devices[0].set_battery_level(Some(12));
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv()).await.unwrap().unwrap();
+        assert_eq!((event.device_id.as_str(), event.level, event.low), ("notif_atoms3_1", 12, true));
+        
+        // 低バッテリーのままなら再通知せず、回復したら通知
+        devices[0].set_battery_level(Some(10));
+        devices[0].set_battery_level(Some(90));
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv()).await.unwrap().unwrap();
+        assert_eq!((event.level, event.low), (90, false));
+        assert_eq!(manager.list_connected_devices().await[0].battery_level, Some(90));
+    }
+    
+    #[tokio::test]
+    async fn test_statistics_per_device_and_command() {
+        let devices = devices(2);
+        let manager = connected_manager(&devices).await;
+        
+        let clear = Command::Clear { color: RGB::black() };
+        manager.send_command_to_device("notif_atoms3_1", clear.clone()).await.unwrap();
+        manager.send_command_to_device("notif_atoms3_1", Command::Update).await.unwrap();
+        devices[1].fail_next_sends(1);
+        assert!(manager.send_command_to_device("notif_atoms3_2", clear).await.is_err());
+        
+        let stats = manager.get_statistics().await;
+        assert_eq!((stats.total_commands_sent, stats.total_errors), (2, 1));
+        assert_eq!(stats.by_command["clear"].errors, 1);
+        assert_eq!(stats.by_command["update"].bytes_sent, 1);
+        assert_eq!(stats.devices["notif_atoms3_2"].total.errors, 1);
+        
+        let devices = manager.list_connected_devices().await;
+        let statistics = devices[0].statistics.as_ref().unwrap();
+        assert_eq!(statistics.total.commands_sent, 2);
+        assert_eq!(statistics.by_command.keys().collect::<Vec<_>>(), ["clear", "update"]);
+    }
+    
+    #[tokio::test]
+    async fn test_statistics_count_adapted_commands() {
+        let capabilities = DeviceCapabilities { circles: false, lines: true, ..DeviceCapabilities::default() };
+        let no_lines = DeviceCapabilities { lines: false, ..capabilities.clone() };
+        let devices = devices(2);
+        devices[0].set_capabilities(capabilities.clone());
+        devices[1].set_capabilities(no_lines);
+        let manager = connected_manager(&devices).await;
+        
+        // 円は直線に変換されるため、送信バイト数は変換後のコマンドで数える
+        let circle = Command::Circle { x: 60, y: 60, radius: 20, color: RGB::white(), filled: true };
+        let adapted = capabilities.adapt_command(circle.clone()).unwrap();
+        manager.send_command_to_device("notif_atoms3_1", circle).await.unwrap();
+        
+        // 変換できないコマンドも失敗として数える
+        let line = Command::Line { x1: 0, y1: 0, x2: 10, y2: 10, width: 1, color: RGB::white() };
+        assert!(manager.send_command_to_device("notif_atoms3_2", line.clone()).await.is_err());
+        assert!(manager.send_sequenced_to_device("notif_atoms3_2", vec![line]).await.is_err());
+        
+        let stats = manager.get_statistics().await;
+        assert_eq!(stats.by_command["circle"].bytes_sent, encoded_bytes(std::slice::from_ref(&adapted)));
+        assert_eq!(stats.by_command["line"].errors, 1);
+        assert_eq!(stats.devices["notif_atoms3_2"].total.errors, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_event_bus_lifecycle() {
+        let devices = devices(1);
+        let manager = manager(&devices);
+        let mut events = manager.subscribe_events();
+        manager.scan_and_connect_all().await.unwrap();
+        
+        manager.send_command_to_device("notif_atoms3_1", Command::Update).await.unwrap();
+        
+        // 送信失敗で切断を検出し、自動再接続する
+        devices[0].drop_connection();
+        assert!(manager.send_command_to_device("notif_atoms3_1", Command::Update).await.is_err());
+        manager.disconnect_device("notif_atoms3_1").await.unwrap();
+        
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            assert_eq!(event.device_id(), "notif_atoms3_1");
+            received.push(serde_json::to_value(&event).unwrap());
+        }
+        let kinds: Vec<&str> = received.iter().map(|event| event["event"].as_str().unwrap()).collect();
+        assert_eq!(kinds, [
+            "connected", "command_succeeded", "disconnected", "reconnected", "command_failed", "disconnected",
+        ]);
+        assert_eq!((received[2]["requested"].as_bool(), received[5]["requested"].as_bool()), (Some(false), Some(true)));
+    }
+    
+    #[tokio::test]
+    async fn test_disconnect_events_without_auto_reconnect() {
+        let devices = devices(2);
+        let manager = connected_manager(&devices).await;
+        manager.set_auto_reconnect(false).await.unwrap();
+        let mut events = manager.subscribe_events();
+        
+        // 自動再接続が無効でも切断は1回だけ通知する
+        devices[0].drop_connection();
+        for _ in 0..2 {
+            assert!(manager.send_command_to_device("notif_atoms3_1", Command::Update).await.is_err());
+        }
+        manager.reconnect_device("notif_atoms3_1").await.unwrap();
+        
+        // 切断に失敗しても取り外したデバイスは切断として通知する
+        devices[1].fail_next_disconnects(1);
+        manager.remove_device("notif_atoms3_2").await.unwrap();
+        manager.disconnect_all().await.unwrap();
+        
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(serde_json::to_value(&event).unwrap());
+        }
+        let kinds: Vec<(&str, &str)> = received.iter()
+            .map(|event| (event["device_id"].as_str().unwrap(), event["event"].as_str().unwrap()))
+            .collect();
+        assert_eq!(kinds, [
+            ("notif_atoms3_1", "disconnected"),
+            ("notif_atoms3_1", "command_failed"),
+            ("notif_atoms3_1", "command_failed"),
+            ("notif_atoms3_1", "reconnected"),
+            ("notif_atoms3_2", "disconnected"),
+            ("notif_atoms3_1", "disconnected"),
+        ]);
+        let requested: Vec<Option<bool>> = [0, 4, 5].iter().map(|&index| received[index]["requested"].as_bool()).collect();
+        assert_eq!(requested, [Some(false), Some(true), Some(true)]);
+    }
+    
+    #[tokio::test]
+    async fn test_send_to_all_reports_each_device() {
+        let devices = devices(2);
+        for device in &devices {
+            device.set_latency(Duration::from_millis(300));
+        }
+        let manager = connected_manager(&devices).await;
+        
+        // 並行送信なので合計時間は1台分の遅延程度
+        devices[1].fail_next_sends(1);
+        let clear = Command::Clear { color: RGB::black() };
+        let report = tokio::time::timeout(Duration::from_millis(500), manager.send_command_to_all(clear.clone()))
+            .await.unwrap().unwrap();
+        
+        assert_eq!(report.succeeded(), vec!["notif_atoms3_1"]);
+        assert_eq!(report.failed().len(), 1);
+        assert_eq!(report.failed()[0].0, "notif_atoms3_2");
+        assert!(!report.all_succeeded());
+        assert_eq!(devices[0].commands().last(), Some(&clear));
+        
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["succeeded"], serde_json::json!(["notif_atoms3_1"]));
+        assert!(json["failed"]["notif_atoms3_2"].is_string());
+        assert!(report.into_result().is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_reconnect_backoff_and_give_up() {
+        let devices = devices(1);
+        let device = &devices[0];
+        let manager = manager(&devices);
+        manager.set_reconnect_policy(ReconnectPolicy {
+            max_attempts: 2,
+            initial_delay: Duration::ZERO,
+            jitter: 0.0,
+            ..ReconnectPolicy::default()
+        }).await;
+        manager.scan_and_connect_all().await.unwrap();
+        
+        let reconnect_state = || async { manager.list_connected_devices().await[0].reconnect.clone().unwrap() };
+        assert_eq!(reconnect_state().await, ReconnectState::Idle);
+        
+        // 送信失敗時の再接続が続けて失敗すると自動再接続を諦める
+        device.drop_connection();
+        device.fail_next_connects(5);
+        let clear = Command::Clear { color: RGB::black() };
+        assert!(manager.send_command_to_device("notif_atoms3_1", clear.clone()).await.is_err());
+        assert!(matches!(reconnect_state().await, ReconnectState::Backoff { attempts: 1, .. }));
+        assert!(manager.send_command_to_device("notif_atoms3_1", clear.clone()).await.is_err());
+        assert!(matches!(reconnect_state().await, ReconnectState::GaveUp { attempts: 2, .. }));
+        
+        // 停止後は試行しない
+        assert!(manager.send_command_to_device("notif_atoms3_1", clear.clone()).await.is_err());
+        assert!(matches!(reconnect_state().await, ReconnectState::GaveUp { attempts: 2, .. }));
+        
+        // 手動の再接続で復帰
+        device.fail_next_connects(0);
+        manager.reconnect_device("notif_atoms3_1").await.unwrap();
+        assert_eq!(reconnect_state().await, ReconnectState::Idle);
+    }
+    
+    #[tokio::test]
+    async fn test_command_timeout_applies_per_frame() {
+        let devices = devices(1);
+        let manager = connected_manager(&devices).await;
+        let clear = Command::Clear { color: RGB::black() };
+        
+        // 複数フレームのコマンドは全体で超えても、各フレームが間に合えば打ち切らない
+        devices[0].set_latency(Duration::from_millis(30));
+        manager.set_command_timeout(Some(Duration::from_millis(50))).await;
+        let batch = Command::Batch { commands: vec![clear.clone(); 600] };
+        assert_eq!(batch.encode_frames().unwrap().len(), 3);
+        manager.send_command_to_device("notif_atoms3_1", batch).await.unwrap();
+        
+        devices[0].set_latency(Duration::from_millis(300));
+        let result = manager.send_command_to_device("notif_atoms3_1", clear).await;
+        assert!(matches!(result, Err(NotifError::Timeout(_))));
+    }
+}
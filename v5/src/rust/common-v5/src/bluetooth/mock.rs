@@ -0,0 +1,429 @@
+//! モックBluetoothバックエンド（`mock`フィーチャー）
+//! 
+//! 実機なしで`CommonBluetoothManager`やAPIハンドラーをテストするための
+//! `Scanner`/`Connection`実装。接続失敗・送信失敗・遅延・切断をスクリプトでき、
+//! デバイスが受信したコマンドを記録する
+
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use tracing::debug;
+
+use crate::error::{NotifError, Result};
+use crate::protocol::{ButtonEvent, ButtonPress, Command, DeviceSetting};
+use super::manager::CommonBluetoothManager;
+use super::traits::{with_frame_timeout, AckPolicy, Connection, DeviceCapabilities, DeviceInfo, Scanner};
+
+/// ボタンイベントのバッファ数
+const BUTTON_EVENT_CAPACITY: usize = 16;
+
+/// 次に作成するデバイスのアドレス（デバイスごとに一意にする）
+static NEXT_ADDRESS: AtomicU16 = AtomicU16::new(1);
+
+/// モックデバイスの状態
+#[derive(Debug)]
+struct MockState {
+    info: DeviceInfo,
+    
+    /// デバイスが受信したコマンド（フレーム単位でデコードしたもの）
+    log: Vec<Command>,
+    
+    /// 書き込まれた設定
+    settings: Vec<DeviceSetting>,
+    
+    /// 接続・送信ごとの遅延
+    latency: Duration,
+    
+    /// 残りの接続失敗回数（接続・再接続の両方に適用）
+    failing_connects: u32,
+    
+    /// 残りの送信失敗回数
+    failing_sends: u32,
+    
+    /// 残りの切断失敗回数
+    failing_disconnects: u32,
+    
+    /// 再接続に成功した回数
+    reconnects: u32,
+    
+    /// 設定されている応答確認ポリシー
+    ack_policy: Option<AckPolicy>,
+    
+    /// スキャンで見つかるか（falseで電源オフを再現）
+    advertising: bool,
+}
+
+/// モックデバイス
+/// 
+/// クローンは同じデバイスを指すため、テスト側で保持したハンドルから
+/// 接続中のデバイスを操作・検査できる
+#[derive(Debug, Clone)]
+pub struct MockDevice {
+    state: Arc<Mutex<MockState>>,
+    events_tx: broadcast::Sender<ButtonEvent>,
+    battery_tx: Arc<watch::Sender<Option<u8>>>,
+}
+
+impl MockDevice {
+    /// 既定の機能（128x128）を持つデバイスを作成
+    pub fn new(name: &str) -> Self {
+        MockDevice {
+            state: Arc::new(Mutex::new(MockState {
+                info: DeviceInfo {
+                    name: name.to_string(),
+                    address: format!("00:00:00:00:{:04X}", NEXT_ADDRESS.fetch_add(1, Ordering::Relaxed)),
+                    connected: false,
+                    number: None,
+                    alias: None,
+                    signal_strength: Some(-50),
+                    battery_level: Some(100),
+                    capabilities: DeviceCapabilities::default(),
+                    firmware_version: Some("mock".to_string()),
+                    reconnect: None,
+                    statistics: None,
+                },
+                log: Vec::new(),
+                settings: Vec::new(),
+                latency: Duration::ZERO,
+                failing_connects: 0,
+                failing_sends: 0,
+                failing_disconnects: 0,
+                reconnects: 0,
+                ack_policy: None,
+                advertising: true,
+            })),
+            events_tx: broadcast::channel(BUTTON_EVENT_CAPACITY).0,
+            battery_tx: Arc::new(watch::channel(Some(100)).0),
+        }
+    }
+    
+    /// デバイス機能を設定（接続時に読み出されるため、接続前に設定する）
+    pub fn set_capabilities(&self, capabilities: DeviceCapabilities) {
+        self.lock().info.capabilities = capabilities;
+    }
+    
+    /// 接続・送信ごとの遅延を設定（接続中の送信にもすぐ反映される）
+    pub fn set_latency(&self, latency: Duration) {
+        self.lock().latency = latency;
+    }
+    
+    /// バッテリーレベルの変化を再現（Battery Levelの通知）
+    pub fn set_battery_level(&self, level: Option<u8>) {
+        self.lock().info.battery_level = level;
+        self.battery_tx.send_replace(level);
+    }
+    
+    /// デバイス名
+    pub fn name(&self) -> String {
+        self.lock().info.name.clone()
+    }
+    
+    /// Bluetoothアドレス
+    pub fn address(&self) -> String {
+        self.lock().info.address.clone()
+    }
+    
+    /// 次の`count`回の接続・再接続を失敗させる
+    pub fn fail_next_connects(&self, count: u32) {
+        self.lock().failing_connects = count;
+    }
+    
+    /// 次の`count`回の送信を失敗させる
+    pub fn fail_next_sends(&self, count: u32) {
+        self.lock().failing_sends = count;
+    }
+    
+    /// 次の`count`回の切断を失敗させる
+    pub fn fail_next_disconnects(&self, count: u32) {
+        self.lock().failing_disconnects = count;
+    }
+    
+    /// スキャンで見つかるかを設定（電源のオン・オフを再現）
+    pub fn set_advertising(&self, advertising: bool) {
+        self.lock().advertising = advertising;
+    }
+    
+    /// デバイス側からの切断（電源断・電波切れ）を再現
+    pub fn drop_connection(&self) {
+        self.lock().info.connected = false;
+    }
+    
+    /// ボタン押下を再現
+    pub fn press_button(&self, button: u8, press: ButtonPress) {
+        let _ = self.events_tx.send(ButtonEvent { button, press });
+    }
+    
+    /// 接続中か
+    pub fn is_connected(&self) -> bool {
+        self.lock().info.connected
+    }
+    
+    /// 受信したコマンド
+    pub fn commands(&self) -> Vec<Command> {
+        self.lock().log.clone()
+    }
+    
+    /// 受信したコマンドの記録をクリア
+    pub fn clear_commands(&self) {
+        self.lock().log.clear();
+    }
+    
+    /// 書き込まれた設定
+    pub fn settings(&self) -> Vec<DeviceSetting> {
+        self.lock().settings.clone()
+    }
+    
+    /// 再接続に成功した回数
+    pub fn reconnect_count(&self) -> u32 {
+        self.lock().reconnects
+    }
+    
+    /// 設定されている応答確認ポリシー
+    pub fn ack_policy(&self) -> Option<AckPolicy> {
+        self.lock().ack_policy
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        // テスト中のパニックで汚染されても状態は検査できるようにする
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    fn latency(&self) -> Duration {
+        self.lock().latency
+    }
+    
+    /// 接続を試みる（スクリプトされた失敗回数が残っていれば失敗）
+    fn try_connect(&self) -> Result<()> {
+        let mut state = self.lock();
+        if state.failing_connects > 0 {
+            state.failing_connects -= 1;
+            return Err(NotifError::Connection(format!("Mock connection to {} failed", state.info.name)));
+        }
+        state.info.connected = true;
+        Ok(())
+    }
+}
+
+/// モックスキャナー
+#[derive(Debug, Clone, Default)]
+pub struct MockScanner {
+    devices: Vec<MockDevice>,
+}
+
+impl MockScanner {
+    pub fn new(devices: Vec<MockDevice>) -> Self {
+        MockScanner { devices }
+    }
+    
+    /// このスキャナーを使うマネージャーを作成
+    pub fn into_manager(self, device_name_prefix: &str) -> CommonBluetoothManager {
+        CommonBluetoothManager::new(device_name_prefix.to_string(), move || {
+            Ok(Box::new(self.clone()) as Box<dyn Scanner>)
+        })
+    }
+    
+    fn find(&self, device_name: &str) -> Option<&MockDevice> {
+        self.devices.iter().find(|device| device.name() == device_name)
+    }
+}
+
+#[async_trait]
+impl Scanner for MockScanner {
+    async fn scan(&self, prefix: &str, _timeout: Duration) -> Result<Vec<DeviceInfo>> {
+        Ok(self.devices.iter()
+            .filter(|device| device.lock().advertising)
+            .map(|device| device.lock().info.clone())
+            .filter(|info| info.name.starts_with(prefix))
+            .collect())
+    }
+    
+    async fn scan_for_device(&self, device_name: &str, _timeout: Duration) -> Result<Option<DeviceInfo>> {
+        Ok(self.find(device_name)
+            .filter(|device| device.lock().advertising)
+            .map(|device| device.lock().info.clone()))
+    }
+    
+    async fn connect(&self, device_info: &DeviceInfo) -> Result<Box<dyn Connection>> {
+        let device = self.find(&device_info.name)
+            .ok_or_else(|| NotifError::DeviceNotFound(device_info.name.clone()))?
+            .clone();
+        
+        tokio::time::sleep(device.latency()).await;
+        device.try_connect()?;
+        debug!("Mock device connected: {}", device_info.name);
+        Ok(Box::new(MockConnection { device, command_timeout: None }))
+    }
+    
+    async fn stop_scan(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// モック接続
+#[derive(Debug)]
+pub struct MockConnection {
+    device: MockDevice,
+    /// 1フレームの送信タイムアウト
+    command_timeout: Option<Duration>,
+}
+
+impl MockConnection {
+    /// スキャナーを介さずに接続済みの接続を作成
+    pub fn new(device: MockDevice) -> Self {
+        device.lock().info.connected = true;
+        MockConnection { device, command_timeout: None }
+    }
+}
+
+#[async_trait]
+impl Connection for MockConnection {
+    async fn send_command(&mut self, command: Command) -> Result<()> {
+        // 実機と同じくフレームにエンコードし、1フレームずつ書き込む（書き込みごとに遅延）
+        for (index, frame) in command.encode_frames()?.into_iter().enumerate() {
+            let latency = self.device.latency();
+            with_frame_timeout(self.command_timeout, async {
+                tokio::time::sleep(latency).await;
+                Ok(())
+            }).await?;
+            
+            let mut state = self.device.lock();
+            if !state.info.connected {
+                return Err(NotifError::DeviceNotConnected(state.info.name.clone()));
+            }
+            if index == 0 && state.failing_sends > 0 {
+                state.failing_sends -= 1;
+                return Err(NotifError::Bluetooth(format!("Mock write to {} failed", state.info.name)));
+            }
+            
+            // デバイス側でデコードした形で記録
+            let (received, _) = Command::decode(&frame)?;
+            state.log.push(received);
+        }
+        Ok(())
+    }
+    
+    async fn is_connected(&self) -> bool {
+        self.device.is_connected()
+    }
+    
+    async fn get_device_info(&self) -> DeviceInfo {
+        self.device.lock().info.clone()
+    }
+    
+    async fn disconnect(&mut self) -> Result<()> {
+        {
+            let mut state = self.device.lock();
+            if state.failing_disconnects > 0 {
+                state.failing_disconnects -= 1;
+                return Err(NotifError::Bluetooth(format!("Mock disconnect from {} failed", state.info.name)));
+            }
+        }
+        self.device.drop_connection();
+        Ok(())
+    }
+    
+    async fn reconnect(&mut self) -> Result<()> {
+        tokio::time::sleep(self.device.latency()).await;
+        self.device.try_connect()?;
+        self.device.lock().reconnects += 1;
+        Ok(())
+    }
+    
+    async fn get_battery_level(&self) -> Option<u8> {
+        self.device.lock().info.battery_level
+    }
+    
+    async fn get_signal_strength(&self) -> Option<i8> {
+        self.device.lock().info.signal_strength
+    }
+    
+    fn set_ack_policy(&mut self, policy: Option<AckPolicy>) {
+        self.device.lock().ack_policy = policy;
+    }
+    
+    fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+        self.command_timeout = timeout;
+    }
+    
+    async fn apply_setting(&mut self, setting: DeviceSetting) -> Result<()> {
+        setting.encode()?;
+        
+        let mut state = self.device.lock();
+        if !state.info.connected {
+            return Err(NotifError::DeviceNotConnected(state.info.name.clone()));
+        }
+        state.settings.push(setting);
+        Ok(())
+    }
+    
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<ButtonEvent>> {
+        Some(self.device.events_tx.subscribe())
+    }
+    
+    fn subscribe_battery(&self) -> Option<watch::Receiver<Option<u8>>> {
+        Some(self.device.battery_tx.subscribe())
+    }
+}
+
+/// マネージャーのテスト用の共通セットアップ
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+    use crate::bluetooth::BluetoothManager;
+    
+    /// `notif_atoms3_1`から順に番号を振ったデバイス
+    pub(crate) fn devices(count: usize) -> Vec<MockDevice> {
+        (1..=count).map(|number| MockDevice::new(&format!("notif_atoms3_{}", number))).collect()
+    }
+    
+    /// デバイスをスキャンできるマネージャー（未接続）
+    pub(crate) fn manager(devices: &[MockDevice]) -> CommonBluetoothManager {
+        MockScanner::new(devices.to_vec()).into_manager("notif_")
+    }
+    
+    /// 全デバイスに接続済みのマネージャー
+    pub(crate) async fn connected_manager(devices: &[MockDevice]) -> CommonBluetoothManager {
+        let manager = manager(devices);
+        manager.scan_and_connect_all().await.unwrap();
+        manager
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RGB;
+    
+    #[tokio::test]
+    async fn test_mock_connection_records_and_fails() {
+        let device = MockDevice::new("notif_atoms3_1");
+        let scanner = MockScanner::new(vec![device.clone(), MockDevice::new("other")]);
+        
+        let found = scanner.scan("notif_", Duration::from_secs(1)).await.unwrap();
+        assert_eq!(found.len(), 1);
+        
+        // スクリプトされた接続失敗
+        device.fail_next_connects(1);
+        assert!(scanner.connect(&found[0]).await.is_err());
+        let mut connection = scanner.connect(&found[0]).await.unwrap();
+        assert!(device.is_connected());
+        
+        let clear = Command::Clear { color: RGB::black() };
+        connection.send_command(clear.clone()).await.unwrap();
+        device.fail_next_sends(1);
+        assert!(connection.send_command(clear.clone()).await.is_err());
+        assert_eq!(device.commands(), vec![clear.clone()]);
+        
+        // 切断中は送信できず、再接続で復帰
+        device.drop_connection();
+        assert!(!connection.is_connected().await);
+        assert!(connection.send_command(clear.clone()).await.is_err());
+        connection.reconnect().await.unwrap();
+        assert_eq!(device.reconnect_count(), 1);
+        
+        connection.set_brightness(80).await.unwrap();
+        assert_eq!(device.settings(), vec![DeviceSetting::Brightness(80)]);
+    }
+}
@@ -2,8 +2,18 @@
 
 pub mod traits;
 pub mod manager;
+pub mod assets;
+pub mod battery;
+pub mod buttons;
+pub mod discovery;
+pub mod events;
+pub mod numbering;
+pub mod reconnect;
+pub mod stats;
+pub mod virtual_display;
+mod worker;
 
-#[cfg(feature = "mock")]
+#[cfg(any(test, feature = "mock"))]
 pub mod mock;
 
 // 再エクスポート
@@ -14,7 +24,21 @@ pub use traits::{
     DeviceInfo,
     DeviceCapabilities,
     DeviceStatistics,
+    SendReport,
+    AckPolicy,
     PlatformData,
+    with_frame_timeout,
 };
 
-pub use manager::CommonBluetoothManager;
\ No newline at end of file
+pub use manager::CommonBluetoothManager;
+pub use assets::AssetRegistry;
+pub use battery::{DeviceBatteryEvent, DEFAULT_LOW_BATTERY_THRESHOLD};
+pub use buttons::{spawn_button_actions, ButtonAction, ButtonBindings, DeviceButtonEvent};
+pub use discovery::{DeviceConnectionEvent, DeviceFilter, DiscoveryPolicy};
+pub use events::NotifEvent;
+pub use numbering::{DeviceAssignment, DeviceNumbering, DevicePin};
+pub use reconnect::{ReconnectPolicy, ReconnectState};
+pub use stats::{CommandStatistics, LatencyPercentiles, TrafficStatistics};
+pub use virtual_display::VirtualConnection;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::{MockConnection, MockDevice, MockScanner};
@@ -0,0 +1,339 @@
+//! デバイス番号・エイリアス・グループの割り当て
+//! 
+//! 発見順ではなくデバイス名（名前が変わった場合はアドレス）ごとに番号を割り当て、
+//! 状態ファイルに保存して再起動後も同じ実機を同じ番号・エイリアスで呼べるようにする。
+//! グループは複数のデバイスをまとめて `device=group:desk` のように指定するためのもの
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::{NotifError, Result};
+
+/// 1台分の割り当て
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceAssignment {
+    /// デバイス番号（1から始まる）
+    pub number: usize,
+    
+    /// エイリアス（`device=desk` のように名前の代わりに使える）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    
+    /// Bluetoothアドレス（デバイス名が変わっても同じ実機と判断するため）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    
+    /// 設定・APIで固定した番号か
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// 番号・エイリアスの固定指定（設定ファイル・APIで使う）
+/// 
+/// TOML例:
+/// ```toml
+/// [devices.pinned.notif_atoms3_c9d8ec]
+/// number = 1
+/// alias = "desk"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DevicePin {
+    /// 固定する番号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<usize>,
+    
+    /// 設定するエイリアス（空文字列で削除）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+/// グループ指定のプレフィックス（`group:desk`）
+pub const GROUP_PREFIX: &str = "group:";
+
+/// デバイス名 -> 割り当て、グループ名 -> メンバー（状態ファイルの内容）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceNumbering {
+    #[serde(default)]
+    devices: BTreeMap<String, DeviceAssignment>,
+    
+    /// メンバーはデバイス名・エイリアス・番号のいずれか（送信時に解決する）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    groups: BTreeMap<String, Vec<String>>,
+}
+
+impl DeviceNumbering {
+    /// 状態ファイルから読み込む（ファイルがなければ空）
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DeviceNumbering::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    /// 状態ファイルに保存（書き込み途中で落ちても壊れないよう一時ファイルから置き換える）
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+    
+    /// 接続したデバイスの番号を返す（初めてのデバイスには空いている最小の番号を割り当てる）
+    /// 
+    /// 割り当てを変更した場合は2つ目の値がtrue
+    pub fn assign(&mut self, name: &str, address: Option<&str>) -> (usize, bool) {
+        if let Some(assignment) = self.devices.get_mut(name) {
+            let changed = address.is_some() && assignment.address.as_deref() != address;
+            if changed {
+                assignment.address = address.map(str::to_string);
+            }
+            return (assignment.number, changed);
+        }
+        
+        // 名前を変えた実機はアドレスで引き継ぐ
+        let renamed = address.and_then(|address| {
+            self.devices.iter()
+                .find(|(_, assignment)| assignment.address.as_deref() == Some(address))
+                .map(|(old_name, _)| old_name.clone())
+        });
+        let assignment = match renamed.and_then(|old_name| self.devices.remove(&old_name)) {
+            Some(assignment) => assignment,
+            None => DeviceAssignment {
+                number: self.free_number(None),
+                alias: None,
+                address: address.map(str::to_string),
+                pinned: false,
+            },
+        };
+        
+        let number = assignment.number;
+        self.devices.insert(name.to_string(), assignment);
+        (number, true)
+    }
+    
+    /// 番号・エイリアスを固定する
+    /// 
+    /// 後から指定した方が優先され、番号やエイリアスが重なったデバイスからは外す
+    pub fn pin(&mut self, name: &str, pin: &DevicePin) -> Result<DeviceAssignment> {
+        if let Some(number) = pin.number {
+            if number == 0 {
+                return Err(NotifError::InvalidParameter("Device numbers start at 1".to_string()));
+            }
+        }
+        let alias = pin.alias.as_deref().map(str::trim);
+        if let Some(alias) = alias.filter(|alias| !alias.is_empty()) {
+            validate_alias(alias)?;
+            if alias != name && self.devices.contains_key(alias) {
+                return Err(NotifError::InvalidParameter(format!("Alias {} is already a device name", alias)));
+            }
+        }
+        
+        if !self.devices.contains_key(name) {
+            self.assign(name, None);
+        }
+        
+        if let Some(number) = pin.number {
+            if let Some(assignment) = self.devices.get_mut(name) {
+                assignment.number = number;
+                assignment.pinned = true;
+            }
+            
+            // 番号が重なったデバイスは空いている番号へ移す
+            let displaced = self.devices.iter()
+                .find(|(other, assignment)| other.as_str() != name && assignment.number == number)
+                .map(|(other, _)| other.clone());
+            if let Some(other) = displaced {
+                let free = self.free_number(Some(number));
+                if let Some(assignment) = self.devices.get_mut(&other) {
+                    assignment.number = free;
+                    assignment.pinned = false;
+                }
+            }
+        }
+        
+        if let Some(alias) = alias {
+            for (other, assignment) in self.devices.iter_mut() {
+                if other != name && assignment.alias.as_deref() == Some(alias) {
+                    assignment.alias = None;
+                }
+            }
+            if let Some(assignment) = self.devices.get_mut(name) {
+                assignment.alias = (!alias.is_empty()).then(|| alias.to_string());
+            }
+        }
+        
+        Ok(self.devices[name].clone())
+    }
+    
+    /// デバイスの割り当て
+    pub fn get(&self, name: &str) -> Option<&DeviceAssignment> {
+        self.devices.get(name)
+    }
+    
+    /// 番号が割り当てられたデバイス名
+    pub fn name_for_number(&self, number: usize) -> Option<&str> {
+        self.devices.iter()
+            .find(|(_, assignment)| assignment.number == number)
+            .map(|(name, _)| name.as_str())
+    }
+    
+    /// エイリアスのデバイス名
+    pub fn resolve_alias(&self, alias: &str) -> Option<&str> {
+        self.devices.iter()
+            .find(|(_, assignment)| assignment.alias.as_deref() == Some(alias))
+            .map(|(name, _)| name.as_str())
+    }
+    
+    /// グループのメンバーを設定（空の場合はグループを削除）
+    pub fn set_group(&mut self, group: &str, members: Vec<String>) -> Result<()> {
+        let group = group.trim();
+        if group.is_empty() || group.contains(':') {
+            return Err(NotifError::InvalidParameter(format!("Invalid group name: {:?}", group)));
+        }
+        
+        let members: Vec<String> = members.into_iter()
+            .map(|member| member.trim().to_string())
+            .filter(|member| !member.is_empty())
+            .collect();
+        if let Some(member) = members.iter().find(|member| *member == "all" || member.starts_with(GROUP_PREFIX)) {
+            return Err(NotifError::InvalidParameter(format!("Group member must be a single device: {}", member)));
+        }
+        
+        if members.is_empty() {
+            self.groups.remove(group);
+        } else {
+            self.groups.insert(group.to_string(), members);
+        }
+        Ok(())
+    }
+    
+    /// グループのメンバー（設定した値のまま）
+    pub fn group(&self, group: &str) -> Option<&[String]> {
+        self.groups.get(group).map(Vec::as_slice)
+    }
+    
+    /// 全グループ
+    pub fn groups(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.groups
+    }
+    
+    /// グループのメンバーをデバイス名に解決（番号・エイリアスを変換）
+    pub fn resolve_group(&self, group: &str) -> Option<Vec<String>> {
+        let members = self.group(group)?;
+        let mut device_names: Vec<String> = Vec::with_capacity(members.len());
+        for member in members {
+            let device_name = match member.parse::<usize>() {
+                Ok(number) => self.name_for_number(number).unwrap_or(member),
+                Err(_) => self.resolve_alias(member).unwrap_or(member),
+            };
+            if !device_names.iter().any(|name| name == device_name) {
+                device_names.push(device_name.to_string());
+            }
+        }
+        Some(device_names)
+    }
+    
+    /// 使われていない最小の番号（`reserved`も除く）
+    fn free_number(&self, reserved: Option<usize>) -> usize {
+        (1..)
+            .find(|number| {
+                Some(*number) != reserved
+                    && !self.devices.values().any(|assignment| assignment.number == *number)
+            })
+            .unwrap_or(1)
+    }
+}
+
+/// デバイス指定と紛らわしいエイリアスを拒否
+fn validate_alias(alias: &str) -> Result<()> {
+    if alias == "all" || alias.parse::<usize>().is_ok() || alias.starts_with(GROUP_PREFIX) {
+        return Err(NotifError::InvalidParameter(format!(
+            "Alias {} would be read as a device number, group or \"all\"", alias
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_numbers_survive_restart() {
+        let mut numbering = DeviceNumbering::default();
+        assert_eq!(numbering.assign("notif_b", Some("AA:02")), (1, true));
+        assert_eq!(numbering.assign("notif_a", Some("AA:01")), (2, true));
+        numbering.pin("notif_a", &DevicePin { number: None, alias: Some("desk".to_string()) }).unwrap();
+        
+        let path = std::env::temp_dir().join(format!("notif_numbering_{}.json", std::process::id()));
+        numbering.save(&path).unwrap();
+        let mut restored = DeviceNumbering::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        // 発見順が変わっても番号は変わらない
+        assert_eq!(restored.assign("notif_a", Some("AA:01")), (2, false));
+        assert_eq!(restored.assign("notif_b", Some("AA:02")), (1, false));
+        assert_eq!(restored.resolve_alias("desk"), Some("notif_a"));
+        
+        // 名前を変えた実機はアドレスで番号を引き継ぐ
+        assert_eq!(restored.assign("notif_renamed", Some("AA:02")), (1, true));
+        assert!(restored.get("notif_b").is_none());
+    }
+    
+    #[test]
+    fn test_pin_displaces_conflicts() {
+        let mut numbering = DeviceNumbering::default();
+        numbering.assign("notif_a", None);
+        numbering.assign("notif_b", None);
+        numbering.pin("notif_a", &DevicePin { number: None, alias: Some("desk".to_string()) }).unwrap();
+        
+        let pinned = numbering.pin("notif_b", &DevicePin { number: Some(1), alias: Some("desk".to_string()) }).unwrap();
+        assert_eq!((pinned.number, pinned.alias.as_deref(), pinned.pinned), (1, Some("desk"), true));
+        assert_eq!(numbering.get("notif_a").unwrap().number, 2);
+        assert_eq!(numbering.get("notif_a").unwrap().alias, None);
+        assert_eq!(numbering.name_for_number(1), Some("notif_b"));
+        
+        // 未接続のデバイスも固定できる
+        numbering.pin("notif_c", &DevicePin { number: Some(5), alias: None }).unwrap();
+        assert_eq!(numbering.name_for_number(5), Some("notif_c"));
+        
+        // 空文字列でエイリアスを削除
+        numbering.pin("notif_b", &DevicePin { number: None, alias: Some(String::new()) }).unwrap();
+        assert_eq!(numbering.resolve_alias("desk"), None);
+        
+        assert!(numbering.pin("notif_a", &DevicePin { number: Some(0), alias: None }).is_err());
+        assert!(numbering.pin("notif_a", &DevicePin { number: None, alias: Some("3".to_string()) }).is_err());
+        assert!(numbering.pin("notif_a", &DevicePin { number: None, alias: Some("notif_b".to_string()) }).is_err());
+    }
+    
+    #[test]
+    fn test_group_members_resolve() {
+        let mut numbering = DeviceNumbering::default();
+        numbering.assign("notif_a", None);
+        numbering.assign("notif_b", None);
+        numbering.pin("notif_b", &DevicePin { number: None, alias: Some("desk".to_string()) }).unwrap();
+        
+        // 番号・エイリアス・名前が混在しても重複なくデバイス名になる
+        let members = vec!["1".to_string(), "desk".to_string(), "notif_b".to_string(), "notif_offline".to_string()];
+        numbering.set_group("build-status", members).unwrap();
+        assert_eq!(
+            numbering.resolve_group("build-status").unwrap(),
+            vec!["notif_a".to_string(), "notif_b".to_string(), "notif_offline".to_string()]
+        );
+        assert_eq!(numbering.resolve_group("unknown"), None);
+        
+        assert!(numbering.set_group("bad:name", vec!["notif_a".to_string()]).is_err());
+        assert!(numbering.set_group("nested", vec!["group:build-status".to_string()]).is_err());
+        assert!(numbering.pin("notif_a", &DevicePin { number: None, alias: Some("group:x".to_string()) }).is_err());
+        
+        // 空のメンバーで削除
+        numbering.set_group("build-status", Vec::new()).unwrap();
+        assert!(numbering.groups().is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+//! 再接続ポリシー
+//! 
+//! 切断されたデバイスへの再接続を指数バックオフ（揺らぎ付き）で行い、
+//! 連続して失敗した回数が上限に達したら再接続を諦める
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// バックオフの倍率（失敗ごとに待機時間を倍にする）
+const BACKOFF_MULTIPLIER: f64 = 2.0;
+
+/// 再接続ポリシー
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    /// 連続失敗で諦めるまでの試行回数（0は諦めない。初回接続は1回のみ試行）
+    pub max_attempts: u32,
+    
+    /// 最初の再試行までの待機時間
+    pub initial_delay: Duration,
+    
+    /// 待機時間の上限
+    pub max_delay: Duration,
+    
+    /// 待機時間に加える揺らぎの割合（0.0〜1.0、複数デバイスの再接続が重ならないように）
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(300),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// `attempt`回目（1から）の失敗後、次の試行までの待機時間
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let base = (self.initial_delay.as_secs_f64() * BACKOFF_MULTIPLIER.powi(exponent))
+            .min(self.max_delay.as_secs_f64());
+        
+        let jitter = self.jitter.clamp(0.0, 1.0) * (2.0 * random_unit() - 1.0);
+        Duration::from_secs_f64((base * (1.0 + jitter)).max(0.0))
+    }
+    
+    /// 連続失敗回数が上限に達したか
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        self.max_attempts > 0 && attempts >= self.max_attempts
+    }
+    
+    /// 初回接続の試行回数
+    pub fn connect_attempts(&self) -> u32 {
+        self.max_attempts.max(1)
+    }
+}
+
+/// 0.0以上1.0未満の乱数（揺らぎ用、暗号用途には使わない）
+fn random_unit() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    
+    // RandomStateは生成ごとに異なる鍵を持つ
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// デバイスの再接続状態（/api/devicesで公開）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ReconnectState {
+    /// 接続中、または切断を検出して最初の試行待ち
+    Idle,
+    
+    /// 再接続に失敗し、次の試行を待っている
+    Backoff {
+        /// 連続失敗回数
+        attempts: u32,
+        
+        /// 次の試行までの残り時間（ミリ秒）
+        next_attempt_in_ms: u64,
+        
+        /// 直近のエラー
+        last_error: String,
+    },
+    
+    /// 連続失敗が上限に達したため自動再接続を停止（手動の再接続で復帰）
+    GaveUp {
+        /// 連続失敗回数
+        attempts: u32,
+        
+        /// 直近のエラー
+        last_error: String,
+    },
+}
+
+/// デバイスごとの再接続の進行状況
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReconnectTracker {
+    attempts: u32,
+    next_attempt: Option<Instant>,
+    gave_up: bool,
+    last_error: String,
+}
+
+impl ReconnectTracker {
+    /// 再接続を試行してよいか（バックオフ中・停止後はfalse）
+    pub(crate) fn is_due(&self, now: Instant) -> bool {
+        !self.gave_up && self.next_attempt.is_none_or(|at| now >= at)
+    }
+    
+    /// 再接続の失敗を記録し、上限に達したらtrueを返す
+    pub(crate) fn record_failure(&mut self, policy: &ReconnectPolicy, error: &str, now: Instant) -> bool {
+        self.attempts += 1;
+        self.last_error = error.to_string();
+        
+        if policy.is_exhausted(self.attempts) {
+            self.gave_up = true;
+            self.next_attempt = None;
+        } else {
+            self.next_attempt = Some(now + policy.delay(self.attempts));
+        }
+        self.gave_up
+    }
+    
+    /// 連続失敗回数
+    pub(crate) fn attempts(&self) -> u32 {
+        self.attempts
+    }
+    
+    /// 公開用の状態
+    pub(crate) fn state(&self, now: Instant) -> ReconnectState {
+        if self.gave_up {
+            return ReconnectState::GaveUp {
+                attempts: self.attempts,
+                last_error: self.last_error.clone(),
+            };
+        }
+        
+        match self.next_attempt {
+            Some(at) => ReconnectState::Backoff {
+                attempts: self.attempts,
+                next_attempt_in_ms: at.saturating_duration_since(now).as_millis() as u64,
+                last_error: self.last_error.clone(),
+            },
+            None => ReconnectState::Idle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_backoff_grows_and_gives_up() {
+        let policy = ReconnectPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(3),
+            jitter: 0.0,
+        };
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(3), Duration::from_secs(3));
+        
+        // 揺らぎは指定した割合の範囲に収まる
+        let jittered = ReconnectPolicy { jitter: 0.5, ..policy };
+        let delay = jittered.delay(2).as_secs_f64();
+        assert!((1.0..=3.0).contains(&delay));
+        
+        let now = Instant::now();
+        let mut tracker = ReconnectTracker::default();
+        assert!(tracker.is_due(now));
+        assert!(!tracker.record_failure(&policy, "timeout", now));
+        assert!(!tracker.is_due(now));
+        assert!(tracker.is_due(now + Duration::from_secs(1)));
+        assert!(matches!(tracker.state(now), ReconnectState::Backoff { attempts: 1, .. }));
+        
+        assert!(!tracker.record_failure(&policy, "timeout", now));
+        assert!(tracker.record_failure(&policy, "timeout", now));
+        assert!(!tracker.is_due(now + Duration::from_secs(3600)));
+        assert_eq!(tracker.state(now), ReconnectState::GaveUp { attempts: 3, last_error: "timeout".to_string() });
+        
+        // 0は諦めない
+        let unlimited = ReconnectPolicy { max_attempts: 0, ..policy };
+        assert!(!unlimited.is_exhausted(1000));
+        assert_eq!(unlimited.connect_attempts(), 1);
+    }
+}
@@ -0,0 +1,235 @@
+//! コマンド送信の統計
+//! 
+//! 短いテキストと大きな画像タイルでは送信時間が桁違いのため、
+//! デバイスごと・コマンドの種類ごとに件数・エラー・送信バイト数・レイテンシーを集計する。
+//! レイテンシーは固定バケットのヒストグラムで保持し、長時間動かしてもメモリは増えない
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::protocol::Command;
+
+/// レイテンシーのバケット上限（ミリ秒、およそ1-1.5-2-3-5-7刻み）
+const LATENCY_BUCKETS_MS: [u64; 27] = [
+    1, 2, 3, 5, 7, 10, 15, 20, 30, 50, 70, 100, 150, 200, 300, 500, 700,
+    1000, 1500, 2000, 3000, 5000, 7000, 10000, 15000, 30000, 60000,
+];
+
+/// レイテンシーのパーセンタイル（ミリ秒、バケット上限で近似）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+/// コマンド送信の統計
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandStatistics {
+    /// 送信に成功したコマンド数
+    pub commands_sent: u64,
+    
+    /// 送信に失敗したコマンド数
+    pub errors: u64,
+    
+    /// 送信したバイト数（エンコード後のフレーム長の合計）
+    pub bytes_sent: u64,
+    
+    /// 成功した送信のレイテンシー（キュー待ちを含まない）
+    pub latency_ms: LatencyPercentiles,
+}
+
+/// 全体とコマンドの種類ごとの統計
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrafficStatistics {
+    #[serde(flatten)]
+    pub total: CommandStatistics,
+    
+    /// コマンドの種類（`text`、`image`等） -> 統計
+    #[serde(default)]
+    pub by_command: BTreeMap<String, CommandStatistics>,
+}
+
+/// 統計上のコマンドの種類
+/// 
+/// 圧縮タイルをまとめたBatchは画像として数える
+pub(crate) fn command_kind(command: &Command) -> &'static str {
+    match command {
+        Command::Text { .. } => "text",
+        Command::Clear { .. } => "clear",
+        Command::Line { .. } => "line",
+        Command::Rect { .. } => "rect",
+        Command::Circle { .. } => "circle",
+        Command::Image { .. } => "image",
+        Command::Emoji { .. } => "emoji",
+        Command::Region { .. } => "region",
+        Command::Batch { commands } if !commands.is_empty()
+            && commands.iter().all(|c| matches!(c, Command::Image { .. })) => "image",
+        Command::Batch { .. } => "batch",
+        Command::Update => "update",
+        Command::Sequenced { .. } => "sequenced",
+        Command::DefineAsset { .. } | Command::AssetData { .. } => "asset_upload",
+        Command::DrawAsset { .. } => "draw_asset",
+    }
+}
+
+/// 送信するバイト数（エンコードできないコマンドは0）
+pub(crate) fn encoded_bytes(commands: &[Command]) -> u64 {
+    commands.iter()
+        .filter_map(|command| command.encode_frames().ok())
+        .flatten()
+        .map(|frame| frame.len() as u64)
+        .sum()
+}
+
+/// レイテンシーのヒストグラム
+#[derive(Debug, Clone, Default)]
+struct LatencyHistogram {
+    /// バケットごとの件数（最後の要素は最大のバケットを超えたもの）
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    max: u64,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency_ms: u64) {
+        let bucket = LATENCY_BUCKETS_MS.partition_point(|&upper| upper < latency_ms);
+        self.counts[bucket] += 1;
+        self.max = self.max.max(latency_ms);
+    }
+    
+    /// `quantile`（0.0〜1.0）のレイテンシー（そのバケットの上限。最大値を超えない）
+    fn quantile(&self, quantile: f64) -> u64 {
+        let total: u64 = self.counts.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+        
+        let rank = ((total as f64 * quantile).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let upper = LATENCY_BUCKETS_MS.get(bucket).copied().unwrap_or(self.max);
+                return upper.min(self.max);
+            }
+        }
+        self.max
+    }
+    
+    fn percentiles(&self) -> LatencyPercentiles {
+        LatencyPercentiles {
+            p50: self.quantile(0.50),
+            p95: self.quantile(0.95),
+            p99: self.quantile(0.99),
+            max: self.max,
+        }
+    }
+}
+
+/// 1系統の集計値
+#[derive(Debug, Clone, Default)]
+struct CommandCounters {
+    sent: u64,
+    errors: u64,
+    bytes: u64,
+    latency: LatencyHistogram,
+}
+
+impl CommandCounters {
+    fn statistics(&self) -> CommandStatistics {
+        CommandStatistics {
+            commands_sent: self.sent,
+            errors: self.errors,
+            bytes_sent: self.bytes,
+            latency_ms: self.latency.percentiles(),
+        }
+    }
+}
+
+/// 全体とコマンドの種類ごとの集計
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrafficCounters {
+    total: CommandCounters,
+    by_command: BTreeMap<&'static str, CommandCounters>,
+}
+
+impl TrafficCounters {
+    /// 送信成功を記録
+    pub(crate) fn record_success(&mut self, kind: &'static str, bytes: u64, latency_ms: u64) {
+        let by_command = self.by_command.entry(kind).or_default();
+        for counters in [&mut self.total, by_command] {
+            counters.sent += 1;
+            counters.bytes += bytes;
+            counters.latency.record(latency_ms);
+        }
+    }
+    
+    /// 送信失敗を記録
+    pub(crate) fn record_error(&mut self, kind: &'static str) {
+        self.total.errors += 1;
+        self.by_command.entry(kind).or_default().errors += 1;
+    }
+    
+    /// 公開用の統計
+    pub(crate) fn statistics(&self) -> TrafficStatistics {
+        TrafficStatistics {
+            total: self.total.statistics(),
+            by_command: self.by_command.iter()
+                .map(|(kind, counters)| (kind.to_string(), counters.statistics()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RGB;
+    
+    #[test]
+    fn test_latency_percentiles() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentiles(), LatencyPercentiles::default());
+        
+        // 90件は速く、10件は遅い
+        for _ in 0..90 {
+            histogram.record(4);
+        }
+        for _ in 0..9 {
+            histogram.record(180);
+        }
+        histogram.record(2500);
+        
+        let percentiles = histogram.percentiles();
+        assert_eq!(percentiles.p50, 5);
+        assert_eq!(percentiles.p95, 200);
+        assert_eq!(percentiles.p99, 200);
+        assert_eq!(percentiles.max, 2500);
+        
+        // 最大のバケットを超えた値は最大値で表す
+        histogram.record(90_000);
+        assert_eq!(histogram.quantile(1.0), 90_000);
+    }
+    
+    #[test]
+    fn test_traffic_by_command() {
+        let mut counters = TrafficCounters::default();
+        let clear = Command::Clear { color: RGB::black() };
+        let empty_batch = Command::Batch { commands: Vec::new() };
+        assert_eq!(command_kind(&clear), "clear");
+        assert_eq!(command_kind(&empty_batch), "batch");
+        
+        let bytes = encoded_bytes(std::slice::from_ref(&clear));
+        counters.record_success("clear", bytes, 3);
+        counters.record_success("image", 1000, 120);
+        counters.record_error("image");
+        
+        let statistics = counters.statistics();
+        assert_eq!((statistics.total.commands_sent, statistics.total.errors), (2, 1));
+        assert_eq!(statistics.total.bytes_sent, bytes + 1000);
+        assert_eq!(statistics.by_command["clear"].latency_ms.max, 3);
+        assert_eq!(statistics.by_command["image"].errors, 1);
+        assert_eq!(statistics.by_command["image"].latency_ms.p50, 120);
+    }
+}
@@ -2,10 +2,25 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::sync::Arc;
-use crate::error::Result;
-use crate::protocol::Command;
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+use crate::display::Framebuffer;
+use crate::error::{NotifError, Result};
+use crate::image::PaletteSize;
+use crate::protocol::{
+    capability_flag, image_format, Asset, ButtonEvent, CapabilityDescriptor, Command, DeviceSetting,
+    EXTENDED_COMMAND_VERSION, GRID_PIXELS,
+};
+use super::battery::DeviceBatteryEvent;
+use super::buttons::DeviceButtonEvent;
+use super::discovery::DeviceConnectionEvent;
+use super::events::NotifEvent;
+use super::numbering::{DeviceAssignment, DevicePin};
+use super::reconnect::ReconnectState;
+use super::stats::{CommandStatistics, LatencyPercentiles, TrafficStatistics};
 
 /// デバイス情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +37,10 @@ pub struct DeviceInfo {
     /// デバイス番号（1から始まる）
     pub number: Option<usize>,
     
+    /// エイリアス（マネージャーが設定）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    
     /// 信号強度（RSSI）
     pub signal_strength: Option<i8>,
     
@@ -30,6 +49,18 @@ pub struct DeviceInfo {
     
     /// デバイス機能
     pub capabilities: DeviceCapabilities,
+    
+    /// ファームウェアバージョン（機能ディスクリプタ非対応の場合はNone）
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+    
+    /// 再接続状態（マネージャーが設定）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconnect: Option<ReconnectState>,
+    
+    /// 送信の統計（マネージャーが設定）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistics: Option<TrafficStatistics>,
 }
 
 /// デバイス機能
@@ -47,6 +78,30 @@ pub struct DeviceCapabilities {
     /// 領域分割対応
     pub regions: bool,
     
+    /// 直線描画対応
+    #[serde(default = "default_true")]
+    pub lines: bool,
+    
+    /// 円描画対応（非対応の場合は直線にラスタライズして送信）
+    #[serde(default)]
+    pub circles: bool,
+    
+    /// RLE圧縮画像対応（非対応の場合は展開して送信）
+    #[serde(default)]
+    pub rle_images: bool,
+    
+    /// インデックスカラー画像対応（非対応の場合は展開して送信）
+    #[serde(default)]
+    pub indexed_images: bool,
+    
+    /// アセットキャッシュ対応（非対応の場合は毎回画像として送信）
+    #[serde(default)]
+    pub assets: bool,
+    
+    /// 拡張コマンドセット（u16座標）対応（非対応の場合は255を超える座標をエラーにする）
+    #[serde(default)]
+    pub extended_coords: bool,
+    
     /// 画面サイズ
     pub display_width: u32,
     pub display_height: u32,
@@ -62,6 +117,12 @@ impl Default for DeviceCapabilities {
             color: true,
             emoji: true,
             regions: true,
+            lines: true,
+            circles: false,
+            rle_images: false,
+            indexed_images: false,
+            assets: false,
+            extended_coords: false,
             display_width: 128,
             display_height: 128,
             color_depth: 16,
@@ -69,6 +130,198 @@ impl Default for DeviceCapabilities {
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
+impl DeviceCapabilities {
+    /// 機能ディスクリプタから作成
+    pub fn from_descriptor(descriptor: &CapabilityDescriptor) -> Self {
+        DeviceCapabilities {
+            display: descriptor.display_width > 0 && descriptor.display_height > 0,
+            color: descriptor.has(capability_flag::COLOR),
+            emoji: descriptor.has(capability_flag::EMOJI),
+            regions: descriptor.has(capability_flag::REGIONS),
+            lines: descriptor.has(capability_flag::LINES),
+            circles: descriptor.has(capability_flag::CIRCLES),
+            rle_images: descriptor.has(capability_flag::RLE_IMAGES),
+            indexed_images: descriptor.has(capability_flag::INDEXED_IMAGES),
+            assets: descriptor.has(capability_flag::ASSETS),
+            extended_coords: descriptor.extended_commands >= EXTENDED_COMMAND_VERSION,
+            display_width: descriptor.display_width as u32,
+            display_height: descriptor.display_height as u32,
+            color_depth: descriptor.color_depth,
+        }
+    }
+    
+    /// 両方のデバイスで使える機能（全デバイスへの同時送信用）
+    pub fn intersect(&self, other: &DeviceCapabilities) -> Self {
+        DeviceCapabilities {
+            display: self.display && other.display,
+            color: self.color && other.color,
+            emoji: self.emoji && other.emoji,
+            regions: self.regions && other.regions,
+            lines: self.lines && other.lines,
+            circles: self.circles && other.circles,
+            rle_images: self.rle_images && other.rle_images,
+            indexed_images: self.indexed_images && other.indexed_images,
+            assets: self.assets && other.assets,
+            extended_coords: self.extended_coords && other.extended_coords,
+            display_width: self.display_width.min(other.display_width),
+            display_height: self.display_height.min(other.display_height),
+            color_depth: self.color_depth.min(other.color_depth),
+        }
+    }
+    
+    /// 画面サイズ（フレームバッファ用）
+    pub fn screen_size(&self) -> (u16, u16) {
+        (
+            self.display_width.clamp(1, u16::MAX as u32) as u16,
+            self.display_height.clamp(1, u16::MAX as u32) as u16,
+        )
+    }
+    
+    /// 画像の変換先サイズ（拡張コマンドセット非対応の場合は255ピクセルまで）
+    pub fn image_size(&self) -> (u16, u16) {
+        let max = if self.extended_coords { u16::MAX as u32 } else { u8::MAX as u32 };
+        (
+            self.display_width.clamp(1, max) as u16,
+            self.display_height.clamp(1, max) as u16,
+        )
+    }
+    
+    /// テキストレイアウト用のグリッド数（128px = 32グリッド）
+    /// 
+    /// 文字幅を足してもu8に収まるよう240グリッドまで
+    pub fn grid_size(&self) -> (u8, u8) {
+        let grids = |pixels: u32| (pixels / GRID_PIXELS).clamp(1, 240) as u8;
+        (grids(self.display_width), grids(self.display_height))
+    }
+    
+    /// 色深度に合わせた減色パレット（16ビット以上なら減色不要）
+    pub fn palette_size(&self) -> Option<PaletteSize> {
+        match self.color_depth {
+            0..=1 => Some(PaletteSize::Colors2),
+            2..=3 => Some(PaletteSize::Colors4),
+            4..=7 => Some(PaletteSize::Colors16),
+            8..=15 => Some(PaletteSize::Colors256),
+            _ if !self.color => Some(PaletteSize::Colors2),
+            _ => None,
+        }
+    }
+    
+    /// デバイスが描画できる形にコマンドを変換する
+    /// 
+    /// 円非対応の場合は直線にラスタライズし、代替手段のないプリミティブはエラーにする
+    pub fn adapt_command(&self, command: Command) -> Result<Command> {
+        let unsupported = |what: &str| Err(NotifError::InvalidCommand(format!(
+            "{} is not supported by this device", what
+        )));
+        
+        if !self.display {
+            return unsupported("Drawing");
+        }
+        if !self.extended_coords && command.needs_extended() {
+            return unsupported("Coordinate beyond 255");
+        }
+        
+        match command {
+            Command::Line { .. } if !self.lines => unsupported("Line"),
+            Command::Circle { x, y, radius, color, filled } if !self.circles => {
+                if !self.lines {
+                    return unsupported("Circle");
+                }
+                // 拡張コマンドセット非対応なら、はみ出す部分は8ビット座標の範囲で切り捨てる
+                let limit = if self.extended_coords { u16::MAX } else { u8::MAX as u16 };
+                Ok(Command::batched(Command::rasterize_circle(x, y, radius, color, filled, limit)))
+            }
+            Command::Image { x, y, width, height, format: image_format::RLE_RGB565, data } if !self.rle_images => {
+                let pixels = crate::image::rgb565::rle_decode(&data)?;
+                Ok(Command::Image {
+                    x,
+                    y,
+                    width,
+                    height,
+                    format: image_format::RAW_RGB565,
+                    data: crate::image::rgb565::rgb565_to_bytes(&pixels),
+                })
+            }
+            Command::Image { x, y, width, height, format: image_format::INDEXED, data } if !self.indexed_images => {
+                let pixel_count = width as usize * height as usize;
+                let pixels = crate::image::palette::decode_indexed(&data, pixel_count)?;
+                Ok(Command::Image {
+                    x,
+                    y,
+                    width,
+                    height,
+                    format: image_format::RAW_RGB565,
+                    data: crate::image::rgb565::rgb565_to_bytes(&pixels),
+                })
+            }
+            Command::Emoji { .. } if !self.emoji => unsupported("Emoji"),
+            Command::Region { .. } if !self.regions => unsupported("Region"),
+            Command::Region { regions } => {
+                let regions = regions.into_iter()
+                    .map(|mut region| {
+                        region.content = Box::new(self.adapt_command(*region.content)?);
+                        Ok(region)
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Command::Region { regions })
+            }
+            Command::Batch { commands } => {
+                let commands = commands.into_iter()
+                    .map(|command| self.adapt_command(command))
+                    .collect::<Result<_>>()?;
+                Ok(Command::Batch { commands })
+            }
+            Command::Sequenced { seq, command } => Ok(Command::Sequenced {
+                seq,
+                command: Box::new(self.adapt_command(*command)?),
+            }),
+            command => Ok(command),
+        }
+    }
+}
+
+/// 応答確認ポリシー（STATUS_CHAR通知による配信確認）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AckPolicy {
+    /// ステータス通知の待機時間
+    pub timeout: Duration,
+    
+    /// Busy応答時の再送回数
+    pub busy_retries: u32,
+    
+    /// Busy応答後の再送待機時間
+    pub busy_retry_delay: Duration,
+}
+
+impl Default for AckPolicy {
+    fn default() -> Self {
+        AckPolicy {
+            timeout: Duration::from_millis(5000),
+            busy_retries: 3,
+            busy_retry_delay: Duration::from_millis(100),
+        }
+    }
+}
+
+/// 1フレームの送信にタイムアウトを適用する（Noneは無制限）
+/// 
+/// コマンド全体ではなくフレームごとに適用し、送信を始めた複数フレームのコマンドを途中で打ち切らない
+pub async fn with_frame_timeout<F>(timeout: Option<Duration>, send: F) -> Result<()>
+where
+    F: std::future::Future<Output = Result<()>>,
+{
+    let Some(timeout) = timeout else {
+        return send.await;
+    };
+    
+    tokio::time::timeout(timeout, send).await
+        .unwrap_or_else(|_| Err(NotifError::Timeout(format!("Frame was not sent within {}ms", timeout.as_millis()))))
+}
+
 /// Bluetooth接続トレイト
 #[async_trait]
 pub trait Connection: Send + Sync + Debug {
@@ -92,10 +345,78 @@ pub trait Connection: Send + Sync + Debug {
         None
     }
     
+    /// バッテリーレベルの変化を購読（再接続後も同じ受信側で受け取れる）
+    /// 
+    /// Battery Serviceを持たないデバイス・実装ではNone
+    fn subscribe_battery(&self) -> Option<watch::Receiver<Option<u8>>> {
+        None
+    }
+    
     /// 信号強度を取得（オプション）
     async fn get_signal_strength(&self) -> Option<i8> {
         None
     }
+    
+    /// シーケンス番号付きで複数コマンドを送信し、未確認のものだけを再送する
+    /// 
+    /// ステータス通知に対応しない実装では順次`send_command`で送信する
+    async fn send_sequenced(&mut self, commands: Vec<Command>, _max_rounds: u32) -> Result<()> {
+        for command in commands {
+            self.send_command(command).await?;
+        }
+        Ok(())
+    }
+    
+    /// 応答確認ポリシーを設定（Noneで応答を待たない）
+    /// 
+    /// ステータス通知に対応しない実装では無視される
+    fn set_ack_policy(&mut self, _policy: Option<AckPolicy>) {}
+    
+    /// 1フレームの送信タイムアウトを設定（Noneは無制限）
+    /// 
+    /// タイムアウトは`with_frame_timeout`でフレームごとに適用する。送信が詰まらない実装では無視される
+    fn set_command_timeout(&mut self, _timeout: Option<Duration>) {}
+    
+    /// デバイス設定を書き込む（CONFIG_CHAR）
+    /// 
+    /// 設定用キャラクタリスティックを持たない実装ではNotImplementedを返す
+    async fn apply_setting(&mut self, setting: DeviceSetting) -> Result<()> {
+        Err(NotifError::NotImplemented(format!("Device setting {:?}", setting)))
+    }
+    
+    /// 輝度を設定（0-255）
+    async fn set_brightness(&mut self, level: u8) -> Result<()> {
+        self.apply_setting(DeviceSetting::Brightness(level)).await
+    }
+    
+    /// ディスプレイをスリープ（true）/ 復帰（false）
+    async fn set_sleep(&mut self, sleep: bool) -> Result<()> {
+        self.apply_setting(DeviceSetting::Sleep(sleep)).await
+    }
+    
+    /// 画面の向きを設定（0/90/180/270度）
+    async fn set_rotation(&mut self, degrees: u16) -> Result<()> {
+        self.apply_setting(DeviceSetting::rotation_degrees(degrees)?).await
+    }
+    
+    /// BLEデバイス名を変更（再起動後に反映）
+    async fn set_device_name(&mut self, name: &str) -> Result<()> {
+        self.apply_setting(DeviceSetting::DeviceName(name.to_string())).await
+    }
+    
+    /// 機能ディスクリプタを読み直す（ファームウェア更新後の再接続など）
+    /// 
+    /// 非対応の実装では何もしない
+    async fn refresh_capabilities(&mut self) -> Result<()> {
+        Ok(())
+    }
+    
+    /// ボタンイベントを購読（再接続後も同じ受信側で受け取れる）
+    /// 
+    /// ステータス通知に対応しない実装ではNone
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<ButtonEvent>> {
+        None
+    }
 }
 
 /// デバイススキャナートレイト
@@ -145,8 +466,10 @@ pub trait BluetoothManager: Send + Sync {
         command: Command,
     ) -> Result<()>;
     
-    /// 全デバイスにコマンドを送信
-    async fn send_command_to_all(&self, command: Command) -> Result<()>;
+    /// 全デバイスにコマンドを並行して送信し、デバイスごとの結果を返す
+    /// 
+    /// 接続中のデバイスが1台もない場合のみErrを返す
+    async fn send_command_to_all(&self, command: Command) -> Result<SendReport>;
     
     /// デバイス番号を指定してコマンドを送信
     async fn send_command_by_number(
@@ -184,6 +507,70 @@ pub trait BluetoothManager: Send + Sync {
     
     /// v5追加: デバイス番号からデバイス名を取得
     async fn get_device_name_by_number(&self, number: usize) -> Option<String>;
+    
+    /// エイリアスをデバイス名に解決（エイリアスでなければそのまま返す）
+    async fn resolve_device(&self, name_or_alias: &str) -> String;
+    
+    /// デバイスの番号・エイリアスを固定（未接続のデバイスも指定できる）
+    async fn pin_device(&self, device_id: &str, pin: DevicePin) -> Result<DeviceAssignment>;
+    
+    /// グループのメンバーをデバイス名で取得（未定義のグループはNone）
+    async fn group_members(&self, group: &str) -> Option<Vec<String>>;
+    
+    /// グループのメンバーを設定（空の場合はグループを削除）
+    async fn set_group(&self, group: &str, members: Vec<String>) -> Result<()>;
+    
+    /// 全グループ（メンバーは設定した値のまま）
+    async fn list_groups(&self) -> BTreeMap<String, Vec<String>>;
+    
+    /// 複数コマンドをシーケンス番号付きで送信（欠落分のみ再送）
+    async fn send_sequenced_to_device(
+        &self,
+        device_id: &str,
+        commands: Vec<Command>,
+    ) -> Result<()>;
+    
+    /// アセットを登録（デバイスへの転送は初回描画時）
+    async fn register_asset(&self, id: u16, asset: Asset) -> Result<()>;
+    
+    /// アセットを描画（未転送のデバイスには先に転送する）
+    async fn draw_asset(&self, device_id: &str, id: u16, x: u16, y: u16) -> Result<()>;
+    
+    /// デバイス設定を書き込む（輝度・スリープ・向き・デバイス名）
+    async fn apply_settings_to_device(&self, device_id: &str, settings: Vec<DeviceSetting>) -> Result<()>;
+    
+    /// 全デバイスの接続イベントを購読
+    fn subscribe_connection_events(&self) -> broadcast::Receiver<DeviceConnectionEvent>;
+    
+    /// 全デバイスのボタンイベントを購読
+    fn subscribe_button_events(&self) -> broadcast::Receiver<DeviceButtonEvent>;
+    
+    /// 全デバイスの低バッテリー・回復イベントを購読
+    fn subscribe_battery_events(&self) -> broadcast::Receiver<DeviceBatteryEvent>;
+    
+    /// イベントバスを購読（接続・切断・再接続・コマンドの成否・画像転送の進捗・ボタン・バッテリー）
+    fn subscribe_events(&self) -> broadcast::Receiver<NotifEvent>;
+    
+    /// イベントバスにイベントを発行（マネージャー外で進む画像転送の進捗等）
+    fn publish_event(&self, event: NotifEvent);
+    
+    /// デバイスに表示中の画面（送信済みコマンドから描画したもの、未送信ならNone）
+    async fn get_screen(&self, device_id: &str) -> Option<Framebuffer>;
+    
+    /// 送信先の機能を取得（Noneは全デバイス、全デバイスで共通に使える範囲）
+    /// 
+    /// 該当デバイスがない場合は既定値
+    async fn target_capabilities(&self, number: Option<usize>) -> DeviceCapabilities {
+        self.list_connected_devices().await
+            .into_iter()
+            .filter(|info| number.is_none() || info.number == number)
+            .map(|info| info.capabilities)
+            .reduce(|common, capabilities| common.intersect(&capabilities))
+            .unwrap_or_default()
+    }
+    
+    /// 表示中の通知を消去（再接続時の復元対象からも外す）
+    async fn dismiss_device(&self, device_id: &str) -> Result<()>;
 }
 
 /// デバイス統計情報
@@ -206,6 +593,22 @@ pub struct DeviceStatistics {
     
     /// 稼働時間（秒）
     pub uptime_seconds: u64,
+    
+    /// 送信バイト総数
+    #[serde(default)]
+    pub bytes_sent: u64,
+    
+    /// 応答時間のパーセンタイル
+    #[serde(default)]
+    pub latency_ms: LatencyPercentiles,
+    
+    /// コマンドの種類ごとの統計
+    #[serde(default)]
+    pub by_command: BTreeMap<String, CommandStatistics>,
+    
+    /// デバイスごとの統計（切断したデバイスも含む）
+    #[serde(default)]
+    pub devices: BTreeMap<String, TrafficStatistics>,
 }
 
 impl Default for DeviceStatistics {
@@ -217,10 +620,71 @@ impl Default for DeviceStatistics {
             total_errors: 0,
             average_response_time_ms: 0.0,
             uptime_seconds: 0,
+            bytes_sent: 0,
+            latency_ms: LatencyPercentiles::default(),
+            by_command: BTreeMap::new(),
+            devices: BTreeMap::new(),
         }
     }
 }
 
+/// 複数デバイスへの送信結果
+/// 
+/// JSONでは `{"succeeded": ["notif_1"], "failed": {"notif_2": "エラー内容"}}` の形になる
+#[derive(Debug, Default)]
+pub struct SendReport {
+    /// デバイス名ごとの結果
+    pub results: BTreeMap<String, Result<()>>,
+}
+
+impl SendReport {
+    /// 1台分の結果から作成
+    pub fn single(device_id: &str, result: Result<()>) -> Self {
+        let mut report = SendReport::default();
+        report.results.insert(device_id.to_string(), result);
+        report
+    }
+    
+    /// 送信に成功したデバイス
+    pub fn succeeded(&self) -> Vec<&str> {
+        self.results.iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(device_id, _)| device_id.as_str())
+            .collect()
+    }
+    
+    /// 送信に失敗したデバイスとエラー
+    pub fn failed(&self) -> Vec<(&str, &NotifError)> {
+        self.results.iter()
+            .filter_map(|(device_id, result)| result.as_ref().err().map(|e| (device_id.as_str(), e)))
+            .collect()
+    }
+    
+    /// 全デバイスへの送信に成功したか
+    pub fn all_succeeded(&self) -> bool {
+        self.results.values().all(|result| result.is_ok())
+    }
+    
+    /// 失敗があれば最初のエラーを返す（デバイス名順）
+    pub fn into_result(self) -> Result<()> {
+        self.results.into_values().find(|result| result.is_err()).unwrap_or(Ok(()))
+    }
+}
+
+impl Serialize for SendReport {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        
+        let failed: BTreeMap<&str, String> = self.failed().into_iter()
+            .map(|(device_id, e)| (device_id, e.to_string()))
+            .collect();
+        let mut state = serializer.serialize_struct("SendReport", 2)?;
+        state.serialize_field("succeeded", &self.succeeded())?;
+        state.serialize_field("failed", &failed)?;
+        state.end()
+    }
+}
+
 /// プラットフォーム固有データを保持するトレイト
 pub trait PlatformData: Send + Sync + Debug {
     /// プラットフォーム名を取得
@@ -257,7 +721,7 @@ impl<T: BluetoothManager> BluetoothManager for Arc<T> {
         (**self).send_command_to_device(device_id, command).await
     }
     
-    async fn send_command_to_all(&self, command: Command) -> Result<()> {
+    async fn send_command_to_all(&self, command: Command) -> Result<SendReport> {
         (**self).send_command_to_all(command).await
     }
     
@@ -308,4 +772,114 @@ impl<T: BluetoothManager> BluetoothManager for Arc<T> {
     async fn get_device_name_by_number(&self, number: usize) -> Option<String> {
         (**self).get_device_name_by_number(number).await
     }
+    
+    async fn resolve_device(&self, name_or_alias: &str) -> String {
+        (**self).resolve_device(name_or_alias).await
+    }
+    
+    async fn pin_device(&self, device_id: &str, pin: DevicePin) -> Result<DeviceAssignment> {
+        (**self).pin_device(device_id, pin).await
+    }
+    
+    async fn group_members(&self, group: &str) -> Option<Vec<String>> {
+        (**self).group_members(group).await
+    }
+    
+    async fn set_group(&self, group: &str, members: Vec<String>) -> Result<()> {
+        (**self).set_group(group, members).await
+    }
+    
+    async fn list_groups(&self) -> BTreeMap<String, Vec<String>> {
+        (**self).list_groups().await
+    }
+    
+    async fn send_sequenced_to_device(
+        &self,
+        device_id: &str,
+        commands: Vec<Command>,
+    ) -> Result<()> {
+        (**self).send_sequenced_to_device(device_id, commands).await
+    }
+    
+    async fn register_asset(&self, id: u16, asset: Asset) -> Result<()> {
+        (**self).register_asset(id, asset).await
+    }
+    
+    async fn draw_asset(&self, device_id: &str, id: u16, x: u16, y: u16) -> Result<()> {
+        (**self).draw_asset(device_id, id, x, y).await
+    }
+    
+    async fn apply_settings_to_device(&self, device_id: &str, settings: Vec<DeviceSetting>) -> Result<()> {
+        (**self).apply_settings_to_device(device_id, settings).await
+    }
+    
+    fn subscribe_connection_events(&self) -> broadcast::Receiver<DeviceConnectionEvent> {
+        (**self).subscribe_connection_events()
+    }
+    
+    fn subscribe_button_events(&self) -> broadcast::Receiver<DeviceButtonEvent> {
+        (**self).subscribe_button_events()
+    }
+    
+    fn subscribe_battery_events(&self) -> broadcast::Receiver<DeviceBatteryEvent> {
+        (**self).subscribe_battery_events()
+    }
+    
+    fn subscribe_events(&self) -> broadcast::Receiver<NotifEvent> {
+        (**self).subscribe_events()
+    }
+    
+    fn publish_event(&self, event: NotifEvent) {
+        (**self).publish_event(event)
+    }
+    
+    async fn get_screen(&self, device_id: &str) -> Option<Framebuffer> {
+        (**self).get_screen(device_id).await
+    }
+    
+    async fn dismiss_device(&self, device_id: &str) -> Result<()> {
+        (**self).dismiss_device(device_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_capabilities_from_descriptor() {
+        let wide = DeviceCapabilities::from_descriptor(&CapabilityDescriptor {
+            firmware_version: (2, 0, 1),
+            display_width: 320,
+            display_height: 240,
+            color_depth: 16,
+            features: capability_flag::COLOR | capability_flag::CIRCLES | capability_flag::ASSETS,
+            extended_commands: EXTENDED_COMMAND_VERSION,
+        });
+        assert!(wide.circles && wide.assets && !wide.emoji && wide.extended_coords);
+        assert_eq!(wide.image_size(), (320, 240));
+        assert_eq!(wide.grid_size(), (80, 60));
+        assert_eq!(wide.palette_size(), None);
+        
+        // 全デバイス送信では小さい方の画面・色深度に合わせる
+        let common = wide.intersect(&DeviceCapabilities::default());
+        assert_eq!((common.display_width, common.display_height), (128, 128));
+        assert_eq!(common.grid_size(), (32, 32));
+        assert!(!common.circles && !common.extended_coords && common.color);
+        
+        // 拡張コマンドセット非対応のデバイスには255を超える座標を送らない
+        let far = Command::Rect { x: 300, y: 0, width: 10, height: 10, fill: true, color: crate::protocol::RGB::white() };
+        assert!(wide.adapt_command(far.clone()).is_ok());
+        assert!(common.adapt_command(far).is_err());
+        
+        // 円非対応のデバイスでは直線にラスタライズし、はみ出す部分も255以内に収める
+        let circle = Command::Circle { x: 250, y: 100, radius: 10, color: crate::protocol::RGB::white(), filled: false };
+        let adapted = DeviceCapabilities::default().adapt_command(circle).unwrap();
+        assert!(!adapted.needs_extended());
+        let Command::Batch { commands: lines } = adapted else { panic!("expected a batch of lines") };
+        assert!(lines.iter().all(|line| matches!(line, Command::Line { x1: ..=255, x2: ..=255, .. })));
+        
+        let mono = DeviceCapabilities { color: false, color_depth: 1, ..DeviceCapabilities::default() };
+        assert_eq!(mono.palette_size(), Some(PaletteSize::Colors2));
+    }
 }
\ No newline at end of file
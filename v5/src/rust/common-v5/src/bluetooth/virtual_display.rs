@@ -0,0 +1,134 @@
+//! 仮想ディスプレイデバイス
+//! 
+//! コマンドをメモリ上のフレームバッファに描画するソフトウェアデバイス。
+//! 実機と同じ座標系（テキスト・領域はグリッド単位）で描くため、`/send`や`/api/draw`の
+//! 出力をAtomS3なしで確認・デモできる
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::display::Framebuffer;
+use crate::error::{NotifError, Result};
+use crate::protocol::{Command, DeviceSetting};
+use super::traits::{Connection, DeviceCapabilities, DeviceInfo};
+
+/// 仮想ディスプレイの接続
+#[derive(Debug)]
+pub struct VirtualConnection {
+    info: DeviceInfo,
+    framebuffer: Arc<RwLock<Framebuffer>>,
+}
+
+impl VirtualConnection {
+    /// 既定の機能（128x128）を持つ仮想ディスプレイを作成
+    pub fn new(name: &str) -> Self {
+        Self::with_capabilities(name, DeviceCapabilities::default())
+    }
+    
+    /// 指定した機能（画面サイズ等）を持つ仮想ディスプレイを作成
+    pub fn with_capabilities(name: &str, capabilities: DeviceCapabilities) -> Self {
+        let (width, height) = capabilities.screen_size();
+        let framebuffer = Framebuffer::new(width, height);
+        VirtualConnection {
+            info: DeviceInfo {
+                name: name.to_string(),
+                address: format!("virtual:{}", name),
+                connected: true,
+                number: None,
+                alias: None,
+                signal_strength: None,
+                battery_level: None,
+                capabilities,
+                firmware_version: Some(format!("virtual-{}", crate::VERSION)),
+                reconnect: None,
+                statistics: None,
+            },
+            framebuffer: Arc::new(RwLock::new(framebuffer)),
+        }
+    }
+    
+    /// 描画先のフレームバッファ（PNG出力等に使う）
+    pub fn framebuffer(&self) -> Arc<RwLock<Framebuffer>> {
+        self.framebuffer.clone()
+    }
+}
+
+#[async_trait]
+impl Connection for VirtualConnection {
+    async fn send_command(&mut self, command: Command) -> Result<()> {
+        if !self.info.connected {
+            return Err(NotifError::DeviceNotConnected(self.info.name.clone()));
+        }
+        
+        // 実機と同じくフレーム単位でエンコード・デコードしてから描画
+        let mut framebuffer = self.framebuffer.write().await;
+        for frame in command.encode_frames()? {
+            let (received, _) = Command::decode(&frame)?;
+            framebuffer.apply(&received)?;
+        }
+        Ok(())
+    }
+    
+    async fn is_connected(&self) -> bool {
+        self.info.connected
+    }
+    
+    async fn get_device_info(&self) -> DeviceInfo {
+        self.info.clone()
+    }
+    
+    async fn disconnect(&mut self) -> Result<()> {
+        self.info.connected = false;
+        Ok(())
+    }
+    
+    async fn reconnect(&mut self) -> Result<()> {
+        self.info.connected = true;
+        Ok(())
+    }
+    
+    async fn apply_setting(&mut self, setting: DeviceSetting) -> Result<()> {
+        // 表示に影響しない設定は受け付けるだけ
+        setting.encode()?;
+        debug!("Virtual display {} setting: {:?}", self.info.name, setting);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{Size, RGB};
+    
+    #[tokio::test]
+    async fn test_virtual_connection_renders() {
+        let mut connection = VirtualConnection::new("notif_virtual");
+        let framebuffer = connection.framebuffer();
+        
+        connection.send_command(Command::Clear { color: RGB::new(0, 0, 255) }).await.unwrap();
+        assert_eq!(framebuffer.read().await.pixel(127, 127), Some(0x001F));
+        
+        connection.disconnect().await.unwrap();
+        assert!(connection.send_command(Command::Update).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_virtual_connection_renders_draw_regions() {
+        let mut connection = VirtualConnection::new("notif_virtual");
+        let framebuffer = connection.framebuffer();
+        
+        // /api/drawと同じく、領域の背景とテキストをグリッド単位で送る（r1=0,0,15,31）
+        connection.send_command(Command::Batch { commands: vec![
+            Command::Clear { color: RGB::black() },
+            Command::Rect { x: 0, y: 0, width: 32, height: 16, fill: true, color: RGB::new(0, 0, 255) },
+            Command::Text { x: 1, y: 1, size: Size::Medium, color: RGB::white(), text: "I".to_string() },
+        ] }).await.unwrap();
+        
+        let framebuffer = framebuffer.read().await;
+        assert_eq!(framebuffer.pixel(127, 63), Some(0x001F));
+        assert_eq!(framebuffer.pixel(127, 64), Some(0));
+        assert_eq!(framebuffer.pixel(4 + 4, 4 + 6), Some(0xFFFF));
+    }
+}
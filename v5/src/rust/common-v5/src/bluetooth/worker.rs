@@ -0,0 +1,125 @@
+//! デバイスごとの送信ワーカー
+//! 
+//! 接続は専用タスクが所有し、有界キューで受け取ったジョブを順に実行する。
+//! あるデバイスへの長い転送が他のデバイスへの送信や状態取得を待たせないよう、
+//! 状態は各ジョブ後に更新するスナップショットから読む
+
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, watch, RwLock};
+use tracing::debug;
+
+use crate::error::{NotifError, Result};
+use super::traits::{Connection, DeviceInfo};
+
+/// 1デバイスあたりの送信キューの長さ（満杯の間は送信側が待つ）
+const QUEUE_CAPACITY: usize = 64;
+
+/// ワーカーが接続に対して実行するジョブ
+pub(crate) type Job = Box<dyn for<'a> FnOnce(&'a mut dyn Connection) -> BoxFuture<'a, ()> + Send>;
+
+/// 送信ワーカーへのハンドル
+/// 
+/// 全てのハンドルが破棄されるとキューの残りを処理してワーカーは終了する
+#[derive(Debug, Clone)]
+pub(crate) struct DeviceWorker {
+    jobs: mpsc::Sender<Job>,
+    
+    /// 直近のデバイス情報（送信中でも待たずに読める）
+    info: Arc<RwLock<DeviceInfo>>,
+    
+    /// 通知で更新されるバッテリーレベル（ジョブを待たずに反映する）
+    battery: Option<watch::Receiver<Option<u8>>>,
+}
+
+impl DeviceWorker {
+    /// 接続を所有するワーカータスクを開始
+    pub(crate) async fn spawn(mut connection: Box<dyn Connection>) -> Self {
+        let info = Arc::new(RwLock::new(connection.get_device_info().await));
+        let battery = connection.subscribe_battery();
+        let (jobs, mut queue) = mpsc::channel::<Job>(QUEUE_CAPACITY);
+        
+        let snapshot = info.clone();
+        tokio::spawn(async move {
+            while let Some(job) = queue.recv().await {
+                job(connection.as_mut()).await;
+                
+                // 接続状態・機能等の変化を反映
+                let mut latest = connection.get_device_info().await;
+                latest.connected = connection.is_connected().await;
+                *snapshot.write().await = latest;
+            }
+            debug!("Send worker for {} stopped", snapshot.read().await.name);
+        });
+        
+        DeviceWorker { jobs, info, battery }
+    }
+    
+    /// 直近のデバイス情報
+    pub(crate) async fn info(&self) -> DeviceInfo {
+        let mut info = self.info.read().await.clone();
+        if let Some(level) = self.battery.as_ref().and_then(|battery| *battery.borrow()) {
+            info.battery_level = Some(level);
+        }
+        info
+    }
+    
+    /// ジョブをキューに入れ、完了を待って結果を返す
+    pub(crate) async fn run<T, F>(&self, job: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: for<'a> FnOnce(&'a mut dyn Connection) -> BoxFuture<'a, Result<T>> + Send + 'static,
+    {
+        let (done, result) = oneshot::channel();
+        let job: Job = Box::new(move |connection| Box::pin(async move {
+            let _ = done.send(job(connection).await);
+        }));
+        
+        let name = || self.info.try_read().map(|info| info.name.clone()).unwrap_or_default();
+        self.jobs.send(job).await
+            .map_err(|_| NotifError::DeviceNotConnected(name()))?;
+        result.await
+            .map_err(|_| NotifError::Connection(format!("Send worker for {} stopped", name())))?
+    }
+    
+    /// 完了を待たずにジョブをキューに入れる（キューが満杯ならfalse）
+    pub(crate) fn try_enqueue(&self, job: Job) -> bool {
+        self.jobs.try_send(job).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::bluetooth::mock::fixtures::{connected_manager, devices};
+    use crate::bluetooth::BluetoothManager;
+    use crate::protocol::{Command, RGB};
+    
+    #[tokio::test]
+    async fn test_slow_device_does_not_block_others() {
+        let devices = devices(2);
+        let manager = Arc::new(connected_manager(&devices).await);
+        
+        let slow = &devices[0];
+        slow.set_latency(Duration::from_millis(500));
+        let clear = Command::Clear { color: RGB::black() };
+        let sending = {
+            let manager = manager.clone();
+            let clear = clear.clone();
+            tokio::spawn(async move { manager.send_command_to_device("notif_atoms3_1", clear).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        
+        // 低速デバイスへの送信中でも他のデバイスと状態取得は待たない
+        let quick = Duration::from_millis(200);
+        tokio::time::timeout(quick, manager.send_command_to_device("notif_atoms3_2", clear.clone()))
+            .await.unwrap().unwrap();
+        let devices = tokio::time::timeout(quick, manager.list_connected_devices()).await.unwrap();
+        assert_eq!(devices.len(), 2);
+        tokio::time::timeout(quick, manager.get_statistics()).await.unwrap();
+        
+        sending.await.unwrap().unwrap();
+        assert_eq!(slow.commands().last(), Some(&clear));
+    }
+}
@@ -1,7 +1,11 @@
 //! 共通設定管理モジュール
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::bluetooth::{AckPolicy, ButtonBindings, DEFAULT_LOW_BATTERY_THRESHOLD, DeviceFilter, DevicePin, DiscoveryPolicy, ReconnectPolicy};
 use crate::error::{NotifError, Result};
 
 /// サーバー設定
@@ -37,6 +41,18 @@ pub struct BluetoothConfig {
     /// デバイス名のプレフィックス
     pub device_name_prefix: String,
     
+    /// 自動接続するデバイス名のパターン（glob、ワイルドカードなしはプレフィックス。空の場合はdevice_name_prefix）
+    #[serde(default)]
+    pub device_name_patterns: Vec<String>,
+    
+    /// 自動接続を許可するアドレス（空の場合は全て許可）
+    #[serde(default)]
+    pub allow_addresses: Vec<String>,
+    
+    /// 自動接続しないアドレス（許可リストより優先）
+    #[serde(default)]
+    pub deny_addresses: Vec<String>,
+    
     /// スキャンタイムアウト（秒）
     pub scan_timeout_secs: u64,
     
@@ -46,26 +62,128 @@ pub struct BluetoothConfig {
     /// 再接続試行回数
     pub reconnect_attempts: u32,
     
-    /// 再接続間隔（秒）
+    /// 再接続間隔（秒、失敗ごとに倍にする）
     pub reconnect_interval_secs: u64,
     
+    /// 再接続間隔の上限（秒）
+    #[serde(default = "default_reconnect_max_interval_secs")]
+    pub reconnect_max_interval_secs: u64,
+    
     /// 最大同時接続数
     pub max_connections: usize,
     
+    /// バックグラウンドスキャンの間隔（秒、0の場合は起動時のみスキャン）
+    #[serde(default)]
+    pub rescan_interval_secs: u64,
+    
     /// コマンドタイムアウト（ミリ秒）
     pub command_timeout_ms: u64,
+    
+    /// デバイスの応答（STATUS_CHAR通知）を待って配信を確認する
+    #[serde(default)]
+    pub require_ack: bool,
+    
+    /// Busy応答時の再送回数
+    #[serde(default = "default_ack_busy_retries")]
+    pub ack_busy_retries: u32,
+    
+    /// 仮想ディスプレイ（ソフトウェア描画のデバイス）を登録する
+    #[serde(default)]
+    pub virtual_display: bool,
+    
+    /// 低バッテリーとみなすしきい値（%、0の場合は通知しない）
+    #[serde(default = "default_low_battery_threshold")]
+    pub low_battery_threshold: u8,
+}
+
+fn default_ack_busy_retries() -> u32 {
+    3
+}
+
+fn default_reconnect_max_interval_secs() -> u64 {
+    300
+}
+
+fn default_low_battery_threshold() -> u8 {
+    DEFAULT_LOW_BATTERY_THRESHOLD
+}
+
+/// カンマ区切りの環境変数をリストにする（空の要素は除く）
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+impl BluetoothConfig {
+    /// 応答確認ポリシーを取得（無効な場合はNone）
+    pub fn ack_policy(&self) -> Option<AckPolicy> {
+        if !self.require_ack {
+            return None;
+        }
+        
+        Some(AckPolicy {
+            timeout: Duration::from_millis(self.command_timeout_ms),
+            busy_retries: self.ack_busy_retries,
+            ..AckPolicy::default()
+        })
+    }
+    
+    /// 再接続ポリシーを取得（reconnect_attemptsが0の場合は自動再接続を諦めない）
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        let initial_delay = Duration::from_secs(self.reconnect_interval_secs);
+        ReconnectPolicy {
+            max_attempts: self.reconnect_attempts,
+            initial_delay,
+            max_delay: Duration::from_secs(self.reconnect_max_interval_secs).max(initial_delay),
+            ..ReconnectPolicy::default()
+        }
+    }
+    
+    /// 探索ポリシーを取得（スキャン時間・バックグラウンドスキャンの間隔・接続数の上限）
+    pub fn discovery_policy(&self) -> DiscoveryPolicy {
+        DiscoveryPolicy {
+            scan_timeout: Duration::from_secs(self.scan_timeout_secs),
+            rescan_interval: (self.rescan_interval_secs > 0).then(|| Duration::from_secs(self.rescan_interval_secs)),
+            max_connections: self.max_connections,
+        }
+    }
+    
+    /// 自動接続の対象にするデバイスの条件を取得
+    pub fn device_filter(&self) -> DeviceFilter {
+        DeviceFilter {
+            name_patterns: self.device_name_patterns.clone(),
+            allow_addresses: self.allow_addresses.clone(),
+            deny_addresses: self.deny_addresses.clone(),
+        }
+    }
+    
+    /// 1フレームの送信タイムアウト（0の場合は無制限）
+    pub fn command_timeout(&self) -> Option<Duration> {
+        (self.command_timeout_ms > 0).then(|| Duration::from_millis(self.command_timeout_ms))
+    }
 }
 
 impl Default for BluetoothConfig {
     fn default() -> Self {
         BluetoothConfig {
             device_name_prefix: "notif_atoms3".to_string(),
+            device_name_patterns: Vec::new(),
+            allow_addresses: Vec::new(),
+            deny_addresses: Vec::new(),
             scan_timeout_secs: 10,
             auto_reconnect: true,
             reconnect_attempts: 3,
             reconnect_interval_secs: 5,
+            reconnect_max_interval_secs: default_reconnect_max_interval_secs(),
             max_connections: 10,
+            rescan_interval_secs: 0,
             command_timeout_ms: 5000,
+            require_ack: false,
+            ack_busy_retries: default_ack_busy_retries(),
+            virtual_display: false,
+            low_battery_threshold: default_low_battery_threshold(),
         }
     }
 }
@@ -129,6 +247,43 @@ pub struct PerformanceConfig {
     pub use_memory_pool: Option<bool>,
 }
 
+/// デバイス番号・エイリアスの設定
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DevicesConfig {
+    /// 番号・エイリアスを保存する状態ファイル（空文字列の場合は保存しない）
+    #[serde(default = "default_state_file")]
+    pub state_file: String,
+    
+    /// デバイス名 -> 固定する番号・エイリアス（状態ファイルより優先）
+    #[serde(default)]
+    pub pinned: BTreeMap<String, DevicePin>,
+    
+    /// グループ名 -> メンバー（デバイス名・エイリアス・番号、状態ファイルより優先）
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+}
+
+fn default_state_file() -> String {
+    "notif_devices.json".to_string()
+}
+
+impl Default for DevicesConfig {
+    fn default() -> Self {
+        DevicesConfig {
+            state_file: default_state_file(),
+            pinned: BTreeMap::new(),
+            groups: BTreeMap::new(),
+        }
+    }
+}
+
+impl DevicesConfig {
+    /// 状態ファイルのパス（保存しない場合はNone）
+    pub fn state_file_path(&self) -> Option<PathBuf> {
+        (!self.state_file.is_empty()).then(|| PathBuf::from(&self.state_file))
+    }
+}
+
 impl Default for ApiConfig {
     fn default() -> Self {
         ApiConfig {
@@ -168,6 +323,14 @@ pub struct Settings {
     
     /// パフォーマンス設定
     pub performance: PerformanceConfig,
+    
+    /// デバイスのボタンへのアクション割り当て
+    #[serde(default)]
+    pub buttons: ButtonBindings,
+    
+    /// デバイス番号・エイリアス
+    #[serde(default)]
+    pub devices: DevicesConfig,
 }
 
 impl Default for Settings {
@@ -178,6 +341,8 @@ impl Default for Settings {
             logging: LoggingConfig::default(),
             api: ApiConfig::default(),
             performance: PerformanceConfig::default(),
+            buttons: ButtonBindings::default(),
+            devices: DevicesConfig::default(),
         }
     }
 }
@@ -248,6 +413,15 @@ impl Settings {
         if let Ok(device_name) = env::var("DEVICE_NAME_PREFIX") {
             self.bluetooth.device_name_prefix = device_name;
         }
+        if let Ok(patterns) = env::var("DEVICE_NAME_PATTERNS") {
+            self.bluetooth.device_name_patterns = split_list(&patterns);
+        }
+        if let Ok(addresses) = env::var("ALLOW_ADDRESSES") {
+            self.bluetooth.allow_addresses = split_list(&addresses);
+        }
+        if let Ok(addresses) = env::var("DENY_ADDRESSES") {
+            self.bluetooth.deny_addresses = split_list(&addresses);
+        }
         if let Ok(scan_timeout) = env::var("SCAN_TIMEOUT") {
             if let Ok(timeout) = scan_timeout.parse() {
                 self.bluetooth.scan_timeout_secs = timeout;
@@ -257,11 +431,34 @@ impl Settings {
             self.bluetooth.auto_reconnect = auto_reconnect.to_lowercase() == "true" 
                 || auto_reconnect == "1";
         }
+        if let Ok(require_ack) = env::var("REQUIRE_ACK") {
+            self.bluetooth.require_ack = require_ack.to_lowercase() == "true" 
+                || require_ack == "1";
+        }
+        if let Ok(virtual_display) = env::var("VIRTUAL_DISPLAY") {
+            self.bluetooth.virtual_display = virtual_display.to_lowercase() == "true" 
+                || virtual_display == "1";
+        }
         if let Ok(max_connections) = env::var("MAX_CONNECTIONS") {
             if let Ok(max) = max_connections.parse() {
                 self.bluetooth.max_connections = max;
             }
         }
+        if let Ok(rescan_interval) = env::var("RESCAN_INTERVAL") {
+            if let Ok(interval) = rescan_interval.parse() {
+                self.bluetooth.rescan_interval_secs = interval;
+            }
+        }
+        if let Ok(low_battery_threshold) = env::var("LOW_BATTERY_THRESHOLD") {
+            if let Ok(threshold) = low_battery_threshold.parse() {
+                self.bluetooth.low_battery_threshold = threshold;
+            }
+        }
+        
+        // デバイス番号・エイリアス設定
+        if let Ok(state_file) = env::var("DEVICE_STATE_FILE") {
+            self.devices.state_file = state_file;
+        }
         
         // ロギング設定
         if let Ok(log_level) = env::var("LOG_LEVEL") {
@@ -306,6 +503,18 @@ impl Settings {
             return Err(NotifError::Config("Device name prefix cannot be empty".to_string()));
         }
         
+        // 空のパターンは全てのデバイスに一致してしまう
+        if self.bluetooth.device_name_patterns.iter().any(|pattern| pattern.trim().is_empty()) {
+            return Err(NotifError::Config("Device name patterns cannot be empty".to_string()));
+        }
+        
+        // バッテリーレベルは0-100%
+        if self.bluetooth.low_battery_threshold > 100 {
+            return Err(NotifError::Config(format!(
+                "Low battery threshold must be 0-100: {}", self.bluetooth.low_battery_threshold
+            )));
+        }
+        
         // APIキーの検証
         if self.api.api_key_enabled && self.api.api_key.is_none() {
             return Err(NotifError::Config("API key is required when API key authentication is enabled".to_string()));
@@ -0,0 +1,475 @@
+//! ソフトウェア描画（フレームバッファ）
+//! 
+//! `protocol::Command`をRGB565のメモリ上フレームバッファに描画する。
+//! 実機なしで動かす仮想ディスプレイや、表示内容の確認に使う。
+//! 実機と同じく、Text・Emoji・Rect・Regionの座標はグリッド単位（`GRID_PIXELS`）として扱う
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use crate::error::{NotifError, Result};
+use crate::image::palette::decode_indexed;
+use crate::image::rgb565::rle_decode;
+use crate::protocol::{image_format, Asset, Command, Size, GRID_PIXELS, RGB};
+
+/// 既定の画面サイズ（AtomS3）
+pub const DEFAULT_WIDTH: u16 = 128;
+pub const DEFAULT_HEIGHT: u16 = 128;
+
+/// 文字セルのサイズ（5x7グリフ + 間隔）
+const CELL_WIDTH: i32 = 6;
+const CELL_HEIGHT: i32 = 8;
+
+/// 1グリッドのピクセル数
+const GRID: i32 = GRID_PIXELS as i32;
+
+/// ASCII 0x20〜0x7Eの5x7フォント（列ごと、下位ビットが上）
+const FONT_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5F, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], [0x24, 0x2A, 0x7F, 0x2A, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x56, 0x20, 0x50], [0x00, 0x08, 0x07, 0x03, 0x00], [0x00, 0x1C, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1C, 0x00], [0x2A, 0x1C, 0x7F, 0x1C, 0x2A], [0x08, 0x08, 0x3E, 0x08, 0x08],
+    [0x00, 0x80, 0x70, 0x30, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x00, 0x60, 0x60, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02], [0x3E, 0x51, 0x49, 0x45, 0x3E], [0x00, 0x42, 0x7F, 0x40, 0x00],
+    [0x72, 0x49, 0x49, 0x49, 0x46], [0x21, 0x41, 0x49, 0x4D, 0x33], [0x18, 0x14, 0x12, 0x7F, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39], [0x3C, 0x4A, 0x49, 0x49, 0x31], [0x41, 0x21, 0x11, 0x09, 0x07],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x46, 0x49, 0x49, 0x29, 0x1E], [0x00, 0x00, 0x14, 0x00, 0x00],
+    [0x00, 0x40, 0x34, 0x00, 0x00], [0x00, 0x08, 0x14, 0x22, 0x41], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x59, 0x09, 0x06], [0x3E, 0x41, 0x5D, 0x59, 0x4E],
+    [0x7C, 0x12, 0x11, 0x12, 0x7C], [0x7F, 0x49, 0x49, 0x49, 0x36], [0x3E, 0x41, 0x41, 0x41, 0x22],
+    [0x7F, 0x41, 0x41, 0x41, 0x3E], [0x7F, 0x49, 0x49, 0x49, 0x41], [0x7F, 0x09, 0x09, 0x09, 0x01],
+    [0x3E, 0x41, 0x41, 0x51, 0x73], [0x7F, 0x08, 0x08, 0x08, 0x7F], [0x00, 0x41, 0x7F, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3F, 0x01], [0x7F, 0x08, 0x14, 0x22, 0x41], [0x7F, 0x40, 0x40, 0x40, 0x40],
+    [0x7F, 0x02, 0x1C, 0x02, 0x7F], [0x7F, 0x04, 0x08, 0x10, 0x7F], [0x3E, 0x41, 0x41, 0x41, 0x3E],
+    [0x7F, 0x09, 0x09, 0x09, 0x06], [0x3E, 0x41, 0x51, 0x21, 0x5E], [0x7F, 0x09, 0x19, 0x29, 0x46],
+    [0x26, 0x49, 0x49, 0x49, 0x32], [0x03, 0x01, 0x7F, 0x01, 0x03], [0x3F, 0x40, 0x40, 0x40, 0x3F],
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], [0x3F, 0x40, 0x38, 0x40, 0x3F], [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x03, 0x04, 0x78, 0x04, 0x03], [0x61, 0x59, 0x49, 0x4D, 0x43], [0x00, 0x7F, 0x41, 0x41, 0x41],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x41, 0x7F], [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40], [0x00, 0x03, 0x07, 0x08, 0x00], [0x20, 0x54, 0x54, 0x78, 0x40],
+    [0x7F, 0x28, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x28], [0x38, 0x44, 0x44, 0x28, 0x7F],
+    [0x38, 0x54, 0x54, 0x54, 0x18], [0x00, 0x08, 0x7E, 0x09, 0x02], [0x18, 0xA4, 0xA4, 0x9C, 0x78],
+    [0x7F, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7D, 0x40, 0x00], [0x20, 0x40, 0x40, 0x3D, 0x00],
+    [0x7F, 0x10, 0x28, 0x44, 0x00], [0x00, 0x41, 0x7F, 0x40, 0x00], [0x7C, 0x04, 0x78, 0x04, 0x78],
+    [0x7C, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], [0xFC, 0x18, 0x24, 0x24, 0x18],
+    [0x18, 0x24, 0x24, 0x18, 0xFC], [0x7C, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x24],
+    [0x04, 0x04, 0x3F, 0x44, 0x24], [0x3C, 0x40, 0x40, 0x20, 0x7C], [0x1C, 0x20, 0x40, 0x20, 0x1C],
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], [0x44, 0x28, 0x10, 0x28, 0x44], [0x4C, 0x90, 0x90, 0x90, 0x7C],
+    [0x44, 0x64, 0x54, 0x4C, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], [0x00, 0x00, 0x77, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00], [0x02, 0x01, 0x02, 0x04, 0x02],
+];
+
+/// 絵文字の代替表示色（黄色）
+const EMOJI_COLOR: RGB = RGB { r: 255, g: 200, b: 0 };
+
+/// RGBをRGB565に変換
+pub fn rgb_to_rgb565(color: RGB) -> u16 {
+    let r5 = (color.r as u16 * 31 + 127) / 255;
+    let g6 = (color.g as u16 * 63 + 127) / 255;
+    let b5 = (color.b as u16 * 31 + 127) / 255;
+    (r5 << 11) | (g6 << 5) | b5
+}
+
+/// RGB565をRGBに変換
+pub fn rgb565_to_rgb(pixel: u16) -> RGB {
+    let r5 = (pixel >> 11) & 0x1F;
+    let g6 = (pixel >> 5) & 0x3F;
+    let b5 = pixel & 0x1F;
+    RGB::new(
+        ((r5 * 255 + 15) / 31) as u8,
+        ((g6 * 255 + 31) / 63) as u8,
+        ((b5 * 255 + 15) / 31) as u8,
+    )
+}
+
+/// 描画範囲（左上を含み、右下を含まない）
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Clip {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
+
+/// 定義中・定義済みのアセット
+#[derive(Debug, Clone)]
+struct StoredAsset {
+    asset: Asset,
+    /// 宣言されたデータ長
+    length: usize,
+}
+
+/// RGB565フレームバッファ
+/// 
+/// デバイスと同じくアセットキャッシュを持ち、`DrawAsset`も描画できる
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: u16,
+    height: u16,
+    pixels: Vec<u16>,
+    assets: HashMap<u16, StoredAsset>,
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Framebuffer::new(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+}
+
+impl Framebuffer {
+    /// 黒で初期化したフレームバッファを作成
+    pub fn new(width: u16, height: u16) -> Self {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize],
+            assets: HashMap::new(),
+        }
+    }
+    
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+    
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+    
+    /// 全ピクセル（行優先のRGB565）
+    pub fn pixels(&self) -> &[u16] {
+        &self.pixels
+    }
+    
+    /// ピクセルを取得（範囲外はNone）
+    pub fn pixel(&self, x: u16, y: u16) -> Option<u16> {
+        (x < self.width && y < self.height).then(|| self.pixels[y as usize * self.width as usize + x as usize])
+    }
+    
+    /// コマンドを描画
+    /// 
+    /// 画面外にはみ出す部分は切り捨てる。データが壊れたコマンドはエラーにする
+    pub fn apply(&mut self, command: &Command) -> Result<()> {
+        let clip = self.screen();
+        self.draw(command, clip)
+    }
+    
+    /// 複数コマンドを順に描画
+    pub fn apply_all<'a>(&mut self, commands: impl IntoIterator<Item = &'a Command>) -> Result<()> {
+        for command in commands {
+            self.apply(command)?;
+        }
+        Ok(())
+    }
+    
+    /// PNGにエンコード
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let image = image::RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = rgb565_to_rgb(self.pixels[y as usize * self.width as usize + x as usize]);
+            image::Rgb([color.r, color.g, color.b])
+        });
+        
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(image)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| NotifError::ImageProcessing(format!("PNG encode failed: {}", e)))?;
+        Ok(png)
+    }
+    
+    /// PNGファイルに保存
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        std::fs::write(path, self.to_png()?)?;
+        Ok(())
+    }
+    
+    fn screen(&self) -> Clip {
+        Clip { x0: 0, y0: 0, x1: self.width as i32, y1: self.height as i32 }
+    }
+    
+    fn draw(&mut self, command: &Command, clip: Clip) -> Result<()> {
+        match command {
+            Command::Clear { color } => {
+                let pixel = rgb_to_rgb565(*color);
+                self.fill(clip, pixel);
+            }
+            Command::Text { x, y, size, color, text } => {
+                self.draw_text(*x as i32 * GRID, *y as i32 * GRID, *size, rgb_to_rgb565(*color), text, clip);
+            }
+            Command::Rect { x, y, width, height, fill, color } => {
+                let (x, y, w, h) = (*x as i32 * GRID, *y as i32 * GRID, *width as i32 * GRID, *height as i32 * GRID);
+                let pixel = rgb_to_rgb565(*color);
+                if *fill {
+                    self.fill_rect(x, y, w, h, pixel, clip);
+                } else if w > 0 && h > 0 {
+                    self.fill_rect(x, y, w, 1, pixel, clip);
+                    self.fill_rect(x, y + h - 1, w, 1, pixel, clip);
+                    self.fill_rect(x, y, 1, h, pixel, clip);
+                    self.fill_rect(x + w - 1, y, 1, h, pixel, clip);
+                }
+            }
+            Command::Line { x1, y1, x2, y2, width, color } => {
+                let pixel = rgb_to_rgb565(*color);
+                self.draw_line((*x1 as i32, *y1 as i32), (*x2 as i32, *y2 as i32), *width as i32, pixel, clip);
+            }
+            Command::Circle { x, y, radius, color, filled } => {
+                for line in Command::rasterize_circle(*x, *y, *radius, *color, *filled, u16::MAX) {
+                    self.draw(&line, clip)?;
+                }
+            }
+            Command::Emoji { x, y, size, .. } => {
+                // グリフは持たないため、絵文字の枠（1行分の高さの正方形）に円を描く
+                let grids = Size::from_byte(*size).map(|size| size.line_grids()).unwrap_or(*size);
+                let radius = (grids as u16 * GRID_PIXELS as u16 / 2).max(1);
+                let circle = Command::Circle {
+                    x: (x.saturating_mul(GRID_PIXELS as u16)).saturating_add(radius),
+                    y: (y.saturating_mul(GRID_PIXELS as u16)).saturating_add(radius),
+                    radius,
+                    color: EMOJI_COLOR,
+                    filled: true,
+                };
+                self.draw(&circle, clip)?;
+            }
+            Command::Image { x, y, width, height, format, data } => {
+                let pixels = decode_image(*width, *height, *format, data)?;
+                self.blit(*x as i32, *y as i32, *width as i32, &pixels, clip);
+            }
+            Command::Region { regions } => {
+                for region in regions {
+                    let (x, y) = (region.x * GRID, region.y * GRID);
+                    let area = Clip {
+                        x0: x.max(clip.x0),
+                        y0: y.max(clip.y0),
+                        x1: (x + region.width as i32 * GRID).min(clip.x1),
+                        y1: (y + region.height as i32 * GRID).min(clip.y1),
+                    };
+                    self.draw(&region.content, area)?;
+                }
+            }
+            Command::Batch { commands } => {
+                for command in commands {
+                    self.draw(command, clip)?;
+                }
+            }
+            Command::Sequenced { command, .. } => self.draw(command, clip)?,
+            Command::Update => {}
+            Command::DefineAsset { id, width, height, format, length } => {
+                self.assets.insert(*id, StoredAsset {
+                    asset: Asset { width: *width, height: *height, format: *format, data: Vec::new() },
+                    length: *length as usize,
+                });
+            }
+            Command::AssetData { id, offset, data } => {
+                let stored = self.assets.get_mut(id)
+                    .ok_or_else(|| NotifError::InvalidCommand(format!("Asset {} is not defined", id)))?;
+                let end = *offset as usize + data.len();
+                if end > stored.length {
+                    return Err(NotifError::InvalidCommand(format!(
+                        "Asset {} data exceeds declared length {}", id, stored.length
+                    )));
+                }
+                if stored.asset.data.len() < end {
+                    stored.asset.data.resize(end, 0);
+                }
+                stored.asset.data[*offset as usize..end].copy_from_slice(data);
+            }
+            Command::DrawAsset { id, x, y } => {
+                let stored = self.assets.get(id)
+                    .filter(|stored| stored.asset.data.len() == stored.length)
+                    .ok_or_else(|| NotifError::InvalidCommand(format!("Asset {} is not loaded", id)))?;
+                let image = stored.asset.to_image(*x, *y);
+                self.draw(&image, clip)?;
+            }
+        }
+        Ok(())
+    }
+    
+    fn put(&mut self, x: i32, y: i32, pixel: u16, clip: Clip) {
+        if x >= clip.x0 && x < clip.x1 && y >= clip.y0 && y < clip.y1 {
+            self.pixels[y as usize * self.width as usize + x as usize] = pixel;
+        }
+    }
+    
+    fn fill(&mut self, clip: Clip, pixel: u16) {
+        self.fill_rect(clip.x0, clip.y0, clip.x1 - clip.x0, clip.y1 - clip.y0, pixel, clip);
+    }
+    
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, pixel: u16, clip: Clip) {
+        for row in y.max(clip.y0)..(y + height).min(clip.y1) {
+            for column in x.max(clip.x0)..(x + width).min(clip.x1) {
+                self.put(column, row, pixel, clip);
+            }
+        }
+    }
+    
+    /// ブレゼンハムで線を描く（太さ2以上は各点に正方形を置く）
+    fn draw_line(&mut self, from: (i32, i32), to: (i32, i32), width: i32, pixel: u16, clip: Clip) {
+        let (mut x, mut y) = from;
+        let (dx, dy) = ((to.0 - x).abs(), -(to.1 - y).abs());
+        let (sx, sy) = (if x < to.0 { 1 } else { -1 }, if y < to.1 { 1 } else { -1 });
+        let offset = (width.max(1) - 1) / 2;
+        let mut error = dx + dy;
+        
+        loop {
+            self.fill_rect(x - offset, y - offset, width.max(1), width.max(1), pixel, clip);
+            if (x, y) == to {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+    
+    /// 5x7フォントで文字列を描く（ASCII以外は全角幅の枠で代替）
+    /// 
+    /// 文字送りはテキスト配置と同じグリッド幅にそろえる
+    fn draw_text(&mut self, x: i32, y: i32, size: Size, pixel: u16, text: &str, clip: Clip) {
+        let advance = size.char_grids() as i32 * GRID;
+        let scale = (advance / CELL_WIDTH).max(1);
+        let mut cursor = x;
+        
+        for c in text.chars() {
+            match FONT_5X7.get((c as usize).wrapping_sub(0x20)) {
+                Some(glyph) => {
+                    for (column, bits) in glyph.iter().enumerate() {
+                        for row in 0..CELL_HEIGHT {
+                            if bits & (1 << row) != 0 {
+                                self.fill_rect(cursor + column as i32 * scale, y + row * scale, scale, scale, pixel, clip);
+                            }
+                        }
+                    }
+                    cursor += advance;
+                }
+                None => {
+                    let (w, h) = (advance * 2 - 1, (CELL_HEIGHT - 1) * scale);
+                    self.fill_rect(cursor, y, w, 1, pixel, clip);
+                    self.fill_rect(cursor, y + h - 1, w, 1, pixel, clip);
+                    self.fill_rect(cursor, y, 1, h, pixel, clip);
+                    self.fill_rect(cursor + w - 1, y, 1, h, pixel, clip);
+                    cursor += advance * 2;
+                }
+            }
+        }
+    }
+    
+    fn blit(&mut self, x: i32, y: i32, width: i32, pixels: &[u16], clip: Clip) {
+        for (index, &pixel) in pixels.iter().enumerate() {
+            let index = index as i32;
+            self.put(x + index % width, y + index / width, pixel, clip);
+        }
+    }
+}
+
+/// 画像データをRGB565にデコード
+pub(crate) fn decode_image(width: u16, height: u16, format: u8, data: &[u8]) -> Result<Vec<u16>> {
+    let count = width as usize * height as usize;
+    let pixels = match format {
+        image_format::RAW_RGB => data.chunks_exact(3)
+            .map(|rgb| rgb_to_rgb565(RGB::new(rgb[0], rgb[1], rgb[2])))
+            .collect(),
+        image_format::RAW_RGB565 => data.chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect(),
+        image_format::RLE_RGB565 => rle_decode(data)?,
+        image_format::INDEXED => decode_indexed(data, count)?,
+        _ => return Err(NotifError::InvalidCommand(format!("Unknown image format: {}", format))),
+    };
+    
+    if pixels.len() != count {
+        return Err(NotifError::InvalidCommand(format!(
+            "Image {}x{} has {} pixels, expected {}", width, height, pixels.len(), count
+        )));
+    }
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::rgb565::rle_encode;
+    use crate::protocol::Region;
+    
+    const RED: u16 = 0xF800;
+    
+    #[test]
+    fn test_draw_primitives() {
+        let mut framebuffer = Framebuffer::default();
+        framebuffer.apply(&Command::Clear { color: RGB::white() }).unwrap();
+        assert!(framebuffer.pixels().iter().all(|&pixel| pixel == 0xFFFF));
+        
+        framebuffer.apply(&Command::Batch { commands: vec![
+            // 矩形はグリッド単位（1グリッド4px）
+            Command::Rect { x: 10, y: 10, width: 4, height: 4, fill: true, color: RGB::new(255, 0, 0) },
+            Command::Line { x1: 0, y1: 100, x2: 127, y2: 100, width: 1, color: RGB::black() },
+            // 画面外にはみ出す円は切り捨てる
+            Command::Circle { x: 127, y: 127, radius: 20, color: RGB::black(), filled: true },
+        ] }).unwrap();
+        
+        assert_eq!(framebuffer.pixel(40, 40), Some(RED));
+        assert_eq!(framebuffer.pixel(55, 55), Some(RED));
+        assert_eq!(framebuffer.pixel(56, 56), Some(0xFFFF));
+        assert_eq!(framebuffer.pixel(64, 100), Some(0));
+        assert_eq!(framebuffer.pixel(120, 120), Some(0));
+        assert_eq!(framebuffer.pixel(128, 0), None);
+        
+        framebuffer.apply(&Command::Text {
+            x: 0, y: 0, size: Size::Small, color: RGB::black(), text: "I".to_string(),
+        }).unwrap();
+        assert_eq!(framebuffer.pixel(2, 3), Some(0)); // 「I」の縦棒
+        assert_eq!(framebuffer.pixel(0, 3), Some(0xFFFF));
+    }
+    
+    #[test]
+    fn test_grid_unit_commands() {
+        let mut framebuffer = Framebuffer::default();
+        
+        // 2グリッド目（8px）から、Mediumは1文字3グリッド（12px）で送る
+        framebuffer.apply(&Command::Text {
+            x: 2, y: 1, size: Size::Medium, color: RGB::white(), text: "II".to_string(),
+        }).unwrap();
+        assert_eq!(framebuffer.pixel(8 + 4, 4 + 6), Some(0xFFFF));
+        assert_eq!(framebuffer.pixel(8 + 12 + 4, 4 + 6), Some(0xFFFF));
+        assert_eq!(framebuffer.pixel(8 + 6, 4 + 6), Some(0));
+        
+        // 領域の範囲もグリッド単位で切り取る
+        framebuffer.apply(&Command::Region { regions: vec![Region {
+            x: 16, y: 16, width: 4, height: 2, content: Box::new(Command::Clear { color: RGB::new(255, 0, 0) }),
+        }] }).unwrap();
+        assert_eq!(framebuffer.pixel(64, 64), Some(RED));
+        assert_eq!(framebuffer.pixel(79, 71), Some(RED));
+        assert_eq!(framebuffer.pixel(80, 71), Some(0));
+        assert_eq!(framebuffer.pixel(79, 72), Some(0));
+    }
+    
+    #[test]
+    fn test_draw_images_and_assets() {
+        let mut framebuffer = Framebuffer::new(16, 16);
+        let tile = vec![RED; 16];
+        
+        framebuffer.apply(&Command::Image {
+            x: 4, y: 4, width: 4, height: 4, format: image_format::RLE_RGB565, data: rle_encode(&tile),
+        }).unwrap();
+        assert_eq!(framebuffer.pixel(7, 7), Some(RED));
+        assert_eq!(framebuffer.pixel(8, 8), Some(0));
+        
+        // データ長が合わない画像はエラー
+        assert!(framebuffer.apply(&Command::Image {
+            x: 0, y: 0, width: 4, height: 4, format: image_format::RAW_RGB565, data: vec![0; 6],
+        }).is_err());
+        
+        let asset = Asset { width: 2, height: 2, format: image_format::RAW_RGB565, data: vec![0xFF; 8] };
+        assert!(framebuffer.apply(&Command::DrawAsset { id: 1, x: 0, y: 0 }).is_err());
+        framebuffer.apply_all(&asset.upload_commands(1).unwrap()).unwrap();
+        framebuffer.apply(&Command::DrawAsset { id: 1, x: 14, y: 14 }).unwrap();
+        assert_eq!(framebuffer.pixel(15, 15), Some(0xFFFF));
+        
+        let png = framebuffer.to_png().unwrap();
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (16, 16));
+    }
+}
@@ -73,6 +73,14 @@ pub enum NotifError {
     
     #[error("未実装: {0}")]
     NotImplemented(String),
+    
+    /// デバイスがBusy応答を返した
+    #[error("Device busy: {0}")]
+    DeviceBusy(String),
+    
+    /// デバイスがエラー応答を返した
+    #[error("Device error: {0}")]
+    DeviceError(String),
 }
 
 /// Result型のエイリアス
@@ -103,6 +111,8 @@ impl NotifError {
             NotifError::UnsupportedFormat(_) => 400,
             NotifError::ImageTooLarge(_, _) => 413,
             NotifError::NotImplemented(_) => 501,
+            NotifError::DeviceBusy(_) => 503,
+            NotifError::DeviceError(_) => 502,
         }
     }
     
@@ -128,6 +138,8 @@ impl NotifError {
             NotifError::UnsupportedFormat(_) => "UNSUPPORTED_FORMAT",
             NotifError::ImageTooLarge(_, _) => "IMAGE_TOO_LARGE",
             NotifError::NotImplemented(_) => "NOT_IMPLEMENTED",
+            NotifError::DeviceBusy(_) => "DEVICE_BUSY",
+            NotifError::DeviceError(_) => "DEVICE_ERROR",
         }
     }
 }
\ No newline at end of file
@@ -4,10 +4,12 @@
 pub mod processor;
 pub mod formats; 
 pub mod rgb565;
+pub mod palette;
 
 // 公開API
 pub use processor::ImageProcessor;
 pub use rgb565::to_rgb565;
+pub use palette::PaletteSize;
 
 /// 画像処理結果
 #[derive(Debug, Clone)]
@@ -22,10 +24,10 @@ pub struct ProcessedImage {
 /// 画像タイル（BLE送信用の小分割データ）
 #[derive(Debug, Clone)]
 pub struct ImageTile {
-    pub x: u8,                   // タイル開始X座標
-    pub y: u8,                   // タイル開始Y座標
-    pub width: u8,               // タイル幅
-    pub height: u8,              // タイル高さ
+    pub x: u16,                  // タイル開始X座標
+    pub y: u16,                  // タイル開始Y座標
+    pub width: u16,              // タイル幅
+    pub height: u16,             // タイル高さ
     pub rgb565_data: Vec<u16>,   // RGB565データ
 }
 
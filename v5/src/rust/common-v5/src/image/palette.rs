@@ -0,0 +1,319 @@
+//! パレット（インデックスカラー）変換
+//! 
+//! メディアンカットで色数を減らし、タイルごとに使用色だけのパレットと
+//! パック済みインデックスを送ることでBLE転送量を削減する
+
+use std::collections::HashMap;
+
+use crate::error::NotifError;
+
+/// 減色後の色数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "u16")]
+pub enum PaletteSize {
+    Colors2,
+    Colors4,
+    Colors16,
+    Colors256,
+}
+
+impl PaletteSize {
+    /// 最大色数
+    pub fn colors(&self) -> usize {
+        match self {
+            PaletteSize::Colors2 => 2,
+            PaletteSize::Colors4 => 4,
+            PaletteSize::Colors16 => 16,
+            PaletteSize::Colors256 => 256,
+        }
+    }
+}
+
+impl TryFrom<u16> for PaletteSize {
+    type Error = String;
+    
+    fn try_from(colors: u16) -> Result<Self, Self::Error> {
+        match colors {
+            2 => Ok(PaletteSize::Colors2),
+            4 => Ok(PaletteSize::Colors4),
+            16 => Ok(PaletteSize::Colors16),
+            256 => Ok(PaletteSize::Colors256),
+            _ => Err(format!("Invalid palette size: {} (2, 4, 16, 256)", colors)),
+        }
+    }
+}
+
+impl std::str::FromStr for PaletteSize {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let colors: u16 = s.trim().parse().map_err(|_| format!("Invalid palette size: {}", s))?;
+        PaletteSize::try_from(colors)
+    }
+}
+
+/// RGB565を8bit RGBに展開
+fn expand(pixel: u16) -> [u32; 3] {
+    let r5 = ((pixel >> 11) & 0x1F) as u32;
+    let g6 = ((pixel >> 5) & 0x3F) as u32;
+    let b5 = (pixel & 0x1F) as u32;
+    [(r5 * 255 + 15) / 31, (g6 * 255 + 31) / 63, (b5 * 255 + 15) / 31]
+}
+
+/// 8bit RGBをRGB565に変換
+fn pack(rgb: [u32; 3]) -> u16 {
+    let r5 = ((rgb[0] * 31 + 127) / 255) as u16;
+    let g6 = ((rgb[1] * 63 + 127) / 255) as u16;
+    let b5 = ((rgb[2] * 31 + 127) / 255) as u16;
+    (r5 << 11) | (g6 << 5) | b5
+}
+
+/// メディアンカットでパレットを生成
+/// 
+/// 色数が`max_colors`以下の画像は元の色をそのまま返す
+pub fn median_cut(pixels: &[u16], max_colors: usize) -> Vec<u16> {
+    let mut histogram: HashMap<u16, u32> = HashMap::new();
+    for &pixel in pixels {
+        *histogram.entry(pixel).or_insert(0) += 1;
+    }
+    
+    let mut colors: Vec<(u16, u32)> = histogram.into_iter().collect();
+    colors.sort_unstable();
+    if colors.len() <= max_colors {
+        return colors.into_iter().map(|(pixel, _)| pixel).collect();
+    }
+    
+    // ボックス内で値の幅が最大のチャネルとその幅
+    let channel_range = |colors: &[(u16, u32)]| -> (usize, u32) {
+        (0..3)
+            .map(|channel| {
+                let values = colors.iter().map(|&(pixel, _)| expand(pixel)[channel]);
+                let min = values.clone().min().unwrap_or(0);
+                let max = values.max().unwrap_or(0);
+                (channel, max - min)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0))
+    };
+    
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        // レンジが最大のボックスを分割
+        let Some((index, channel)) = boxes.iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(index, colors)| {
+                let (channel, range) = channel_range(colors);
+                (index, channel, range)
+            })
+            .max_by_key(|&(_, _, range)| range)
+            .map(|(index, channel, _)| (index, channel))
+        else {
+            break;
+        };
+        
+        let mut colors = boxes.swap_remove(index);
+        colors.sort_by_key(|&(pixel, _)| expand(pixel)[channel]);
+        
+        // 画素数の中央で分割（両側に最低1色残す）
+        let total: u32 = colors.iter().map(|&(_, count)| count).sum();
+        let mut accumulated = 0;
+        let mut split = 1;
+        for (position, &(_, count)) in colors.iter().enumerate() {
+            accumulated += count;
+            if accumulated * 2 >= total {
+                split = (position + 1).clamp(1, colors.len() - 1);
+                break;
+            }
+        }
+        
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+    
+    // 各ボックスの画素数加重平均を代表色にする
+    let mut palette: Vec<u16> = boxes.iter()
+        .map(|colors| {
+            let total: u32 = colors.iter().map(|&(_, count)| count).sum();
+            let mut sum = [0u64; 3];
+            for &(pixel, count) in colors {
+                for (channel, value) in expand(pixel).iter().enumerate() {
+                    sum[channel] += *value as u64 * count as u64;
+                }
+            }
+            pack(sum.map(|value| (value / total.max(1) as u64) as u32))
+        })
+        .collect();
+    palette.sort_unstable();
+    palette.dedup();
+    palette
+}
+
+/// 各ピクセルをパレット内の最も近い色に置き換える
+pub fn remap(pixels: &[u16], palette: &[u16]) -> Vec<u16> {
+    let mut cache: HashMap<u16, u16> = HashMap::new();
+    
+    pixels.iter()
+        .map(|&pixel| {
+            *cache.entry(pixel).or_insert_with(|| {
+                let [r, g, b] = expand(pixel);
+                palette.iter()
+                    .copied()
+                    .min_by_key(|&candidate| {
+                        let [pr, pg, pb] = expand(candidate);
+                        let (dr, dg, db) = (r as i32 - pr as i32, g as i32 - pg as i32, b as i32 - pb as i32);
+                        dr * dr + dg * dg + db * db
+                    })
+                    .unwrap_or(pixel)
+            })
+        })
+        .collect()
+}
+
+/// インデックスカラー形式にエンコード（使用色が256色を超える場合はNone）
+/// 
+/// 形式: [ビット数(1), パレット色数-1(1), パレット(RGB565 LE × 色数), パック済みインデックス]
+/// インデックスは1/2/4/8ビットで、上位ビットから詰める
+pub fn encode_indexed(pixels: &[u16]) -> Option<Vec<u8>> {
+    let mut palette: Vec<u16> = Vec::new();
+    let mut lookup: HashMap<u16, u8> = HashMap::new();
+    let mut indices = Vec::with_capacity(pixels.len());
+    
+    for &pixel in pixels {
+        let index = match lookup.get(&pixel) {
+            Some(&index) => index,
+            None => {
+                if palette.len() == 256 {
+                    return None;
+                }
+                let index = palette.len() as u8;
+                palette.push(pixel);
+                lookup.insert(pixel, index);
+                index
+            }
+        };
+        indices.push(index);
+    }
+    
+    if palette.is_empty() {
+        return None;
+    }
+    
+    let bits_per_pixel: u8 = match palette.len() {
+        0..=2 => 1,
+        3..=4 => 2,
+        5..=16 => 4,
+        _ => 8,
+    };
+    
+    let mut data = Vec::with_capacity(2 + palette.len() * 2 + pixels.len() * bits_per_pixel as usize / 8 + 1);
+    data.push(bits_per_pixel);
+    data.push((palette.len() - 1) as u8);
+    for color in &palette {
+        data.extend_from_slice(&color.to_le_bytes());
+    }
+    
+    let per_byte = 8 / bits_per_pixel as usize;
+    for chunk in indices.chunks(per_byte) {
+        let mut byte = 0u8;
+        for (position, &index) in chunk.iter().enumerate() {
+            byte |= index << (8 - bits_per_pixel as usize * (position + 1));
+        }
+        data.push(byte);
+    }
+    
+    Some(data)
+}
+
+/// インデックスカラー形式をRGB565にデコード
+pub fn decode_indexed(data: &[u8], pixel_count: usize) -> Result<Vec<u16>, NotifError> {
+    let invalid = |message: String| NotifError::InvalidCommand(format!("Indexed image: {}", message));
+    
+    let (&bits_per_pixel, rest) = data.split_first()
+        .ok_or_else(|| invalid("empty data".to_string()))?;
+    if !matches!(bits_per_pixel, 1 | 2 | 4 | 8) {
+        return Err(invalid(format!("unsupported bits per pixel: {}", bits_per_pixel)));
+    }
+    
+    let (&last_index, rest) = rest.split_first()
+        .ok_or_else(|| invalid("missing palette size".to_string()))?;
+    let palette_len = last_index as usize + 1;
+    if rest.len() < palette_len * 2 {
+        return Err(invalid(format!("palette truncated ({} colors)", palette_len)));
+    }
+    
+    let (palette_bytes, packed) = rest.split_at(palette_len * 2);
+    let palette: Vec<u16> = palette_bytes.chunks_exact(2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect();
+    
+    let bits = bits_per_pixel as usize;
+    let expected_len = (pixel_count * bits).div_ceil(8);
+    if packed.len() != expected_len {
+        return Err(invalid(format!("expected {} index bytes, got {}", expected_len, packed.len())));
+    }
+    
+    let mask = ((1u16 << bits) - 1) as u8;
+    (0..pixel_count)
+        .map(|pixel| {
+            let bit_offset = pixel * bits;
+            let shift = 8 - bits - bit_offset % 8;
+            let index = (packed[bit_offset / 8] >> shift) & mask;
+            palette.get(index as usize)
+                .copied()
+                .ok_or_else(|| invalid(format!("index {} out of palette range", index)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_palette_size_parse() {
+        assert_eq!("16".parse::<PaletteSize>().unwrap(), PaletteSize::Colors16);
+        assert_eq!(PaletteSize::try_from(256).unwrap().colors(), 256);
+        assert!("3".parse::<PaletteSize>().is_err());
+    }
+    
+    #[test]
+    fn test_median_cut_reduces_colors() {
+        // 64色のグラデーション
+        let pixels: Vec<u16> = (0..64).map(|g| g << 5).collect();
+        
+        let palette = median_cut(&pixels, 4);
+        assert_eq!(palette.len(), 4);
+        
+        let remapped = remap(&pixels, &palette);
+        assert!(remapped.iter().all(|pixel| palette.contains(pixel)));
+        
+        // 色数が少なければ元の色をそのまま使う
+        let few = vec![0xF800, 0x07E0, 0xF800];
+        let mut palette = median_cut(&few, 16);
+        palette.sort_unstable();
+        assert_eq!(palette, vec![0x07E0, 0xF800]);
+    }
+    
+    #[test]
+    fn test_indexed_round_trip() {
+        let pixels: Vec<u16> = (0..128).map(|i| [0x0000, 0xFFFF, 0xF800][i % 3]).collect();
+        
+        let encoded = encode_indexed(&pixels).unwrap();
+        assert_eq!(encoded[0], 2); // 3色 → 2ビット
+        assert_eq!(encoded[1], 2); // パレット3色
+        assert_eq!(encoded.len(), 2 + 3 * 2 + 128 * 2 / 8);
+        assert_eq!(decode_indexed(&encoded, pixels.len()).unwrap(), pixels);
+        
+        // 単色は1ビット
+        let flat = encode_indexed(&[0x1234; 128]).unwrap();
+        assert_eq!(flat.len(), 2 + 2 + 16);
+        
+        // 256色を超える場合はエンコードしない
+        let many: Vec<u16> = (0..300).collect();
+        assert!(encode_indexed(&many).is_none());
+        
+        assert!(decode_indexed(&encoded[..encoded.len() - 1], pixels.len()).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 //! 画像処理パイプライン
 
-use crate::image::{ProcessedImage, FitMode, ImageTile};
+use crate::image::{ProcessedImage, FitMode, ImageTile, PaletteSize};
 use crate::error::{Result, NotifError};
 use image::DynamicImage;
 use std::time::Instant;
@@ -52,6 +52,24 @@ impl ImageProcessor {
         })
     }
     
+    /// 指定色数に減色（メディアンカット）
+    /// 
+    /// 減色後の画像はタイル送信時にインデックスカラー形式で転送される
+    pub fn quantize(&self, image: ProcessedImage, colors: PaletteSize) -> ProcessedImage {
+        let start = Instant::now();
+        
+        let palette = super::palette::median_cut(&image.rgb565_data, colors.colors());
+        let rgb565_data = super::palette::remap(&image.rgb565_data, &palette);
+        
+        info!("減色完了: {}色パレット ({}ms)", palette.len(), start.elapsed().as_millis());
+        
+        ProcessedImage {
+            rgb565_data,
+            processing_time_ms: image.processing_time_ms + start.elapsed().as_millis() as u64,
+            ..image
+        }
+    }
+    
     /// URL から画像を取得して処理
     #[cfg(feature = "http-endpoints")]
     pub async fn process_from_url(
@@ -244,10 +262,10 @@ impl ImageProcessor {
                 }
                 
                 tiles.push(ImageTile {
-                    x: start_x as u8,
-                    y: start_y as u8,
-                    width: actual_width as u8,
-                    height: actual_height as u8,
+                    x: start_x,
+                    y: start_y,
+                    width: actual_width,
+                    height: actual_height,
                     rgb565_data: tile_data,
                 });
             }
@@ -2,6 +2,8 @@
 
 use image::DynamicImage;
 
+use crate::protocol::image_format;
+
 /// RGB888からRGB565への高精度変換
 pub fn to_rgb565(img: &DynamicImage) -> Vec<u16> {
     let rgba = img.to_rgba8();
@@ -39,6 +41,68 @@ pub fn rgb565_to_bytes(rgb565_data: &[u16]) -> Vec<u8> {
     result
 }
 
+/// RLE圧縮RGB565をエンコード
+/// 
+/// 連続する同色ピクセルを [ラン長(1), ピクセル(2, リトルエンディアン)] の3バイトで表す。
+/// ラン長は1〜255。
+pub fn rle_encode(rgb565_data: &[u16]) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut pixels = rgb565_data.iter().peekable();
+    
+    while let Some(&pixel) = pixels.next() {
+        let mut run: u8 = 1;
+        while run < u8::MAX && pixels.peek() == Some(&&pixel) {
+            pixels.next();
+            run += 1;
+        }
+        result.push(run);
+        result.extend_from_slice(&pixel.to_le_bytes());
+    }
+    
+    result
+}
+
+/// RLE圧縮RGB565をデコード
+pub fn rle_decode(data: &[u8]) -> Result<Vec<u16>, crate::error::NotifError> {
+    if !data.len().is_multiple_of(3) {
+        return Err(crate::error::NotifError::InvalidCommand(format!(
+            "RLE data length {} is not a multiple of 3", data.len()
+        )));
+    }
+    
+    let mut result = Vec::new();
+    for run in data.chunks_exact(3) {
+        if run[0] == 0 {
+            return Err(crate::error::NotifError::InvalidCommand("RLE run length is zero".to_string()));
+        }
+        let pixel = u16::from_le_bytes([run[1], run[2]]);
+        result.extend(std::iter::repeat_n(pixel, run[0] as usize));
+    }
+    
+    Ok(result)
+}
+
+/// タイルを送信用にエンコードし、(画像フォーマット, データ) を返す
+/// 
+/// RLE・インデックスカラーのうち最も小さくなるものを選び、
+/// どちらも小さくならなければ生のRGB565を使う
+pub fn encode_tile(rgb565_data: &[u16]) -> (u8, Vec<u8>) {
+    let mut best = (image_format::RAW_RGB565, rgb565_to_bytes(rgb565_data));
+    
+    let compressed = rle_encode(rgb565_data);
+    if compressed.len() < best.1.len() {
+        best = (image_format::RLE_RGB565, compressed);
+    }
+    
+    if let Some(indexed) = super::palette::encode_indexed(rgb565_data) {
+        if indexed.len() < best.1.len() {
+            best = (image_format::INDEXED, indexed);
+        }
+    }
+    
+    best
+}
+
 /// RGB565からRGB888への逆変換（デバッグ用）
 #[cfg(test)]
 pub fn rgb565_to_rgb888(rgb565: u16) -> (u8, u8, u8) {
@@ -292,4 +356,41 @@ mod tests {
         // 透明度に関係なく赤色として処理される
         assert_eq!(rgb565_data[0], 0xF800);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rle_round_trip() {
+        let pixels = vec![0xF800; 300]
+            .into_iter()
+            .chain([0x07E0, 0x001F, 0x001F])
+            .collect::<Vec<u16>>();
+        
+        let encoded = rle_encode(&pixels);
+        // 300ピクセルは255+45の2ランに分かれる
+        assert_eq!(encoded.len(), 4 * 3);
+        assert_eq!(&encoded[0..3], &[255, 0x00, 0xF8]);
+        assert_eq!(rle_decode(&encoded).unwrap(), pixels);
+        
+        assert!(rle_decode(&[1, 0]).is_err());
+        assert!(rle_decode(&[0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_encode_tile_falls_back_to_raw() {
+        // 単色タイルは圧縮される
+        let flat = vec![0x1234u16; 128];
+        let (format, data) = encode_tile(&flat);
+        assert_eq!(format, image_format::RLE_RGB565);
+        assert_eq!(data, vec![128, 0x34, 0x12]);
+        
+        // 少数色のパターンはインデックスカラー
+        let striped: Vec<u16> = (0..128).map(|i| if i % 2 == 0 { 0xF800 } else { 0x001F }).collect();
+        let (format, _) = encode_tile(&striped);
+        assert_eq!(format, image_format::INDEXED);
+        
+        // 隣接ピクセルがすべて異なる場合は生データのまま
+        let noisy: Vec<u16> = (0..128).collect();
+        let (format, data) = encode_tile(&noisy);
+        assert_eq!(format, image_format::RAW_RGB565);
+        assert_eq!(data, rgb565_to_bytes(&noisy));
+    }
+}
@@ -8,6 +8,7 @@ pub mod bluetooth;
 pub mod config;
 pub mod api;
 pub mod text;
+pub mod display;
 pub mod mcp;
 
 // v5新機能（追加のみ）
@@ -19,14 +20,23 @@ pub const NAME: &str = env!("CARGO_PKG_NAME");
 
 // 主要な型の再エクスポート
 pub use error::{NotifError, Result};
-pub use protocol::{Command, RGB, Size, StatusCode};
+pub use protocol::{Asset, ButtonEvent, ButtonPress, Command, DeviceSetting, DeviceSettings, RGB, Size, StatusCode};
 pub use bluetooth::{
     BluetoothManager,
     Connection,
     Scanner,
     DeviceInfo,
     DeviceCapabilities,
+    AckPolicy,
+    with_frame_timeout,
     CommonBluetoothManager,
+    DeviceBatteryEvent,
+    DeviceButtonEvent,
+    DeviceConnectionEvent,
+    DevicePin,
+    NotifEvent,
+    ReconnectPolicy,
+    SendReport,
 };
 pub use config::Settings;
 pub use text::{
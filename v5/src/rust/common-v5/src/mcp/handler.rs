@@ -75,7 +75,7 @@ async fn handle_json_rpc(
         "initialized" => handle_initialized(&data, &session_id).await,
         "tools/list" => super::tools::list().await,
         "tools/call" => handle_tool_call(&data, request.params).await,
-        "resources/list" => super::resources::list().await,
+        "resources/list" => super::resources::list(&data).await,
         "resources/read" => handle_resource_read(&data, request.params).await,
         "prompts/list" => super::prompts::list().await,
         "prompts/get" => handle_prompt_get(&data, request.params).await,
@@ -209,6 +209,7 @@ async fn handle_tool_call(
         "devices.list" => super::tools::devices::list(arguments, data.clone()).await,
         "devices.connect" => super::tools::devices::connect(arguments, data.clone()).await,
         "devices.disconnect" => super::tools::devices::disconnect(arguments, data.clone()).await,
+        "devices.settings" => super::tools::devices::settings(arguments, data.clone()).await,
         _ => Err(JsonRpcError {
             code: METHOD_NOT_FOUND,
             message: format!("Tool not found: {}", name),
@@ -240,11 +241,14 @@ async fn handle_resource_read(
     match uri {
         "notif://device_state" => super::resources::device_state::read(data.clone()).await,
         "notif://connection_status" => super::resources::connection_status::read(data.clone()).await,
-        _ => Err(JsonRpcError {
-            code: METHOD_NOT_FOUND,
-            message: format!("Resource not found: {}", uri),
-            data: None,
-        }),
+        _ => match uri.strip_prefix(super::resources::screen::URI_PREFIX) {
+            Some(device) => super::resources::screen::read(data.clone(), device).await,
+            None => Err(JsonRpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Resource not found: {}", uri),
+                data: None,
+            }),
+        },
     }
 }
 
@@ -21,5 +21,9 @@ pub async fn read(data: web::Data<Arc<AppState>>) -> Result<Value, JsonRpcError>
         "uptime_seconds": stats.uptime_seconds,
         "total_commands_sent": stats.total_commands_sent,
         "total_errors": stats.total_errors,
+        "bytes_sent": stats.bytes_sent,
+        "latency_ms": stats.latency_ms,
+        "by_command": stats.by_command,
+        "devices": stats.devices,
     }))
 }
\ No newline at end of file
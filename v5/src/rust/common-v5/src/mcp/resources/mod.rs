@@ -3,25 +3,40 @@
 use std::sync::Arc;
 pub mod device_state;
 pub mod connection_status;
+pub mod screen;
 
 use serde_json::{json, Value};
 
-/// リソースリストを返す
-pub async fn list() -> Result<Value, crate::mcp::JsonRpcError> {
+/// リソースリストを返す（画面は接続中のデバイスごと）
+pub async fn list(data: &actix_web::web::Data<Arc<crate::AppState>>) -> Result<Value, crate::mcp::JsonRpcError> {
+    use crate::BluetoothManager;
+    
+    let mut resources = vec![
+        json!({
+            "uri": "notif://device_state",
+            "name": "Device State",
+            "description": "Current state of all connected Bluetooth devices",
+            "mimeType": "application/json"
+        }),
+        json!({
+            "uri": "notif://connection_status",
+            "name": "Connection Status",
+            "description": "Overall connection status and statistics",
+            "mimeType": "application/json"
+        }),
+    ];
+    
+    for info in data.bt_manager.list_connected_devices().await {
+        let Some(number) = info.number else { continue };
+        resources.push(json!({
+            "uri": format!("{}{}", screen::URI_PREFIX, number),
+            "name": format!("Screen of {}", info.name),
+            "description": "What the device is currently showing, rendered from delivered commands",
+            "mimeType": "image/png"
+        }));
+    }
+    
     Ok(json!({
-        "resources": [
-            {
-                "uri": "notif://device_state",
-                "name": "Device State",
-                "description": "Current state of all connected Bluetooth devices",
-                "mimeType": "application/json"
-            },
-            {
-                "uri": "notif://connection_status",
-                "name": "Connection Status",
-                "description": "Overall connection status and statistics",
-                "mimeType": "application/json"
-            }
-        ]
+        "resources": resources
     }))
 }
\ No newline at end of file
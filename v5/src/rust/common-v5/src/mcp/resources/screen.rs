@@ -0,0 +1,46 @@
+//! 画面ミラーリソース
+
+use std::sync::Arc;
+use crate::mcp::{JsonRpcError, INTERNAL_ERROR, INVALID_PARAMS};
+use crate::AppState;
+use actix_web::web;
+use base64::Engine as _;
+use crate::BluetoothManager;
+use serde_json::{json, Value};
+
+/// 画面リソースURIのプレフィックス（後ろにデバイス番号・名前・エイリアス）
+pub const URI_PREFIX: &str = "notif://screen/";
+
+/// デバイスに表示中の画面をPNGで読み取る
+pub async fn read(data: web::Data<Arc<AppState>>, device: &str) -> Result<Value, JsonRpcError> {
+    let bt_manager = &data.bt_manager;
+    
+    let device_name = match device.parse::<usize>() {
+        Ok(number) => bt_manager.get_device_name_by_number(number).await.ok_or_else(|| JsonRpcError {
+            code: INVALID_PARAMS,
+            message: format!("Device #{} not found", number),
+            data: None,
+        })?,
+        Err(_) => bt_manager.resolve_device(device).await,
+    };
+    
+    let screen = bt_manager.get_screen(&device_name).await.ok_or_else(|| JsonRpcError {
+        code: INVALID_PARAMS,
+        message: format!("No screen for device {}", device_name),
+        data: None,
+    })?;
+    
+    let png = screen.to_png().map_err(|e| JsonRpcError {
+        code: INTERNAL_ERROR,
+        message: "Failed to encode screen".to_string(),
+        data: Some(json!({ "error": e.to_string() })),
+    })?;
+    
+    Ok(json!({
+        "contents": [{
+            "uri": format!("{}{}", URI_PREFIX, device),
+            "mimeType": "image/png",
+            "blob": base64::engine::general_purpose::STANDARD.encode(png),
+        }]
+    }))
+}
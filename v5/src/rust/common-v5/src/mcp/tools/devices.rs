@@ -2,9 +2,11 @@
 
 use std::sync::Arc;
 use crate::mcp::{JsonRpcError, INTERNAL_ERROR, INVALID_PARAMS};
+use crate::api::{handlers::resolve_selector, v2};
+use super::device_argument;
 use crate::AppState;
 use actix_web::web;
-use crate::{BluetoothManager, Scanner};
+use crate::{BluetoothManager, DeviceSettings, Scanner};
 use serde_json::{json, Value};
 use tracing::{debug, error};
 
@@ -160,4 +162,64 @@ pub async fn disconnect(
             })
         }
     }
+}
+
+/// devices.settingsの実行
+pub async fn settings(
+    arguments: Value,
+    data: web::Data<Arc<AppState>>,
+) -> Result<Value, JsonRpcError> {
+    let device = device_argument(&arguments);
+    let request: DeviceSettings = serde_json::from_value(arguments.clone())
+        .map_err(|e| JsonRpcError {
+            code: INVALID_PARAMS,
+            message: format!("Invalid settings: {}", e),
+            data: None,
+        })?;
+
+    debug!("MCP devices.settings: device={:?}, {:?}", device, request);
+
+    let curl_command = format!(
+        "curl -X POST \"http://localhost:18080/api/devices/{}/settings\" \\\n  -H \"Content-Type: application/json\" \\\n  -d '{}'",
+        device.as_deref().unwrap_or("all"),
+        serde_json::to_string(&request).unwrap_or_default()
+    );
+
+    let settings = request.into_settings().map_err(|e| JsonRpcError {
+        code: INVALID_PARAMS,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    let bt_manager = &data.bt_manager;
+    let device_names = resolve_selector(bt_manager, &v2::DeviceSelector::parse(device))
+        .await
+        .map_err(|e| JsonRpcError {
+            code: INVALID_PARAMS,
+            message: e.to_string(),
+            data: None,
+        })?;
+
+    for device_name in &device_names {
+        if let Err(e) = bt_manager.apply_settings_to_device(device_name, settings.clone()).await {
+            error!("Failed to apply settings to {}: {}", device_name, e);
+            return Err(JsonRpcError {
+                code: INTERNAL_ERROR,
+                message: format!("Failed to apply settings to {}", device_name),
+                data: Some(json!({ "error": e.to_string() })),
+            });
+        }
+    }
+
+    Ok(json!({
+        "success": true,
+        "message": format!("Applied {} setting(s) to {} device(s)", settings.len(), device_names.len()),
+        "devices": device_names,
+        "curl_equivalent": curl_command,
+        "api_info": {
+            "endpoint": "/api/devices/{device}/settings",
+            "method": "POST",
+            "description": "v2 APIのデバイス設定エンドポイント"
+        }
+    }))
 }
\ No newline at end of file
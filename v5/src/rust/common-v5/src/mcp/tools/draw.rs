@@ -2,9 +2,11 @@
 
 use std::sync::Arc;
 use crate::mcp::{JsonRpcError, INTERNAL_ERROR, INVALID_PARAMS};
+use crate::api::{handlers::{selector_capabilities, send_to_selector}, v2};
+use super::{device_argument, send_report_error};
 use crate::AppState;
 use actix_web::web;
-use crate::{BluetoothManager, protocol::{Command, RGB, Size}, api::models::parse_color_name};
+use crate::{protocol::{Command, RGB, Size}, api::models::parse_color_name};
 use serde_json::{json, Value};
 use tracing::{debug, error, info};
 
@@ -23,10 +25,8 @@ pub async fn execute(
             data: None,
         })?;
 
-    let device = arguments
-        .get("device")
-        .and_then(|d| d.as_u64())
-        .unwrap_or(1) as usize;
+    let device = device_argument(&arguments).unwrap_or_else(|| "1".to_string());
+    let selector = v2::DeviceSelector::parse(Some(device.clone()));
 
     let overwrite = arguments
         .get("overwrite")
@@ -42,17 +42,22 @@ pub async fn execute(
 
     let bt_manager = &data.bt_manager;
 
+    // 送信先の画面サイズに合わせたグリッドで領域を制限
+    let (grid_width, grid_height) = selector_capabilities(bt_manager, &selector).await.grid_size();
+    let (grid_width, grid_height) = (grid_width as i32, grid_height as i32);
+
     // overwrite=false（デフォルト）の場合は画面クリア
     // overwrite=trueの場合は既存表示を保持
     if !overwrite {
         let clear_cmd = Command::Clear { 
             color: RGB::black() 
         };
-        if let Err(e) = bt_manager
-            .send_command_by_number(device, clear_cmd)
-            .await
-        {
-            error!("Failed to clear screen: {}", e);
+        match send_to_selector(bt_manager, &selector, clear_cmd).await {
+            Ok(report) if !report.all_succeeded() => {
+                error!("Failed to clear screen on {} device(s)", report.failed().len());
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to clear screen: {}", e),
         }
     }
 
@@ -71,11 +76,11 @@ pub async fn execute(
             continue;
         }
         
-        // 座標パラメータを解析（coords: "row1,col1,row2,col2" v2 API互換）
+        // 座標パラメータを解析（coords: "row1,col1,row2,col2" v2 API互換、省略時は全画面）
         let coords = region
             .get("coords")
             .and_then(|c| c.as_str())
-            .unwrap_or("0,0,31,31");
+            .unwrap_or("");
         
         let coord_parts: Vec<&str> = coords.split(',').collect();
         let row1 = coord_parts.get(0)
@@ -86,16 +91,16 @@ pub async fn execute(
             .unwrap_or(0);
         let row2 = coord_parts.get(2)
             .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(31);
+            .unwrap_or(grid_height - 1);
         let col2 = coord_parts.get(3)
             .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(31);
+            .unwrap_or(grid_width - 1);
         
         // v2 API互換: row,col座標をx,y座標に変換
-        let x = col1.max(0).min(31) as u8;
-        let y = row1.max(0).min(31) as u8;
-        let width = (col2 - col1 + 1).max(1).min(32) as u8;
-        let height = (row2 - row1 + 1).max(1).min(32) as u8;
+        let x = col1.clamp(0, grid_width - 1) as u16;
+        let y = row1.clamp(0, grid_height - 1) as u16;
+        let width = (col2 - col1 + 1).clamp(1, grid_width) as u16;
+        let height = (row2 - row1 + 1).clamp(1, grid_height) as u16;
         
         // 背景色パラメータ（bg）
         let bg_color = region
@@ -166,8 +171,12 @@ pub async fn execute(
     // バッチコマンドとして送信
     let batch_command = Command::Batch { commands };
     
-    match bt_manager.send_command_by_number(device, batch_command).await {
-        Ok(_) => {
+    match send_to_selector(bt_manager, &selector, batch_command).await {
+        Ok(report) if !report.all_succeeded() => {
+            error!("Failed to draw regions on {} device(s)", report.failed().len());
+            Err(send_report_error("Failed to draw regions", &report))
+        }
+        Ok(report) => {
             info!("MCP draw command executed successfully");
             
             // 等価なcurlコマンドを生成（v2 API形式）
@@ -215,6 +224,7 @@ pub async fn execute(
             Ok(json!({
                 "success": true,
                 "message": format!("Drew {} regions", regions_processed),
+                "devices": report,
                 "curl_equivalent": curl_command,
                 "api_info": {
                     "endpoint": "/api/draw",
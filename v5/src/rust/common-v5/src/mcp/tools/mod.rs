@@ -20,13 +20,14 @@ pub async fn list() -> Result<Value, super::JsonRpcError> {
                     "properties": {
                         "text": {
                             "type": "string",
-                            "description": "送信するテキスト。絵文字(😊など)と改行(\\n)に対応。32x32グリッドに自動折り返し。"
+                            "description": "送信するテキスト。絵文字(😊など)と改行(\\n)に対応。送信先の画面のグリッド（128pxで32x32）に自動折り返し。"
                         },
                         "device": {
-                            "type": "integer",
-                            "description": "デバイス番号 (1-9)。省略時は全デバイスに送信。",
+                            "type": ["integer", "string"],
+                            "description": "デバイス番号 (1-9)、エイリアス、または \"group:名前\" でグループ内の全デバイス。省略時は全デバイスに送信。",
                             "minimum": 1,
-                            "maximum": 9
+                            "maximum": 9,
+                            "examples": [1, "desk", "group:build-status"]
                         },
                         "bgcolor": {
                             "type": "string",
@@ -65,7 +66,7 @@ pub async fn list() -> Result<Value, super::JsonRpcError> {
                                 "properties": {
                                     "coords": {
                                         "type": "string",
-                                        "description": "領域座標 'row1,col1,row2,col2' 形式 (0-31の範囲)",
+                                        "description": "領域座標 'row1,col1,row2,col2' 形式 (128pxの画面で0-31、画面外は切り詰め。省略時は全画面)",
                                         "pattern": "^\\d+,\\d+,\\d+,\\d+$",
                                         "examples": ["0,0,31,31", "0,0,15,15", "16,16,31,31"]
                                     },
@@ -95,11 +96,12 @@ pub async fn list() -> Result<Value, super::JsonRpcError> {
                             }
                         },
                         "device": {
-                            "type": "integer",
-                            "description": "デバイス番号 (1-9)",
+                            "type": ["integer", "string"],
+                            "description": "デバイス番号 (1-9)、エイリアス、または \"group:名前\" でグループ内の全デバイス",
                             "default": 1,
                             "minimum": 1,
-                            "maximum": 9
+                            "maximum": 9,
+                            "examples": [1, "desk", "group:build-status"]
                         },
                         "overwrite": {
                             "type": "boolean",
@@ -156,7 +158,61 @@ pub async fn list() -> Result<Value, super::JsonRpcError> {
                     },
                     "required": ["device"]
                 }
+            },
+            {
+                "name": "devices.settings",
+                "description": "Bluetoothディスプレイの輝度・スリープ・画面の向き・デバイス名を変更します。指定した項目だけを変更。夜間の減光などに使用。",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "device": {
+                            "type": ["integer", "string"],
+                            "description": "デバイス番号 (1-9)、エイリアス、または \"group:名前\" でグループ内の全デバイス。省略時は全デバイスに適用。",
+                            "minimum": 1,
+                            "maximum": 9
+                        },
+                        "brightness": {
+                            "type": "integer",
+                            "description": "バックライト輝度 (0-255)",
+                            "minimum": 0,
+                            "maximum": 255
+                        },
+                        "sleep": {
+                            "type": "boolean",
+                            "description": "true: ディスプレイをスリープ、false: 復帰"
+                        },
+                        "rotation": {
+                            "type": "integer",
+                            "description": "画面の向き（度）",
+                            "enum": [0, 90, 180, 270]
+                        },
+                        "name": {
+                            "type": "string",
+                            "description": "BLEデバイス名（最大20バイト、デバイス再起動後に反映）",
+                            "minLength": 1,
+                            "maxLength": 20
+                        }
+                    }
+                }
             }
         ]
     }))
-}
\ No newline at end of file
+}
+/// ツール引数の送信先（デバイス番号、またはエイリアス・`group:名前`の文字列）
+pub(crate) fn device_argument(arguments: &Value) -> Option<String> {
+    match arguments.get("device")? {
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(device) => Some(device.clone()),
+        _ => None,
+    }
+}
+
+/// 一部のデバイスへの送信に失敗した場合のエラー（デバイスごとの結果を含む）
+pub(crate) fn send_report_error(message: &str, report: &crate::SendReport) -> super::JsonRpcError {
+    let error = report.failed().first().map(|(_, e)| e.to_string());
+    super::JsonRpcError {
+        code: super::INTERNAL_ERROR,
+        message: message.to_string(),
+        data: Some(json!({ "error": error, "devices": report })),
+    }
+}
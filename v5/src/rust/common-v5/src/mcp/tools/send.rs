@@ -2,9 +2,11 @@
 
 use std::sync::Arc;
 use crate::mcp::{JsonRpcError, INTERNAL_ERROR, INVALID_PARAMS};
+use crate::api::{handlers::{selector_capabilities, send_to_selector}, v2};
+use super::{device_argument, send_report_error};
 use crate::AppState;
 use actix_web::web;
-use crate::{BluetoothManager, protocol::{Command, RGB, Size}, api::models::parse_color_name};
+use crate::{protocol::{Command, RGB, Size}, api::models::parse_color_name};
 use serde_json::{json, Value};
 use tracing::{debug, error, info};
 
@@ -38,18 +40,20 @@ pub async fn execute(
         .and_then(|s| s.as_u64())
         .unwrap_or(3) as u8;
 
-    let device = arguments
-        .get("device")
-        .and_then(|d| d.as_u64())
-        .map(|d| d as usize);
+    let device = device_argument(&arguments);
 
     info!(
         "MCP send tool called: text='{}', bg={}, color={}, size={}, device={:?}",
         text, bgcolor, color, size, device
     );
 
+    // 送信先の画面サイズに合わせて折り返す
+    let bt_manager = &data.bt_manager;
+    let selector = v2::DeviceSelector::parse(device.clone());
+    let grid = selector_capabilities(bt_manager, &selector).await.grid_size();
+
     // v1 API実装を活用したコマンド生成
-    let commands = match build_mcp_send_commands(text, bgcolor, color, size) {
+    let commands = match build_mcp_send_commands(text, bgcolor, color, size, grid) {
         Ok(cmds) => {
             info!("MCP send tool: Built {} commands", cmds.len());
             cmds
@@ -66,27 +70,22 @@ pub async fn execute(
 
     let batch_command = Command::Batch { commands };
 
-    // 送信
-    let bt_manager = &data.bt_manager;
-    let result = if let Some(device_num) = device {
-        // 特定デバイスに送信
-        bt_manager
-            .send_command_by_number(device_num, batch_command)
-            .await
-    } else {
-        // 全デバイスに送信
-        bt_manager.send_command_to_all(batch_command).await
-    };
+    // 送信（省略時は全デバイス、グループはメンバー全員）
+    let result = send_to_selector(bt_manager, &selector, batch_command).await;
 
     match result {
-        Ok(_) => {
+        Ok(report) if !report.all_succeeded() => {
+            error!("Failed to send message to {} device(s)", report.failed().len());
+            Err(send_report_error("Failed to send message", &report))
+        }
+        Ok(report) => {
             info!("MCP send command executed successfully");
             
             // 等価なcurlコマンドを生成
-            let curl_command = if let Some(device_num) = device {
+            let curl_command = if let Some(device) = &device {
                 format!(
                     "curl -G \"http://localhost:18080/send\" \\\n  --data-urlencode \"text={}\" \\\n  --data-urlencode \"bgcolor={}\" \\\n  --data-urlencode \"color={}\" \\\n  --data-urlencode \"size={}\" \\\n  --data-urlencode \"device={}\"",
-                    text, bgcolor, color, size, device_num
+                    text, bgcolor, color, size, device
                 )
             } else {
                 format!(
@@ -98,6 +97,7 @@ pub async fn execute(
             Ok(json!({
                 "success": true,
                 "message": format!("Message sent: {}", text),
+                "devices": report,
                 "curl_equivalent": curl_command,
                 "api_info": {
                     "endpoint": "/send",
@@ -128,7 +128,8 @@ fn build_mcp_send_commands(
     text: &str,
     bgcolor: &str, 
     color: &str,
-    size: u8
+    size: u8,
+    grid: (u8, u8),
 ) -> crate::error::Result<Vec<Command>> {
     let mut commands = Vec::new();
     
@@ -147,14 +148,14 @@ fn build_mcp_send_commands(
     };
     
     // v1 API実装と同じテキスト処理を使用
-    let text_commands = build_mcp_text_commands(text, text_size, text_rgb)?;
+    let text_commands = build_mcp_text_commands(text, text_size, text_rgb, grid)?;
     commands.extend(text_commands);
     
     Ok(commands)
 }
 
 /// MCPテキスト処理（v1 API実装をベース）
-fn build_mcp_text_commands(text: &str, size: Size, color: RGB) -> crate::error::Result<Vec<Command>> {
+fn build_mcp_text_commands(text: &str, size: Size, color: RGB, grid: (u8, u8)) -> crate::error::Result<Vec<Command>> {
     use crate::text::{parse_text_with_emoji, TextSegment};
     
     info!("MCP send tool: Processing text with emoji support: '{}'", text);
@@ -166,19 +167,15 @@ fn build_mcp_text_commands(text: &str, size: Size, color: RGB) -> crate::error::
     let lines: Vec<&str> = text.split('\n').collect();
     info!("MCP send tool: Split into {} lines", lines.len());
     
-    // v2互換のグリッド座標系（32x32）での文字サイズを計算
-    let (ascii_width_grids, y_spacing_grids) = match size {
-        Size::Small => (2, 4),   // サイズ1: ASCII 2グリッド、漢字 4グリッド、4グリッド行間
-        Size::Medium => (3, 6),  // サイズ2: ASCII 3グリッド、漢字 6グリッド、6グリッド行間
-        Size::Large => (4, 8),   // サイズ3: ASCII 4グリッド、漢字 8グリッド、8グリッド行間
-        Size::XLarge => (5, 10), // サイズ4: ASCII 5グリッド、漢字 10グリッド、10グリッド行間
-    };
+    // v2互換のグリッド座標系（1グリッド4ピクセル、128pxで32x32）での文字サイズを計算
+    let (grid_width, grid_height) = grid;
+    let (ascii_width_grids, y_spacing_grids) = (size.char_grids(), size.line_grids());
     
     let mut current_y = 0u8;
     
     for line in lines.iter() {
-        // 画面外チェック（32グリッドの高さ制限）
-        if current_y + y_spacing_grids > 32 {
+        // 画面外チェック（グリッドの高さ制限）
+        if current_y + y_spacing_grids > grid_height {
             break;
         }
         
@@ -209,13 +206,13 @@ fn build_mcp_text_commands(text: &str, size: Size, color: RGB) -> crate::error::
                                 ascii_width_grids * 2  // 全角文字はASCIIの2倍
                             };
                             
-                            // 現在の行に収まらない場合（32グリッド幅制限）
-                            if current_x + char_width > 32 {
+                            // 現在の行に収まらない場合（グリッド幅制限）
+                            if current_x + char_width > grid_width {
                                 // これまでのテキストを送信
                                 if !line_text.is_empty() {
                                     commands.push(Command::Text {
-                                        x: line_start_x,
-                                        y: current_y,
+                                        x: line_start_x as u16,
+                                        y: current_y as u16,
                                         size,
                                         color,
                                         text: line_text.clone(),
@@ -228,7 +225,7 @@ fn build_mcp_text_commands(text: &str, size: Size, color: RGB) -> crate::error::
                                 current_y += y_spacing_grids;
                                 line_start_x = 0;
                                 
-                                if current_y + y_spacing_grids > 32 {
+                                if current_y + y_spacing_grids > grid_height {
                                     return Ok(commands);  // 画面外なので終了
                                 }
                                 
@@ -243,11 +240,11 @@ fn build_mcp_text_commands(text: &str, size: Size, color: RGB) -> crate::error::
                         }
                         
                         // 残りのテキストを送信
-                        if !line_text.is_empty() && current_y + y_spacing_grids <= 32 {
+                        if !line_text.is_empty() && current_y + y_spacing_grids <= grid_height {
                             info!("MCP send tool: Adding text '{}' at ({},{})", line_text, line_start_x, current_y);
                             commands.push(Command::Text {
-                                x: line_start_x,
-                                y: current_y,
+                                x: line_start_x as u16,
+                                y: current_y as u16,
                                 size,
                                 color,
                                 text: line_text,
@@ -260,34 +257,29 @@ fn build_mcp_text_commands(text: &str, size: Size, color: RGB) -> crate::error::
                     let emoji_width = ascii_width_grids * 2;
                     
                     // 絵文字の高さ（フォントサイズに応じた実際の高さ）
-                    let emoji_height = match size {
-                        Size::Small => 4,   // 1行分の高さ
-                        Size::Medium => 6,  // 1行分の高さ
-                        Size::Large => 8,   // 1行分の高さ
-                        Size::XLarge => 10, // 1行分の高さ
-                    };
+                    let emoji_height = y_spacing_grids;  // 1行分の高さ
                     
                     info!("Processing emoji U+{:04X} at current_x={}, current_y={}, emoji_width={}, emoji_height={}", 
                           code, current_x, current_y, emoji_width, emoji_height);
                     
                     // 絵文字が現在の行に収まらない場合は改行
-                    if current_x + emoji_width > 32 && current_x > 0 {
+                    if current_x + emoji_width > grid_width && current_x > 0 {
                         info!("Emoji doesn't fit in current line, wrapping to next line");
                         current_x = 0;
                         current_y += y_spacing_grids;
                         
-                        if current_y + emoji_height > 32 {
-                            info!("Emoji Y coordinate {} + {} > 32, skipping", current_y, emoji_height);
+                        if current_y + emoji_height > grid_height {
+                            info!("Emoji Y coordinate {} + {} > {}, skipping", current_y, emoji_height, grid_height);
                             break;
                         }
                     }
                     
                     // 絵文字が画面内に収まるかチェック
-                    if current_x + emoji_width <= 32 && current_y + emoji_height <= 32 {
+                    if current_x + emoji_width <= grid_width && current_y + emoji_height <= grid_height {
                         info!("MCP send tool: Adding emoji U+{:04X} at ({},{})", code, current_x, current_y);
                         commands.push(Command::Emoji {
-                            x: current_x,
-                            y: current_y,
+                            x: current_x as u16,
+                            y: current_y as u16,
                             size: size.to_byte(),
                             code,
                         });
@@ -45,6 +45,17 @@ impl Size {
         }
     }
     
+    /// バイト値からフォントサイズに変換（不明な値はエラー）
+    pub fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            1 => Ok(Size::Small),
+            2 => Ok(Size::Medium),
+            3 => Ok(Size::Large),
+            4 => Ok(Size::XLarge),
+            _ => Err(NotifError::InvalidCommand(format!("Unknown font size: {}", byte))),
+        }
+    }
+    
     pub fn from_str(s: &str) -> Self {
         match s {
             "1" | "small" => Size::Small,
@@ -63,16 +74,36 @@ impl Size {
             Size::XLarge => 24,
         }
     }
+    
+    /// テキスト配置でのASCII 1文字の幅（グリッド単位、全角文字・絵文字は2倍）
+    pub fn char_grids(&self) -> u8 {
+        match self {
+            Size::Small => 2,
+            Size::Medium => 3,
+            Size::Large => 4,
+            Size::XLarge => 5,
+        }
+    }
+    
+    /// テキスト配置での行の送り（グリッド単位）
+    pub fn line_grids(&self) -> u8 {
+        match self {
+            Size::Small => 4,
+            Size::Medium => 6,
+            Size::Large => 8,
+            Size::XLarge => 10,
+        }
+    }
 }
 
 /// コマンドタイプ
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Command {
     /// テキスト表示 (v2/ATOMS3互換)
     Text {
-        x: u8,
-        y: u8,
+        x: u16,
+        y: u16,
         size: Size,
         color: RGB,
         text: String,
@@ -85,47 +116,47 @@ pub enum Command {
     
     /// 線描画 (v2/ATOMS3互換)
     Line {
-        x1: u8,
-        y1: u8,
-        x2: u8,
-        y2: u8,
+        x1: u16,
+        y1: u16,
+        x2: u16,
+        y2: u16,
         width: u8,
         color: RGB,
     },
     
     /// 矩形描画 (v2/ATOMS3互換)
     Rect {
-        x: u8,
-        y: u8,
-        width: u8,
-        height: u8,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
         fill: bool,
         color: RGB,
     },
     
     /// 円描画 (v2/ATOMS3互換)
     Circle {
-        x: u8,
-        y: u8,
-        radius: u8,
+        x: u16,
+        y: u16,
+        radius: u16,
         color: RGB,
         filled: bool,
     },
     
     /// 画像表示 (v2/ATOMS3互換)
     Image {
-        x: u8,
-        y: u8,
-        width: u8,
-        height: u8,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
         format: u8,
         data: Vec<u8>,
     },
     
     /// 絵文字表示 (v2/ATOMS3互換)
     Emoji {
-        x: u8,
-        y: u8,
+        x: u16,
+        y: u16,
         size: u8,
         code: u32,
     },
@@ -142,10 +173,88 @@ pub enum Command {
     
     /// 画面更新
     Update,
+    
+    /// シーケンス番号付きフレーム（欠落検出・再送用）
+    Sequenced {
+        seq: u16,
+        command: Box<Command>,
+    },
+    
+    /// アセット定義（デバイス側キャッシュ領域を確保）
+    DefineAsset {
+        id: u16,
+        width: u8,
+        height: u8,
+        format: u8,
+        length: u16,
+    },
+    
+    /// アセットデータの断片
+    AssetData {
+        id: u16,
+        offset: u16,
+        data: Vec<u8>,
+    },
+    
+    /// キャッシュ済みアセットを描画
+    DrawAsset {
+        id: u16,
+        x: u16,
+        y: u16,
+    },
+}
+
+/// デバイスにキャッシュさせる画像アセット
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    pub width: u8,
+    pub height: u8,
+    /// 画像フォーマット（`image_format`）
+    pub format: u8,
+    pub data: Vec<u8>,
+}
+
+impl Asset {
+    /// アセットをデバイスへ転送するコマンド列（定義 + データ断片）
+    pub fn upload_commands(&self, id: u16) -> Result<Vec<Command>> {
+        let length = u16::try_from(self.data.len()).map_err(|_| NotifError::InvalidCommand(format!(
+            "Asset {} too large: {} bytes", id, self.data.len()
+        )))?;
+        
+        let mut commands = vec![Command::DefineAsset {
+            id,
+            width: self.width,
+            height: self.height,
+            format: self.format,
+            length,
+        }];
+        
+        for (index, chunk) in self.data.chunks(ASSET_CHUNK_SIZE).enumerate() {
+            commands.push(Command::AssetData {
+                id,
+                offset: (index * ASSET_CHUNK_SIZE) as u16,
+                data: chunk.to_vec(),
+            });
+        }
+        
+        Ok(commands)
+    }
+    
+    /// アセットを直接描画するImageコマンド（アセット非対応デバイス向け）
+    pub fn to_image(&self, x: u16, y: u16) -> Command {
+        Command::Image {
+            x,
+            y,
+            width: self.width as u16,
+            height: self.height as u16,
+            format: self.format,
+            data: self.data.clone(),
+        }
+    }
 }
 
 /// 領域定義
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Region {
     pub x: i32,
     pub y: i32,
@@ -156,108 +265,622 @@ pub struct Region {
 
 impl Command {
     /// コマンドをバイト列にエンコード
-    pub fn encode(&self) -> Vec<u8> {
-        let mut data = Vec::new();
-        
+    /// 
+    /// 座標・サイズが全て255以下なら従来形式、それ以外は拡張コマンドセット（u16）で送る。
+    /// 1バイトや2バイトのフィールドに収まらない値はエラーにする（切り捨てない）
+    pub fn encode(&self) -> Result<Vec<u8>> {
         match self {
             Command::Text { x, y, size, color, text } => {
                 let text_bytes = text.as_bytes();
-                let payload_len = 7 + text_bytes.len(); // x(1) + y(1) + size(1) + color(3) + text_len(1) + text
+                let text_len = u8::try_from(text_bytes.len()).map_err(|_| NotifError::InvalidCommand(format!(
+                    "Text too long: {} bytes (max {})", text_bytes.len(), MAX_TEXT_BYTES
+                )))?;
                 
-                data.push(command_type::TEXT); // 0x02 - ATOMS3互換
-                data.push((payload_len & 0xFF) as u8); // ペイロード長(リトルエンディアン)
-                data.push((payload_len >> 8) as u8);
-                data.extend_from_slice(&[*x, *y, size.to_byte()]);
-                data.extend_from_slice(&[color.r, color.g, color.b]);
-                data.push(text_bytes.len() as u8);
-                data.extend_from_slice(text_bytes);
+                // x(1) + y(1) + size(1) + color(3) + text_len(1) + text
+                let (extended, mut payload) = encode_coords(&[*x, *y]);
+                payload.extend_from_slice(&[size.to_byte(), color.r, color.g, color.b, text_len]);
+                payload.extend_from_slice(text_bytes);
+                frame(command_type::select(command_type::TEXT, extended), &payload) // 0x02 - ATOMS3互換
             }
             
             Command::Clear { color } => {
-                data.push(command_type::CLEAR); // 0x01 - ATOMS3互換
-                data.extend_from_slice(&[3, 0]); // ペイロード長(リトルエンディアン)
-                data.extend_from_slice(&[color.r, color.g, color.b]);
+                frame(command_type::CLEAR, &[color.r, color.g, color.b]) // 0x01 - ATOMS3互換
             }
             
             Command::Line { x1, y1, x2, y2, width, color } => {
-                data.push(command_type::LINE); // 0x05 - ATOMS3互換
-                data.extend_from_slice(&[8, 0]); // ペイロード長(リトルエンディアン)
-                data.extend_from_slice(&[*x1, *y1, *x2, *y2, *width]);
-                data.extend_from_slice(&[color.r, color.g, color.b]);
+                let (extended, mut payload) = encode_coords(&[*x1, *y1, *x2, *y2]);
+                payload.extend_from_slice(&[*width, color.r, color.g, color.b]);
+                frame(command_type::select(command_type::LINE, extended), &payload) // 0x05 - ATOMS3互換
             }
             
             Command::Rect { x, y, width, height, fill, color } => {
-                data.push(command_type::RECT); // 0x04 - ATOMS3互換
-                data.extend_from_slice(&[8, 0]); // ペイロード長(リトルエンディアン)
-                data.extend_from_slice(&[*x, *y, *width, *height]);
-                data.push(if *fill { 1 } else { 0 });
-                data.extend_from_slice(&[color.r, color.g, color.b]);
+                let fill = if *fill { 1 } else { 0 };
+                let (extended, mut payload) = encode_coords(&[*x, *y, *width, *height]);
+                payload.extend_from_slice(&[fill, color.r, color.g, color.b]);
+                frame(command_type::select(command_type::RECT, extended), &payload) // 0x04 - ATOMS3互換
             }
             
             Command::Circle { x, y, radius, color, filled } => {
                 // 注：CircleはATOMS3ファームウェアで未サポートの可能性があります
-                data.push(0x05); // CMD_CIRCLE (カスタム)
-                data.extend_from_slice(&[7, 0]); // ペイロード長(リトルエンディアン)
-                data.extend_from_slice(&[*x, *y, *radius]);
-                data.extend_from_slice(&[color.r, color.g, color.b]);
-                data.push(if *filled { 1 } else { 0 });
+                let filled = if *filled { 1 } else { 0 };
+                let (extended, mut payload) = encode_coords(&[*x, *y, *radius]);
+                payload.extend_from_slice(&[color.r, color.g, color.b, filled]);
+                frame(command_type::select(command_type::CIRCLE, extended), &payload) // 0x07 (カスタム)
             }
             
             Command::Image { x, y, width, height, format, data: image_data } => {
-                let payload_len = 5 + image_data.len(); // x(1) + y(1) + w(1) + h(1) + format(1) + data
-                
-                data.push(command_type::IMAGE); // 0x06 - ATOMS3互換
-                data.push((payload_len & 0xFF) as u8); // ペイロード長(リトルエンディアン)
-                data.push((payload_len >> 8) as u8);
-                data.extend_from_slice(&[*x, *y, *width, *height, *format]);
-                data.extend_from_slice(image_data);
+                // x(1) + y(1) + w(1) + h(1) + format(1) + data
+                let (extended, mut payload) = encode_coords(&[*x, *y, *width, *height]);
+                payload.push(*format);
+                payload.extend_from_slice(image_data);
+                frame(command_type::select(command_type::IMAGE, extended), &payload) // 0x06 - ATOMS3互換
             }
             
             Command::Emoji { x, y, size, code } => {
-                data.push(command_type::EMOJI); // 0x03 - ATOMS3互換
-                data.extend_from_slice(&[7, 0]); // ペイロード長(リトルエンディアン)
-                data.extend_from_slice(&[*x, *y, *size]);
-                data.extend_from_slice(&code.to_le_bytes());
+                let (extended, mut payload) = encode_coords(&[*x, *y]);
+                payload.push(*size);
+                payload.extend_from_slice(&code.to_le_bytes());
+                frame(command_type::select(command_type::EMOJI, extended), &payload) // 0x03 - ATOMS3互換
             }
             
             Command::Update => {
-                data.push(0x08); // CMD_UPDATE
+                Ok(vec![command_type::UPDATE]) // CMD_UPDATE
             }
             
             Command::Batch { commands } => {
-                let mut payload = vec![commands.len() as u8];
+                let count = u8::try_from(commands.len()).map_err(|_| NotifError::InvalidCommand(format!(
+                    "Too many commands in batch: {} (max {})", commands.len(), MAX_BATCH_COMMANDS
+                )))?;
+                
+                let mut payload = vec![count];
                 for cmd in commands {
-                    payload.extend_from_slice(&cmd.encode());
+                    payload.extend_from_slice(&cmd.encode()?);
                 }
-                
-                data.push(command_type::BATCH); // 0x10 - ATOMS3互換
-                let payload_len = payload.len();
-                data.push((payload_len & 0xFF) as u8);
-                data.push((payload_len >> 8) as u8);
-                data.extend_from_slice(&payload);
+                frame(command_type::BATCH, &payload) // 0x10 - ATOMS3互換
             }
             
             Command::Region { regions } => {
                 // Region処理は複雑なのでATOMS3で未サポートの可能性があります
-                data.push(command_type::REGION); // 0x0A (カスタム)
-                let mut payload = vec![regions.len() as u8];
-                for region in regions {
-                    payload.extend_from_slice(&[region.x as u8, region.y as u8]);
-                    payload.extend_from_slice(&[region.width as u8, region.height as u8]);
-                    let content_data = region.content.encode();
-                    payload.extend_from_slice(&(content_data.len() as u16).to_le_bytes());
+                let count = u8::try_from(regions.len()).map_err(|_| NotifError::InvalidCommand(format!(
+                    "Too many regions: {} (max 255)", regions.len()
+                )))?;
+                
+                let bounds = regions.iter()
+                    .map(|region| {
+                        let out_of_range = || NotifError::InvalidCommand(format!(
+                            "Region out of range: ({}, {}) {}x{}", region.x, region.y, region.width, region.height
+                        ));
+                        Ok([
+                            u16::try_from(region.x).map_err(|_| out_of_range())?,
+                            u16::try_from(region.y).map_err(|_| out_of_range())?,
+                            u16::try_from(region.width).map_err(|_| out_of_range())?,
+                            u16::try_from(region.height).map_err(|_| out_of_range())?,
+                        ])
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                
+                // 1つでも255を超える領域があれば全領域を拡張形式で送る
+                let extended = bounds.iter().flatten().any(|&value| value > u8::MAX as u16);
+                
+                let mut payload = vec![count];
+                for (region, bounds) in regions.iter().zip(&bounds) {
+                    if extended {
+                        payload.extend(bounds.iter().flat_map(|value| value.to_le_bytes()));
+                    } else {
+                        payload.extend(bounds.iter().map(|&value| value as u8));
+                    }
+                    let content_data = region.content.encode()?;
+                    let content_len = u16::try_from(content_data.len()).map_err(|_| NotifError::InvalidCommand(format!(
+                        "Region content too large: {} bytes", content_data.len()
+                    )))?;
+                    payload.extend_from_slice(&content_len.to_le_bytes());
                     payload.extend_from_slice(&content_data);
                 }
-                data.push((payload.len() & 0xFF) as u8);
-                data.push((payload.len() >> 8) as u8);
-                data.extend_from_slice(&payload);
+                frame(command_type::select(command_type::REGION, extended), &payload) // 0x0A (カスタム)
+            }
+            
+            Command::Sequenced { seq, command } => {
+                let mut payload = seq.to_le_bytes().to_vec();
+                payload.extend_from_slice(&command.encode()?);
+                frame(command_type::SEQUENCED, &payload) // 0x20 (カスタム)
+            }
+            
+            Command::DefineAsset { id, width, height, format, length } => {
+                let mut payload = id.to_le_bytes().to_vec();
+                payload.extend_from_slice(&[*width, *height, *format]);
+                payload.extend_from_slice(&length.to_le_bytes());
+                frame(command_type::DEFINE_ASSET, &payload) // 0x11 (カスタム)
+            }
+            
+            Command::AssetData { id, offset, data: asset_data } => {
+                let mut payload = id.to_le_bytes().to_vec();
+                payload.extend_from_slice(&offset.to_le_bytes());
+                payload.extend_from_slice(asset_data);
+                frame(command_type::ASSET_DATA, &payload) // 0x12 (カスタム)
+            }
+            
+            Command::DrawAsset { id, x, y } => {
+                let (extended, coords) = encode_coords(&[*x, *y]);
+                let mut payload = id.to_le_bytes().to_vec();
+                payload.extend_from_slice(&coords);
+                frame(command_type::select(command_type::DRAW_ASSET, extended), &payload) // 0x13 (カスタム)
+            }
+        }
+    }
+    
+    /// 拡張コマンドセット（u16座標）でエンコードされるか
+    /// 
+    /// Batch・Sequencedの中身は含まない（個別に判定する）
+    pub fn needs_extended(&self) -> bool {
+        let wide = |values: &[u16]| values.iter().any(|&value| value > u8::MAX as u16);
+        match self {
+            Command::Text { x, y, .. } | Command::Emoji { x, y, .. } | Command::DrawAsset { x, y, .. } => wide(&[*x, *y]),
+            Command::Rect { x, y, width, height, .. } | Command::Image { x, y, width, height, .. } => {
+                wide(&[*x, *y, *width, *height])
+            }
+            Command::Line { x1, y1, x2, y2, .. } => wide(&[*x1, *y1, *x2, *y2]),
+            Command::Circle { x, y, radius, .. } => wide(&[*x, *y, *radius]),
+            Command::Region { regions } => regions.iter().any(|region| {
+                region.x > u8::MAX as i32 || region.y > u8::MAX as i32
+                    || region.width > u8::MAX as u32 || region.height > u8::MAX as u32
+            }),
+            _ => false,
+        }
+    }
+    
+    /// 1フレームに収まらないTextとBatchを複数の有効なコマンドに分割する
+    /// 
+    /// Textは行を送りながら255バイト以下に分け、Batchは件数とペイロード長の上限で区切る。
+    /// それ以外のコマンドはそのまま返す。
+    pub fn split_frames(self) -> Result<Vec<Command>> {
+        match self {
+            Command::Text { x, y, size, color, text } if text.len() > MAX_TEXT_BYTES => {
+                // Textの座標はグリッド単位なので、行の送りもグリッドで数える
+                let line_height = size.line_grids();
+                Ok(split_text(&text, MAX_TEXT_BYTES)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, chunk)| Command::Text {
+                        x,
+                        y: y.saturating_add((line_height as u16).saturating_mul(index.min(u16::MAX as usize) as u16)),
+                        size,
+                        color,
+                        text: chunk.to_string(),
+                    })
+                    .collect())
+            }
+            
+            Command::Batch { commands } => {
+                let mut frames = Vec::new();
+                let mut current = Vec::new();
+                let mut current_len = 1; // 件数(1)
+                
+                for command in commands {
+                    for command in command.split_frames()? {
+                        let len = command.encode()?.len();
+                        if !current.is_empty()
+                            && (current.len() == MAX_BATCH_COMMANDS || current_len + len > u16::MAX as usize)
+                        {
+                            frames.push(Command::Batch { commands: std::mem::take(&mut current) });
+                            current_len = 1;
+                        }
+                        current_len += len;
+                        current.push(command);
+                    }
+                }
+                
+                if !current.is_empty() || frames.is_empty() {
+                    frames.push(Command::Batch { commands: current });
+                }
+                Ok(frames)
             }
+            
+            command => Ok(vec![command]),
         }
+    }
+    
+    /// 必要に応じて分割した上で、送信用のフレーム列にエンコード
+    pub fn encode_frames(&self) -> Result<Vec<Vec<u8>>> {
+        self.clone()
+            .split_frames()?
+            .iter()
+            .map(Command::encode)
+            .collect()
+    }
+    
+    /// バイト列から1コマンドをデコード
+    /// 
+    /// 戻り値はデコードしたコマンドと消費したバイト数。
+    /// `encode` の逆変換で、BatchとRegionの入れ子も再帰的に復元する。
+    pub fn decode(data: &[u8]) -> Result<(Command, usize)> {
+        let opcode = *data.first()
+            .ok_or_else(|| NotifError::InvalidCommand("Empty command data".to_string()))?;
         
-        data
+        // CMD_UPDATEはペイロード長を持たない
+        if opcode == command_type::UPDATE {
+            return Ok((Command::Update, 1));
+        }
+        
+        if data.len() < HEADER_LEN {
+            return Err(NotifError::InvalidCommand(format!(
+                "Truncated header for opcode 0x{:02X}: {} bytes", opcode, data.len()
+            )));
+        }
+        
+        let payload_len = u16::from_le_bytes([data[1], data[2]]) as usize;
+        let total_len = HEADER_LEN + payload_len;
+        if data.len() < total_len {
+            return Err(NotifError::InvalidCommand(format!(
+                "Truncated payload for opcode 0x{:02X}: expected {} bytes, got {}",
+                opcode, payload_len, data.len() - HEADER_LEN
+            )));
+        }
+        let payload = &data[HEADER_LEN..total_len];
+        
+        // 拡張コマンドセットは元のオペコードとして扱い、座標を2バイトで読む
+        let (opcode, extended) = match command_type::base_of_extended(opcode) {
+            Some(base) => (base, true),
+            None => (opcode, false),
+        };
+        
+        let command = match opcode {
+            command_type::CLEAR => {
+                expect_payload_len(opcode, payload, 3)?;
+                Command::Clear {
+                    color: RGB::new(payload[0], payload[1], payload[2]),
+                }
+            }
+            
+            command_type::TEXT => {
+                let ([x, y], rest) = decode_coords(opcode, payload, extended)?;
+                if rest.len() < 5 {
+                    return Err(NotifError::InvalidCommand(format!(
+                        "Text payload too short: {} bytes", payload.len()
+                    )));
+                }
+                let text_len = rest[4] as usize;
+                expect_payload_len(opcode, rest, 5 + text_len)?;
+                let text = String::from_utf8(rest[5..].to_vec())?;
+                Command::Text {
+                    x,
+                    y,
+                    size: Size::from_byte(rest[0])?,
+                    color: RGB::new(rest[1], rest[2], rest[3]),
+                    text,
+                }
+            }
+            
+            command_type::EMOJI => {
+                let ([x, y], rest) = decode_coords(opcode, payload, extended)?;
+                expect_payload_len(opcode, rest, 5)?;
+                Command::Emoji {
+                    x,
+                    y,
+                    size: rest[0],
+                    code: u32::from_le_bytes([rest[1], rest[2], rest[3], rest[4]]),
+                }
+            }
+            
+            command_type::RECT => {
+                let ([x, y, width, height], rest) = decode_coords(opcode, payload, extended)?;
+                expect_payload_len(opcode, rest, 4)?;
+                Command::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill: rest[0] != 0,
+                    color: RGB::new(rest[1], rest[2], rest[3]),
+                }
+            }
+            
+            command_type::CIRCLE => {
+                let ([x, y, radius], rest) = decode_coords(opcode, payload, extended)?;
+                expect_payload_len(opcode, rest, 4)?;
+                Command::Circle {
+                    x,
+                    y,
+                    radius,
+                    color: RGB::new(rest[0], rest[1], rest[2]),
+                    filled: rest[3] != 0,
+                }
+            }
+            
+            command_type::LINE => {
+                let ([x1, y1, x2, y2], rest) = decode_coords(opcode, payload, extended)?;
+                expect_payload_len(opcode, rest, 4)?;
+                Command::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    width: rest[0],
+                    color: RGB::new(rest[1], rest[2], rest[3]),
+                }
+            }
+            
+            command_type::IMAGE => {
+                let ([x, y, width, height], rest) = decode_coords(opcode, payload, extended)?;
+                let (&format, image_data) = rest.split_first().ok_or_else(|| NotifError::InvalidCommand(format!(
+                    "Image payload too short: {} bytes", payload.len()
+                )))?;
+                Command::Image {
+                    x,
+                    y,
+                    width,
+                    height,
+                    format,
+                    data: image_data.to_vec(),
+                }
+            }
+            
+            command_type::BATCH => {
+                let count = *payload.first()
+                    .ok_or_else(|| NotifError::InvalidCommand("Batch payload is empty".to_string()))?;
+                let mut offset = 1;
+                let mut commands = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let (cmd, used) = Command::decode(&payload[offset..])?;
+                    commands.push(cmd);
+                    offset += used;
+                }
+                expect_payload_len(opcode, payload, offset)?;
+                Command::Batch { commands }
+            }
+            
+            command_type::REGION => {
+                let count = *payload.first()
+                    .ok_or_else(|| NotifError::InvalidCommand("Region payload is empty".to_string()))?;
+                let mut offset = 1;
+                let mut regions = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let ([x, y, width, height], rest) = decode_coords(opcode, &payload[offset..], extended)?;
+                    let header = rest.get(..2)
+                        .ok_or_else(|| NotifError::InvalidCommand("Truncated region header".to_string()))?;
+                    let content_len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                    offset = payload.len() - rest.len() + 2;
+                    
+                    let content_data = payload.get(offset..offset + content_len)
+                        .ok_or_else(|| NotifError::InvalidCommand("Truncated region content".to_string()))?;
+                    let (content, used) = Command::decode(content_data)?;
+                    if used != content_len {
+                        return Err(NotifError::InvalidCommand(format!(
+                            "Region content length mismatch: declared {}, decoded {}", content_len, used
+                        )));
+                    }
+                    offset += content_len;
+                    
+                    regions.push(Region {
+                        x: x as i32,
+                        y: y as i32,
+                        width: width as u32,
+                        height: height as u32,
+                        content: Box::new(content),
+                    });
+                }
+                expect_payload_len(opcode, payload, offset)?;
+                Command::Region { regions }
+            }
+            
+            command_type::SEQUENCED => {
+                if payload.len() < 2 {
+                    return Err(NotifError::InvalidCommand(format!(
+                        "Sequenced payload too short: {} bytes", payload.len()
+                    )));
+                }
+                let seq = u16::from_le_bytes([payload[0], payload[1]]);
+                let (command, used) = Command::decode(&payload[2..])?;
+                expect_payload_len(opcode, payload, 2 + used)?;
+                Command::Sequenced { seq, command: Box::new(command) }
+            }
+            
+            command_type::DEFINE_ASSET => {
+                expect_payload_len(opcode, payload, 7)?;
+                Command::DefineAsset {
+                    id: u16::from_le_bytes([payload[0], payload[1]]),
+                    width: payload[2],
+                    height: payload[3],
+                    format: payload[4],
+                    length: u16::from_le_bytes([payload[5], payload[6]]),
+                }
+            }
+            
+            command_type::ASSET_DATA => {
+                if payload.len() < 4 {
+                    return Err(NotifError::InvalidCommand(format!(
+                        "Asset data payload too short: {} bytes", payload.len()
+                    )));
+                }
+                Command::AssetData {
+                    id: u16::from_le_bytes([payload[0], payload[1]]),
+                    offset: u16::from_le_bytes([payload[2], payload[3]]),
+                    data: payload[4..].to_vec(),
+                }
+            }
+            
+            command_type::DRAW_ASSET => {
+                if payload.len() < 2 {
+                    return Err(NotifError::InvalidCommand(format!(
+                        "Draw asset payload too short: {} bytes", payload.len()
+                    )));
+                }
+                let ([x, y], rest) = decode_coords(opcode, &payload[2..], extended)?;
+                expect_payload_len(opcode, rest, 0)?;
+                Command::DrawAsset {
+                    id: u16::from_le_bytes([payload[0], payload[1]]),
+                    x,
+                    y,
+                }
+            }
+            
+            _ => {
+                return Err(NotifError::InvalidCommand(format!("Unknown opcode: 0x{:02X}", opcode)));
+            }
+        };
+        
+        Ok((command, total_len))
+    }
+    
+    /// 連続したバイト列（キャプチャしたトラフィック等）を全てデコード
+    pub fn decode_all(mut data: &[u8]) -> Result<Vec<Command>> {
+        let mut commands = Vec::new();
+        while !data.is_empty() {
+            let (command, used) = Command::decode(data)?;
+            commands.push(command);
+            data = &data[used..];
+        }
+        Ok(commands)
+    }
+    
+    /// 複数コマンドを1つのコマンドにまとめる
+    /// 
+    /// Batchの件数は1バイトのため、255件を超える場合はBatchを入れ子にする
+    pub fn batched(mut commands: Vec<Command>) -> Command {
+        if commands.len() == 1 {
+            return commands.remove(0);
+        }
+        if commands.len() <= MAX_BATCH_COMMANDS {
+            return Command::Batch { commands };
+        }
+        
+        let chunks = commands
+            .chunks(MAX_BATCH_COMMANDS)
+            .map(|chunk| Command::batched(chunk.to_vec()))
+            .collect();
+        Command::batched(chunks)
+    }
+    
+    /// 円を水平線の集合にラスタライズ（円非対応デバイス向け）
+    /// 
+    /// 座標が負になる部分と`limit`を超える部分は切り捨てる
+    pub fn rasterize_circle(x: u16, y: u16, radius: u16, color: RGB, filled: bool, limit: u16) -> Vec<Command> {
+        let (cx, cy, r) = (x as i32, y as i32, radius as i32);
+        let half_width = |r: i32, dy: i32| ((r * r - dy * dy) as f64).sqrt() as i32;
+        
+        let mut spans = Vec::new();
+        for dy in -r..=r {
+            let outer = half_width(r, dy);
+            if filled || dy.abs() >= r {
+                spans.push((cy + dy, cx - outer, cx + outer));
+            } else {
+                // 内側の円（半径r-1）に含まれない部分だけを描く
+                let inner = (half_width(r - 1, dy) + 1).min(outer);
+                spans.push((cy + dy, cx - outer, cx - inner));
+                spans.push((cy + dy, cx + inner, cx + outer));
+            }
+        }
+        
+        let max = limit as i32;
+        spans.into_iter()
+            .filter(|&(row, x1, x2)| (0..=max).contains(&row) && x2 >= 0 && x1 <= max)
+            .map(|(row, x1, x2)| Command::Line {
+                x1: x1.clamp(0, max) as u16,
+                y1: row as u16,
+                x2: x2.clamp(0, max) as u16,
+                y2: row as u16,
+                width: 1,
+                color,
+            })
+            .collect()
+    }
+}
+
+/// コマンドヘッダー長: opcode(1) + ペイロード長(2, リトルエンディアン)
+const HEADER_LEN: usize = 3;
+
+/// Textコマンド1つに入るテキストの最大バイト数（長さフィールドが1バイト）
+pub const MAX_TEXT_BYTES: usize = u8::MAX as usize;
+
+/// アセット転送時の1断片の最大バイト数（BLE制限内に収める）
+pub const ASSET_CHUNK_SIZE: usize = 480;
+
+/// Batchコマンド1つに入る最大コマンド数（件数フィールドが1バイト）
+pub const MAX_BATCH_COMMANDS: usize = u8::MAX as usize;
+
+/// ヘッダーを付けて1フレームを組み立てる（ペイロード長はu16に収まる必要がある）
+fn frame(opcode: u8, payload: &[u8]) -> Result<Vec<u8>> {
+    let payload_len = u16::try_from(payload.len()).map_err(|_| NotifError::InvalidCommand(format!(
+        "Payload too large for opcode 0x{:02X}: {} bytes", opcode, payload.len()
+    )))?;
+    
+    let mut data = Vec::with_capacity(HEADER_LEN + payload.len());
+    data.push(opcode);
+    data.extend_from_slice(&payload_len.to_le_bytes()); // ペイロード長(リトルエンディアン)
+    data.extend_from_slice(payload);
+    Ok(data)
+}
+
+/// 座標・サイズ列をエンコード
+/// 
+/// 全て255以下なら1バイトずつ（従来形式）、それ以外は2バイトLEで並べる。
+/// 戻り値の1つ目は拡張コマンドセットが必要か
+fn encode_coords(values: &[u16]) -> (bool, Vec<u8>) {
+    if values.iter().all(|&value| value <= u8::MAX as u16) {
+        (false, values.iter().map(|&value| value as u8).collect())
+    } else {
+        (true, values.iter().flat_map(|value| value.to_le_bytes()).collect())
     }
 }
 
+/// 先頭の座標・サイズ列をデコードし、残りのペイロードを返す
+fn decode_coords<const N: usize>(opcode: u8, payload: &[u8], extended: bool) -> Result<([u16; N], &[u8])> {
+    let width = if extended { 2 } else { 1 };
+    if payload.len() < N * width {
+        return Err(NotifError::InvalidCommand(format!(
+            "Payload too short for opcode 0x{:02X}: {} bytes", opcode, payload.len()
+        )));
+    }
+    
+    let mut values = [0u16; N];
+    for (index, value) in values.iter_mut().enumerate() {
+        *value = if extended {
+            u16::from_le_bytes([payload[index * 2], payload[index * 2 + 1]])
+        } else {
+            payload[index] as u16
+        };
+    }
+    Ok((values, &payload[N * width..]))
+}
+
+/// テキストを文字境界で`max_bytes`以下の断片に分割（可能なら空白・改行で区切る）
+fn split_text(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        
+        // 単語の途中で切らないよう、直前の空白で区切る
+        if let Some(space) = rest[..end].rfind(char::is_whitespace) {
+            if space > 0 {
+                end = space;
+            }
+        }
+        
+        chunks.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// ペイロード長が期待値と一致するか確認
+fn expect_payload_len(opcode: u8, payload: &[u8], expected: usize) -> Result<()> {
+    if payload.len() != expected {
+        return Err(NotifError::InvalidCommand(format!(
+            "Invalid payload length for opcode 0x{:02X}: expected {}, got {}",
+            opcode, expected, payload.len()
+        )));
+    }
+    Ok(())
+}
+
 /// ステータスコード
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StatusCode {
@@ -279,6 +902,314 @@ impl StatusCode {
             _ => StatusCode::Error,
         }
     }
+    
+    /// バイト値に変換
+    pub fn to_byte(self) -> u8 {
+        self as u8
+    }
+    
+    /// デバイスの応答をResultに変換
+    pub fn into_result(self, device: &str) -> Result<()> {
+        match self {
+            StatusCode::Success => Ok(()),
+            StatusCode::Busy => Err(NotifError::DeviceBusy(device.to_string())),
+            StatusCode::InvalidCommand => Err(NotifError::InvalidCommand(
+                format!("Command rejected by device {}", device)
+            )),
+            StatusCode::OutOfMemory => Err(NotifError::DeviceError(
+                format!("Out of memory on device {}", device)
+            )),
+            StatusCode::Error => Err(NotifError::DeviceError(
+                format!("Command failed on device {}", device)
+            )),
+        }
+    }
+}
+
+/// ステータス通知（STATUS_CHAR）
+/// 
+/// 1バイト目がステータスコード。シーケンス番号付きフレームへの応答では
+/// 続く2バイトに対象のシーケンス番号（リトルエンディアン）が入る。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusNotification {
+    pub status: StatusCode,
+    pub seq: Option<u16>,
+}
+
+impl StatusNotification {
+    /// 通知データをパース
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        match data {
+            [] => Err(NotifError::InvalidCommand("Empty status notification".to_string())),
+            [status] => Ok(StatusNotification {
+                status: StatusCode::from_byte(*status),
+                seq: None,
+            }),
+            [status, lo, hi, ..] => Ok(StatusNotification {
+                status: StatusCode::from_byte(*status),
+                seq: Some(u16::from_le_bytes([*lo, *hi])),
+            }),
+            _ => Err(NotifError::InvalidCommand(format!(
+                "Malformed status notification: {:02X?}", data
+            ))),
+        }
+    }
+}
+
+/// テキスト・領域座標の1グリッドあたりのピクセル数（128px = 32グリッド）
+pub const GRID_PIXELS: u32 = 4;
+
+/// このサーバーが送信する拡張コマンドセット（u16座標）の版
+pub const EXTENDED_COMMAND_VERSION: u8 = 1;
+
+/// 機能ディスクリプタ（接続直後にCONFIG_CHARから読み出す）
+/// 
+/// 形式: [ディスクリプタ版(1), FWバージョン(3: major, minor, patch), 幅(2), 高さ(2), 色深度(1), 機能フラグ(2), 拡張コマンド版(1)]
+/// 数値はリトルエンディアン。新しい版で末尾にフィールドが増えても既知の部分だけを読む
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapabilityDescriptor {
+    pub firmware_version: (u8, u8, u8),
+    pub display_width: u16,
+    pub display_height: u16,
+    pub color_depth: u8,
+    pub features: u16,
+    /// 対応する拡張コマンドセットの版（0は非対応）
+    pub extended_commands: u8,
+}
+
+impl CapabilityDescriptor {
+    /// このサーバーが理解するディスクリプタ版
+    pub const VERSION: u8 = 1;
+    
+    /// 版1のディスクリプタ長
+    const LEN: usize = 12;
+    
+    /// 読み出した値をパース
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::LEN || data[0] == 0 {
+            return Err(NotifError::InvalidCommand(format!(
+                "Malformed capability descriptor: {:02X?}", data
+            )));
+        }
+        
+        Ok(CapabilityDescriptor {
+            firmware_version: (data[1], data[2], data[3]),
+            display_width: u16::from_le_bytes([data[4], data[5]]),
+            display_height: u16::from_le_bytes([data[6], data[7]]),
+            color_depth: data[8],
+            features: u16::from_le_bytes([data[9], data[10]]),
+            extended_commands: data[11],
+        })
+    }
+    
+    /// バイト列にエンコード
+    pub fn encode(&self) -> Vec<u8> {
+        let (major, minor, patch) = self.firmware_version;
+        let mut data = vec![Self::VERSION, major, minor, patch];
+        data.extend_from_slice(&self.display_width.to_le_bytes());
+        data.extend_from_slice(&self.display_height.to_le_bytes());
+        data.push(self.color_depth);
+        data.extend_from_slice(&self.features.to_le_bytes());
+        data.push(self.extended_commands);
+        data
+    }
+    
+    /// ファームウェアバージョン文字列（例: "1.2.0"）
+    pub fn firmware_version_string(&self) -> String {
+        let (major, minor, patch) = self.firmware_version;
+        format!("{}.{}.{}", major, minor, patch)
+    }
+    
+    /// 機能フラグが立っているか
+    pub fn has(&self, flag: u16) -> bool {
+        self.features & flag != 0
+    }
+}
+
+/// ボタン押下の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ButtonPress {
+    Short = 0x01,
+    Long = 0x02,
+    Double = 0x03,
+}
+
+impl ButtonPress {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x01 => Some(ButtonPress::Short),
+            0x02 => Some(ButtonPress::Long),
+            0x03 => Some(ButtonPress::Double),
+            _ => None,
+        }
+    }
+}
+
+/// ボタンイベント通知（STATUS_CHAR）
+/// 
+/// 形式: [BUTTON_EVENT(1), ボタン番号(1), 押下種別(1)]。
+/// 先頭バイトがステータスコードと重ならないため、応答通知と同じキャラクタリスティックで送られる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ButtonEvent {
+    pub button: u8,
+    pub press: ButtonPress,
+}
+
+impl ButtonEvent {
+    /// ボタンイベント通知かどうか
+    pub fn is_event(data: &[u8]) -> bool {
+        data.first() == Some(&notification_type::BUTTON_EVENT)
+    }
+    
+    /// 通知データをパース（ボタンイベントでなければNone）
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        match data {
+            [notification_type::BUTTON_EVENT, button, press] => Some(ButtonEvent {
+                button: *button,
+                press: ButtonPress::from_byte(*press)?,
+            }),
+            _ => None,
+        }
+    }
+    
+    /// 通知データにエンコード
+    pub fn encode(&self) -> Vec<u8> {
+        vec![notification_type::BUTTON_EVENT, self.button, self.press as u8]
+    }
+}
+
+/// デバイス名の最大バイト数（アドバタイズパケットに収まる長さ）
+pub const MAX_DEVICE_NAME_BYTES: usize = 20;
+
+/// デバイス設定（CONFIG_CHARへ書き込む）
+/// 
+/// 形式: [設定キー(1), 値]。デバイス名の変更はデバイスの再起動後に反映される
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceSetting {
+    /// バックライト輝度（0-255）
+    Brightness(u8),
+    /// ディスプレイのスリープ（true）/ 復帰（false）
+    Sleep(bool),
+    /// 画面の向き（0-3、90度単位）
+    Rotation(u8),
+    /// BLEデバイス名
+    DeviceName(String),
+}
+
+impl DeviceSetting {
+    /// 角度（0/90/180/270）から画面の向きを作成
+    pub fn rotation_degrees(degrees: u16) -> Result<Self> {
+        match degrees {
+            0 | 90 | 180 | 270 => Ok(DeviceSetting::Rotation((degrees / 90) as u8)),
+            _ => Err(NotifError::InvalidParameter(format!(
+                "Invalid rotation: {} (0, 90, 180, 270)", degrees
+            ))),
+        }
+    }
+    
+    /// 設定キー
+    pub fn key(&self) -> u8 {
+        match self {
+            DeviceSetting::Brightness(_) => config_key::BRIGHTNESS,
+            DeviceSetting::Sleep(_) => config_key::SLEEP,
+            DeviceSetting::Rotation(_) => config_key::ROTATION,
+            DeviceSetting::DeviceName(_) => config_key::DEVICE_NAME,
+        }
+    }
+    
+    /// CONFIG_CHAR書き込み用のバイト列にエンコード
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut data = vec![self.key()];
+        match self {
+            DeviceSetting::Brightness(level) => data.push(*level),
+            DeviceSetting::Sleep(sleep) => data.push(*sleep as u8),
+            DeviceSetting::Rotation(rotation) => {
+                if *rotation > 3 {
+                    return Err(NotifError::InvalidParameter(format!(
+                        "Invalid rotation: {} (0-3)", rotation
+                    )));
+                }
+                data.push(*rotation);
+            }
+            DeviceSetting::DeviceName(name) => {
+                if name.is_empty() || name.len() > MAX_DEVICE_NAME_BYTES {
+                    return Err(NotifError::InvalidParameter(format!(
+                        "Device name must be 1-{} bytes: {:?}", MAX_DEVICE_NAME_BYTES, name
+                    )));
+                }
+                data.extend_from_slice(name.as_bytes());
+            }
+        }
+        Ok(data)
+    }
+    
+    /// バイト列からデコード
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let invalid = || NotifError::InvalidCommand(format!("Malformed device setting: {:02X?}", data));
+        
+        let (&key, value) = data.split_first().ok_or_else(invalid)?;
+        let setting = match (key, value) {
+            (config_key::BRIGHTNESS, [level]) => DeviceSetting::Brightness(*level),
+            (config_key::SLEEP, [sleep @ (0 | 1)]) => DeviceSetting::Sleep(*sleep == 1),
+            (config_key::ROTATION, [rotation @ 0..=3]) => DeviceSetting::Rotation(*rotation),
+            (config_key::DEVICE_NAME, name) if !name.is_empty() && name.len() <= MAX_DEVICE_NAME_BYTES => {
+                DeviceSetting::DeviceName(String::from_utf8(name.to_vec())?)
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(setting)
+    }
+}
+
+/// デバイス設定の変更要求（HTTP/MCPから受け取る形式、省略した項目は変更しない）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSettings {
+    /// バックライト輝度（0-255）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+    /// true: スリープ、false: 復帰
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sleep: Option<bool>,
+    /// 画面の向き（0/90/180/270度）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<u16>,
+    /// BLEデバイス名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl DeviceSettings {
+    /// 個別の設定に分解（書き込み順: 名前、向き、輝度、スリープ）
+    /// 
+    /// 復帰を伴う場合は輝度より先に復帰させる
+    pub fn into_settings(self) -> Result<Vec<DeviceSetting>> {
+        let mut settings = Vec::new();
+        if let Some(name) = self.name {
+            settings.push(DeviceSetting::DeviceName(name));
+        }
+        if let Some(degrees) = self.rotation {
+            settings.push(DeviceSetting::rotation_degrees(degrees)?);
+        }
+        if self.sleep == Some(false) {
+            settings.push(DeviceSetting::Sleep(false));
+        }
+        if let Some(level) = self.brightness {
+            settings.push(DeviceSetting::Brightness(level));
+        }
+        if self.sleep == Some(true) {
+            settings.push(DeviceSetting::Sleep(true));
+        }
+        
+        if settings.is_empty() {
+            return Err(NotifError::InvalidParameter("No settings specified".to_string()));
+        }
+        // 書き込み前に値を検証
+        for setting in &settings {
+            setting.encode()?;
+        }
+        Ok(settings)
+    }
 }
 
 /// Bluetooth UUID定義（v2互換）
@@ -294,6 +1225,12 @@ pub mod uuid {
     
     /// 設定用キャラクタリスティック（v2互換）
     pub const CONFIG_CHAR: &str = "12345678-1234-5678-1234-56789abcdef3";
+    
+    /// 標準のBattery Service（0x180F、搭載デバイスのみ）
+    pub const BATTERY_SERVICE: &str = "0000180f-0000-1000-8000-00805f9b34fb";
+    
+    /// 標準のBattery Levelキャラクタリスティック（0x2A19、0-100%の1バイト）
+    pub const BATTERY_LEVEL_CHAR: &str = "00002a19-0000-1000-8000-00805f9b34fb";
 }
 
 /// コマンドタイプ（バイト値） - ATOMS3ファームウェア互換
@@ -304,9 +1241,62 @@ pub mod command_type {
     pub const RECT: u8 = 0x04;   // ATOMS3: CMD_RECT = 0x04
     pub const LINE: u8 = 0x05;   // ATOMS3: CMD_LINE = 0x05
     pub const IMAGE: u8 = 0x06;  // ATOMS3: CMD_IMAGE = 0x06
+    pub const CIRCLE: u8 = 0x07;
     pub const UPDATE: u8 = 0x08;
     pub const BATCH: u8 = 0x10;  // ATOMS3: CMD_BATCH = 0x10
+    pub const DEFINE_ASSET: u8 = 0x11;
+    pub const ASSET_DATA: u8 = 0x12;
+    pub const DRAW_ASSET: u8 = 0x13;
     pub const REGION: u8 = 0x0A;
+    pub const SEQUENCED: u8 = 0x20;
+    
+    /// 拡張コマンドセット（座標・サイズをu16 LEで送る）: 元のオペコード | EXTENDED
+    pub const EXTENDED: u8 = 0x40;
+    
+    /// 座標を持つコマンドのオペコードを選ぶ
+    pub fn select(opcode: u8, extended: bool) -> u8 {
+        if extended { opcode | EXTENDED } else { opcode }
+    }
+    
+    /// 拡張コマンドセットのオペコードなら元のオペコードを返す
+    pub fn base_of_extended(opcode: u8) -> Option<u8> {
+        let base = opcode & !EXTENDED;
+        let has_coords = matches!(base, TEXT | EMOJI | RECT | LINE | IMAGE | CIRCLE | REGION | DRAW_ASSET);
+        (opcode & EXTENDED != 0 && has_coords).then_some(base)
+    }
+}
+
+/// 画像フォーマット（Command::Imageのformatバイト）
+pub mod image_format {
+    pub const RAW_RGB: u8 = 0x01;     // RGB888
+    pub const RAW_RGB565: u8 = 0x02;  // ATOMS3: IMG_RAW_RGB565 = 0x02
+    pub const RLE_RGB565: u8 = 0x03;  // [ラン長(1), RGB565(2)] の繰り返し
+    pub const INDEXED: u8 = 0x04;     // [ビット数(1), 色数-1(1), パレット, インデックス]
+}
+
+/// 機能フラグ（機能ディスクリプタのビット）
+pub mod capability_flag {
+    pub const COLOR: u16 = 0x0001;
+    pub const EMOJI: u16 = 0x0002;
+    pub const REGIONS: u16 = 0x0004;           // REGION
+    pub const LINES: u16 = 0x0008;             // LINE
+    pub const CIRCLES: u16 = 0x0010;           // CIRCLE
+    pub const RLE_IMAGES: u16 = 0x0020;        // IMAGE (RLE_RGB565)
+    pub const INDEXED_IMAGES: u16 = 0x0040;    // IMAGE (INDEXED)
+    pub const ASSETS: u16 = 0x0080;            // DEFINE_ASSET / ASSET_DATA / DRAW_ASSET
+}
+
+/// 通知タイプ（STATUS_CHAR通知の先頭バイト、ステータスコード以外）
+pub mod notification_type {
+    pub const BUTTON_EVENT: u8 = 0x80;  // [0x80, ボタン番号(1), 押下種別(1)]
+}
+
+/// 設定キー（CONFIG_CHARへの書き込みの先頭バイト）
+pub mod config_key {
+    pub const BRIGHTNESS: u8 = 0x01;   // [輝度(1)]
+    pub const SLEEP: u8 = 0x02;        // [0: 復帰, 1: スリープ]
+    pub const ROTATION: u8 = 0x03;     // [向き(1), 0-3]
+    pub const DEVICE_NAME: u8 = 0x04;  // [名前(UTF-8)]
 }
 
 #[cfg(test)]
@@ -326,7 +1316,7 @@ mod tests {
             data: img_data.clone(),
         };
         
-        let encoded = cmd.encode();
+        let encoded = cmd.encode().unwrap();
         
         // ヘッダー確認: コマンドタイプ
         assert_eq!(encoded[0], command_type::IMAGE); // 0x06
@@ -358,7 +1348,7 @@ mod tests {
             data: vec![],
         };
         
-        let encoded = cmd.encode();
+        let encoded = cmd.encode().unwrap();
         
         // ヘッダー部分のみ確認
         assert_eq!(encoded[0], command_type::IMAGE);
@@ -384,7 +1374,7 @@ mod tests {
             data: img_data.clone(),
         };
         
-        let encoded = cmd.encode();
+        let encoded = cmd.encode().unwrap();
         
         // ペイロード長確認
         let payload_len = ((encoded[2] as u16) << 8) | (encoded[1] as u16);
@@ -396,4 +1386,416 @@ mod tests {
         // データ部分が正しく配置されていることを確認
         assert_eq!(&encoded[8..], &img_data);
     }
-}
\ No newline at end of file
+
+    /// 全オペコードを網羅したサンプルコマンド
+    fn sample_commands() -> Vec<Command> {
+        vec![
+            Command::Clear { color: RGB::new(1, 2, 3) },
+            Command::Text {
+                x: 5,
+                y: 10,
+                size: Size::Large,
+                color: RGB::white(),
+                text: "接続済み hello".to_string(),
+            },
+            Command::Emoji { x: 0, y: 4, size: 2, code: 0x1F600 },
+            Command::Rect { x: 1, y: 2, width: 30, height: 20, fill: true, color: RGB::new(255, 0, 0) },
+            Command::Line { x1: 0, y1: 0, x2: 31, y2: 31, width: 2, color: RGB::new(0, 255, 0) },
+            Command::Circle { x: 16, y: 16, radius: 8, color: RGB::new(0, 0, 255), filled: false },
+            Command::Image { x: 16, y: 8, width: 16, height: 8, format: 2, data: vec![0xAB; 256] },
+            Command::Update,
+            Command::DefineAsset { id: 7, width: 32, height: 32, format: 3, length: 600 },
+            Command::AssetData { id: 7, offset: 480, data: vec![0x11; 120] },
+            Command::DrawAsset { id: 7, x: 48, y: 48 },
+        ]
+    }
+
+    #[test]
+    fn test_decode_round_trip_all_opcodes() {
+        for cmd in sample_commands() {
+            let encoded = cmd.encode().unwrap();
+            let (decoded, used) = Command::decode(&encoded).unwrap();
+            assert_eq!(decoded, cmd);
+            assert_eq!(used, encoded.len());
+        }
+    }
+
+    #[test]
+    fn test_decode_round_trip_nested() {
+        let batch = Command::Batch { commands: sample_commands() };
+        let region = Command::Region {
+            regions: vec![
+                Region { x: 0, y: 0, width: 16, height: 32, content: Box::new(batch.clone()) },
+                Region { x: 16, y: 0, width: 16, height: 32, content: Box::new(Command::Clear { color: RGB::black() }) },
+            ],
+        };
+        let outer = Command::Batch { commands: vec![region.clone(), batch.clone()] };
+        
+        for cmd in [batch, region, outer] {
+            let encoded = cmd.encode().unwrap();
+            let (decoded, used) = Command::decode(&encoded).unwrap();
+            assert_eq!(decoded, cmd);
+            assert_eq!(used, encoded.len());
+        }
+    }
+
+    #[test]
+    fn test_extended_coordinates() {
+        // 255を超える座標は拡張コマンドセットで送られる
+        let wide = vec![
+            Command::Text { x: 300, y: 10, size: Size::Small, color: RGB::white(), text: "wide".to_string() },
+            Command::Emoji { x: 256, y: 200, size: 1, code: 0x1F600 },
+            Command::Rect { x: 0, y: 0, width: 320, height: 240, fill: false, color: RGB::white() },
+            Command::Line { x1: 0, y1: 0, x2: 319, y2: 239, width: 1, color: RGB::white() },
+            Command::Circle { x: 160, y: 120, radius: 300, color: RGB::white(), filled: true },
+            Command::Image { x: 304, y: 232, width: 16, height: 8, format: 2, data: vec![0; 256] },
+            Command::DrawAsset { id: 1, x: 288, y: 0 },
+            Command::Region {
+                regions: vec![
+                    Region { x: 0, y: 0, width: 240, height: 320, content: Box::new(Command::Update) },
+                    Region { x: 240, y: 0, width: 240, height: 320, content: Box::new(Command::Update) },
+                ],
+            },
+        ];
+        
+        for cmd in wide {
+            let encoded = cmd.encode().unwrap();
+            assert_eq!(command_type::base_of_extended(encoded[0]).map(|base| base | command_type::EXTENDED), Some(encoded[0]));
+            let (decoded, used) = Command::decode(&encoded).unwrap();
+            assert_eq!(decoded, cmd);
+            assert_eq!(used, encoded.len());
+        }
+        
+        // 収まる場合は従来形式のまま
+        let narrow = Command::Rect { x: 0, y: 0, width: 255, height: 255, fill: false, color: RGB::white() };
+        assert_eq!(narrow.encode().unwrap()[0], command_type::RECT);
+        
+        // 座標を持たないコマンドに拡張ビットは付かない
+        assert_eq!(command_type::base_of_extended(command_type::CLEAR | command_type::EXTENDED), None);
+    }
+
+    #[test]
+    fn test_decode_all_stream() {
+        let commands = sample_commands();
+        let stream: Vec<u8> = commands.iter().flat_map(|c| c.encode().unwrap()).collect();
+        
+        assert_eq!(Command::decode_all(&stream).unwrap(), commands);
+    }
+
+    #[test]
+    fn test_sequenced_round_trip() {
+        let tile = Command::Image { x: 0, y: 8, width: 16, height: 8, format: 2, data: vec![0x12; 256] };
+        let cmd = Command::Sequenced { seq: 0x1234, command: Box::new(tile.clone()) };
+        let encoded = cmd.encode().unwrap();
+        
+        assert_eq!(encoded[0], command_type::SEQUENCED);
+        assert_eq!(&encoded[3..5], &[0x34, 0x12]);
+        assert_eq!(&encoded[5..], &tile.encode().unwrap()[..]);
+        
+        let (decoded, used) = Command::decode(&encoded).unwrap();
+        assert_eq!(decoded, cmd);
+        assert_eq!(used, encoded.len());
+    }
+
+    #[test]
+    fn test_status_notification_parse() {
+        let plain = StatusNotification::parse(&[0x00]).unwrap();
+        assert_eq!(plain, StatusNotification { status: StatusCode::Success, seq: None });
+        
+        let sequenced = StatusNotification::parse(&[0x04, 0x07, 0x01]).unwrap();
+        assert_eq!(sequenced, StatusNotification { status: StatusCode::Busy, seq: Some(0x0107) });
+        
+        assert!(StatusNotification::parse(&[]).is_err());
+        assert!(StatusNotification::parse(&[0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_status_code_into_result() {
+        assert!(StatusCode::from_byte(0x00).into_result("dev").is_ok());
+        assert!(matches!(
+            StatusCode::from_byte(0x04).into_result("dev"),
+            Err(NotifError::DeviceBusy(_))
+        ));
+        assert!(matches!(
+            StatusCode::from_byte(0x02).into_result("dev"),
+            Err(NotifError::InvalidCommand(_))
+        ));
+        assert!(matches!(
+            StatusCode::from_byte(0x03).into_result("dev"),
+            Err(NotifError::DeviceError(_))
+        ));
+        // 未知の値はErrorとして扱う
+        assert_eq!(StatusCode::from_byte(0xEE), StatusCode::Error);
+        assert_eq!(StatusCode::Busy.to_byte(), 0x04);
+    }
+
+    #[test]
+    fn test_device_setting_round_trip() {
+        let settings = vec![
+            DeviceSetting::Brightness(128),
+            DeviceSetting::Sleep(true),
+            DeviceSetting::Rotation(2),
+            DeviceSetting::DeviceName("notif_atoms3_9".to_string()),
+        ];
+        for setting in settings {
+            let encoded = setting.encode().unwrap();
+            assert_eq!(encoded[0], setting.key());
+            assert_eq!(DeviceSetting::decode(&encoded).unwrap(), setting);
+        }
+        
+        assert_eq!(DeviceSetting::Brightness(40).encode().unwrap(), vec![config_key::BRIGHTNESS, 40]);
+        assert!(DeviceSetting::Rotation(4).encode().is_err());
+        assert!(DeviceSetting::DeviceName(String::new()).encode().is_err());
+        assert!(DeviceSetting::DeviceName("x".repeat(MAX_DEVICE_NAME_BYTES + 1)).encode().is_err());
+        assert!(DeviceSetting::decode(&[config_key::SLEEP, 2]).is_err());
+        assert!(DeviceSetting::decode(&[0x7F, 0]).is_err());
+    }
+    
+    #[test]
+    fn test_capability_descriptor_parse() {
+        let descriptor = CapabilityDescriptor {
+            firmware_version: (1, 4, 2),
+            display_width: 240,
+            display_height: 135,
+            color_depth: 16,
+            features: capability_flag::COLOR | capability_flag::CIRCLES | capability_flag::ASSETS,
+            extended_commands: 0,
+        };
+        
+        let encoded = descriptor.encode();
+        assert_eq!(encoded.len(), 12);
+        assert_eq!(encoded[0], CapabilityDescriptor::VERSION);
+        assert_eq!(CapabilityDescriptor::parse(&encoded).unwrap(), descriptor);
+        assert_eq!(descriptor.firmware_version_string(), "1.4.2");
+        assert!(descriptor.has(capability_flag::CIRCLES));
+        assert!(!descriptor.has(capability_flag::EMOJI));
+        
+        // 新しい版で追加されたフィールドは無視
+        let mut extended = encoded.clone();
+        extended[0] = 2;
+        extended.extend_from_slice(&[0xAA, 0xBB]);
+        assert_eq!(CapabilityDescriptor::parse(&extended).unwrap(), descriptor);
+        
+        // 旧ファームウェア（設定キャラクタリスティックが空・短い）
+        assert!(CapabilityDescriptor::parse(&[]).is_err());
+        assert!(CapabilityDescriptor::parse(&encoded[..8]).is_err());
+    }
+    
+    #[test]
+    fn test_button_event_parse() {
+        let event = ButtonEvent::parse(&[0x80, 0x00, 0x02]).unwrap();
+        assert_eq!(event, ButtonEvent { button: 0, press: ButtonPress::Long });
+        assert_eq!(event.encode(), vec![0x80, 0x00, 0x02]);
+        assert!(ButtonEvent::is_event(&event.encode()));
+        
+        // ステータス通知はボタンイベントではない
+        assert!(!ButtonEvent::is_event(&[0x00, 0x01, 0x00]));
+        assert!(ButtonEvent::parse(&[0x00]).is_none());
+        assert!(ButtonEvent::parse(&[0x80, 0x00, 0x09]).is_none());
+        assert!(ButtonEvent::parse(&[0x80, 0x00]).is_none());
+    }
+    
+    #[test]
+    fn test_device_settings_into_settings() {
+        let settings = DeviceSettings {
+            brightness: Some(10),
+            sleep: Some(false),
+            rotation: Some(270),
+            name: None,
+        };
+        assert_eq!(settings.into_settings().unwrap(), vec![
+            DeviceSetting::Rotation(3),
+            DeviceSetting::Sleep(false),
+            DeviceSetting::Brightness(10),
+        ]);
+        
+        // スリープは輝度変更の後
+        let night = DeviceSettings { brightness: Some(0), sleep: Some(true), ..Default::default() };
+        assert_eq!(night.into_settings().unwrap(), vec![
+            DeviceSetting::Brightness(0),
+            DeviceSetting::Sleep(true),
+        ]);
+        
+        assert!(DeviceSettings::default().into_settings().is_err());
+        assert!(DeviceSettings { rotation: Some(45), ..Default::default() }.into_settings().is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_data() {
+        // 空データ
+        assert!(Command::decode(&[]).is_err());
+        // 未知のオペコード
+        assert!(Command::decode(&[0x7F, 0, 0]).is_err());
+        // ヘッダー不足
+        assert!(Command::decode(&[command_type::CLEAR, 3]).is_err());
+        // ペイロード不足
+        assert!(Command::decode(&[command_type::CLEAR, 3, 0, 0xFF]).is_err());
+        // ペイロード長の不一致
+        assert!(Command::decode(&[command_type::CLEAR, 2, 0, 0xFF, 0xFF]).is_err());
+        // 不正なフォントサイズ
+        let mut text = Command::Text {
+            x: 0, y: 0, size: Size::Small, color: RGB::black(), text: "a".to_string(),
+        }.encode().unwrap();
+        text[5] = 9;
+        assert!(Command::decode(&text).is_err());
+        // Lineのペイロード長不一致（旧Circle形式）
+        assert!(Command::decode(&[command_type::LINE, 7, 0, 1, 2, 3, 4, 5, 6, 7]).is_err());
+        // Batch内の途中で切れたコマンド
+        let mut batch = Command::Batch { commands: vec![Command::Clear { color: RGB::black() }] }.encode().unwrap();
+        batch.pop();
+        batch[1] -= 1;
+        assert!(Command::decode(&batch).is_err());
+    }
+
+    #[test]
+    fn test_circle_has_dedicated_opcode() {
+        let circle = Command::Circle { x: 16, y: 16, radius: 8, color: RGB::white(), filled: true }.encode().unwrap();
+        assert_eq!(circle[0], command_type::CIRCLE);
+        assert_ne!(command_type::CIRCLE, command_type::LINE);
+    }
+
+    #[test]
+    fn test_rasterize_circle() {
+        let color = RGB::new(0, 0, 255);
+        
+        // 塗りつぶし: 1行につき1本の水平線
+        let filled = Command::rasterize_circle(10, 10, 2, color, true, u16::MAX);
+        assert_eq!(filled.len(), 5);
+        assert_eq!(filled[2], Command::Line { x1: 8, y1: 10, x2: 12, y2: 10, width: 1, color });
+        
+        // 輪郭: 上下端以外は左右2本
+        let outline = Command::rasterize_circle(10, 10, 2, color, false, u16::MAX);
+        assert_eq!(outline.len(), 2 + 3 * 2);
+        assert!(outline.iter().all(|cmd| matches!(cmd, Command::Line { y1, y2, .. } if y1 == y2)));
+        
+        // 画面外にはみ出す部分は切り捨て
+        let clipped = Command::rasterize_circle(0, 0, 3, color, true, u16::MAX);
+        assert_eq!(clipped.len(), 4);
+        assert!(clipped.iter().all(|cmd| matches!(cmd, Command::Line { x1: 0, .. })));
+        
+        // 上限を超える部分も切り捨て
+        let limited = Command::rasterize_circle(250, 253, 10, color, true, 255);
+        assert_eq!(limited.len(), 13);
+        assert!(limited.iter().all(|cmd| matches!(cmd, Command::Line { x2: ..=255, y1: ..=255, .. })));
+        assert_eq!(limited[10], Command::Line { x1: 240, y1: 253, x2: 255, y2: 253, width: 1, color });
+    }
+
+    #[test]
+    fn test_batched_splits_large_batches() {
+        let single = Command::batched(vec![Command::Update]);
+        assert_eq!(single, Command::Update);
+        
+        let large = Command::batched(vec![Command::Update; 600]);
+        match large {
+            Command::Batch { commands } => {
+                assert_eq!(commands.len(), 3);
+                assert!(commands.iter().all(|cmd| matches!(cmd, Command::Batch { commands } if commands.len() <= 255)));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encode_rejects_overflow() {
+        let long_text = Command::Text {
+            x: 0, y: 0, size: Size::Small, color: RGB::white(), text: "a".repeat(256),
+        };
+        assert!(matches!(long_text.encode(), Err(NotifError::InvalidCommand(_))));
+        
+        let big_batch = Command::Batch { commands: vec![Command::Update; 256] };
+        assert!(big_batch.encode().is_err());
+        
+        let region = Command::Region {
+            regions: vec![Region { x: -1, y: 0, width: 10, height: 10, content: Box::new(Command::Update) }],
+        };
+        assert!(region.encode().is_err());
+        
+        let huge_image = Command::Image { x: 0, y: 0, width: 255, height: 255, format: 2, data: vec![0; 70_000] };
+        assert!(huge_image.encode().is_err());
+    }
+
+    #[test]
+    fn test_split_long_text() {
+        let text = "word ".repeat(120); // 600バイト
+        let cmd = Command::Text { x: 2, y: 10, size: Size::Medium, color: RGB::white(), text: text.clone() };
+        let frames = cmd.split_frames().unwrap();
+        
+        assert_eq!(frames.len(), 3);
+        for (index, frame) in frames.iter().enumerate() {
+            match frame {
+                Command::Text { x, y, text, .. } => {
+                    assert_eq!(*x, 2);
+                    assert_eq!(*y, 10 + Size::Medium.line_grids() as u16 * index as u16);
+                    assert!(text.len() <= MAX_TEXT_BYTES);
+                    assert!(!text.starts_with(' '));
+                }
+                other => panic!("unexpected command: {:?}", other),
+            }
+            assert!(frame.encode().is_ok());
+        }
+        
+        // 文字境界を壊さない（マルチバイト文字）
+        let japanese = Command::Text { x: 0, y: 0, size: Size::Small, color: RGB::white(), text: "あ".repeat(100) };
+        let frames = japanese.encode_frames().unwrap();
+        assert_eq!(frames.len(), 2);
+    }
+
+    #[test]
+    fn test_split_text_uses_grid_line_step() {
+        // 行の送りはv1テキストレイアウトと同じグリッド数（Mediumは6グリッド）
+        let medium = Command::Text { x: 0, y: 4, size: Size::Medium, color: RGB::white(), text: "a".repeat(300) };
+        let ys: Vec<u16> = medium.split_frames().unwrap().iter()
+            .map(|frame| match frame {
+                Command::Text { y, .. } => *y,
+                other => panic!("unexpected command: {:?}", other),
+            })
+            .collect();
+        assert_eq!(ys, [4, 10]);
+    }
+
+    #[test]
+    fn test_split_large_batch() {
+        let batch = Command::Batch { commands: vec![Command::Clear { color: RGB::black() }; 600] };
+        let frames = batch.split_frames().unwrap();
+        
+        assert_eq!(frames.len(), 3);
+        let total: usize = frames.iter()
+            .map(|frame| match frame {
+                Command::Batch { commands } => commands.len(),
+                other => panic!("unexpected command: {:?}", other),
+            })
+            .sum();
+        assert_eq!(total, 600);
+        
+        // ペイロード長の上限でも区切る
+        let tile = Command::Image { x: 0, y: 0, width: 16, height: 8, format: 2, data: vec![0; 30_000] };
+        let frames = Command::Batch { commands: vec![tile; 5] }.encode_frames().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|frame| frame.len() <= HEADER_LEN + u16::MAX as usize));
+    }
+
+    #[test]
+    fn test_asset_upload_commands() {
+        let asset = Asset { width: 32, height: 32, format: 2, data: vec![0x5A; 1000] };
+        let commands = asset.upload_commands(3).unwrap();
+        
+        assert_eq!(commands.len(), 1 + 3);
+        assert_eq!(commands[0], Command::DefineAsset { id: 3, width: 32, height: 32, format: 2, length: 1000 });
+        
+        let mut reassembled = Vec::new();
+        for command in &commands[1..] {
+            match command {
+                Command::AssetData { id: 3, offset, data } => {
+                    assert_eq!(*offset as usize, reassembled.len());
+                    assert!(data.len() <= ASSET_CHUNK_SIZE);
+                    reassembled.extend_from_slice(data);
+                }
+                other => panic!("unexpected command: {:?}", other),
+            }
+        }
+        assert_eq!(reassembled, asset.data);
+        
+        let too_large = Asset { width: 255, height: 255, format: 2, data: vec![0; 70_000] };
+        assert!(too_large.upload_commands(1).is_err());
+    }
+}
@@ -174,8 +174,8 @@ pub fn process_line_with_emoji(
             TextSegment::Text(txt) => {
                 if !txt.is_empty() {
                     commands.push(Command::Text {
-                        x: (current_x as i32).clamp(0, 255) as u8,
-                        y: (y as i32).clamp(0, 255) as u8,
+                        x: (current_x as i32).clamp(0, u16::MAX as i32) as u16,
+                        y: (y as i32).clamp(0, u16::MAX as i32) as u16,
                         size,
                         color,
                         text: txt.clone(),
@@ -195,8 +195,8 @@ pub fn process_line_with_emoji(
             TextSegment::Emoji(code) => {
                 // v3のEmoji構造に合わせて変換（u32コードポイント直接使用）
                 commands.push(Command::Emoji {
-                    x: (current_x as i32).clamp(0, 255) as u8,
-                    y: (y as i32).clamp(0, 255) as u8,
+                    x: (current_x as i32).clamp(0, u16::MAX as i32) as u16,
+                    y: (y as i32).clamp(0, u16::MAX as i32) as u16,
                     size: size.to_byte(),
                     code,
                 });
@@ -1,20 +1,30 @@
 //! Linux固有のBluetooth実装
 
 use async_trait::async_trait;
-use btleplug::api::{Central, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType};
+use btleplug::api::{Central, CharPropFlags, Characteristic, Manager as _, Peripheral as _, ScanFilter, WriteType};
 use btleplug::platform::{Adapter, Manager, Peripheral};
+use futures::StreamExt;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use notif_common_v5::{
-    Connection, DeviceCapabilities, DeviceInfo, NotifError, Result, Scanner,
-    Command, protocol::uuid as protocol_uuid,
+    AckPolicy, Connection, DeviceCapabilities, DeviceInfo, NotifError, Result, Scanner, with_frame_timeout,
+    ButtonEvent, Command, DeviceSetting, StatusCode,
+    protocol::{command_type, uuid as protocol_uuid, CapabilityDescriptor, StatusNotification},
 };
 
+/// シーケンス送信でAckPolicy未設定時に使う応答待ちタイムアウト
+const DEFAULT_SEQUENCE_ACK_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// ボタンイベントのバッファ数
+const BUTTON_EVENT_CAPACITY: usize = 16;
+
 /// Linux固有データ
 #[derive(Clone)]
 pub struct LinuxPlatformData {
@@ -35,6 +45,24 @@ pub struct LinuxConnection {
     device_info: DeviceInfo,
     command_char: Characteristic,
     status_char: Option<Characteristic>,
+    /// 設定用キャラクタリスティック（非対応ファームウェアではNone）
+    config_char: Option<Characteristic>,
+    /// STATUS_CHAR通知の受信チャネル
+    status_rx: Option<mpsc::UnboundedReceiver<Vec<u8>>>,
+    /// STATUS_CHAR通知の転送タスク（再購読時に止める）
+    status_task: Option<JoinHandle<()>>,
+    /// ボタンイベントの送信側（再接続しても購読者はそのまま）
+    events_tx: broadcast::Sender<ButtonEvent>,
+    /// Battery Levelキャラクタリスティック（Battery Service非搭載ではNone）
+    battery_char: Option<Characteristic>,
+    /// 最新のバッテリーレベル（通知で更新し、再接続しても購読者はそのまま）
+    battery_tx: Arc<watch::Sender<Option<u8>>>,
+    /// Battery Level通知の転送タスク（再購読時に止める）
+    battery_task: Option<JoinHandle<()>>,
+    /// 応答確認ポリシー
+    ack_policy: Option<AckPolicy>,
+    /// 1フレームの送信タイムアウト
+    command_timeout: Option<Duration>,
 }
 
 impl Debug for LinuxConnection {
@@ -45,6 +73,13 @@ impl Debug for LinuxConnection {
     }
 }
 
+impl Drop for LinuxConnection {
+    fn drop(&mut self) {
+        // 転送タスクが残るとボタンイベントの送信側が閉じず、マネージャー側の購読も終わらない
+        self.stop_notification_tasks();
+    }
+}
+
 impl LinuxConnection {
     /// 新しい接続を作成
     pub async fn new(peripheral: Peripheral, device_name: String) -> Result<Self> {
@@ -61,6 +96,8 @@ impl LinuxConnection {
             .map_err(|e| NotifError::Bluetooth(format!("Invalid command UUID: {}", e)))?;
         let status_uuid = Uuid::parse_str(protocol_uuid::STATUS_CHAR)
             .map_err(|e| NotifError::Bluetooth(format!("Invalid status UUID: {}", e)))?;
+        let config_uuid = Uuid::parse_str(protocol_uuid::CONFIG_CHAR)
+            .map_err(|e| NotifError::Bluetooth(format!("Invalid config UUID: {}", e)))?;
         
         // サービスの発見
         peripheral.discover_services().await
@@ -82,10 +119,24 @@ impl LinuxConnection {
             .find(|c| c.uuid == status_uuid)
             .cloned();
         
+        let config_char = service.characteristics.iter()
+            .find(|c| c.uuid == config_uuid)
+            .cloned();
+        
         // ステータス通知を有効化（可能な場合）
-        if let Some(ref char) = status_char {
-            let _ = peripheral.subscribe(char).await;
-        }
+        let events_tx = broadcast::channel(BUTTON_EVENT_CAPACITY).0;
+        let (status_rx, status_task) = match status_char {
+            Some(ref char) => subscribe_status(&peripheral, char, events_tx.clone()).await.unzip(),
+            None => (None, None),
+        };
+        
+        // バッテリーレベルの読み出しと通知の購読（Battery Service搭載時のみ）
+        let battery_tx = Arc::new(watch::channel(None).0);
+        let battery_char = find_battery_char(&peripheral);
+        let battery_task = match battery_char {
+            Some(ref char) => subscribe_battery(&peripheral, char, battery_tx.clone()).await,
+            None => None,
+        };
         
         // デバイス情報を作成
         let properties = peripheral.properties().await
@@ -97,42 +148,53 @@ impl LinuxConnection {
             address: properties.address.to_string(),
             connected: true,
             number: None,
+            alias: None,
             signal_strength: properties.rssi.map(|r| r as i8),
-            battery_level: None,
+            battery_level: *battery_tx.borrow(),
             capabilities: DeviceCapabilities::default(),
+            firmware_version: None,
+            reconnect: None,
+            statistics: None,
         };
         
-        Ok(LinuxConnection {
+        let mut connection = LinuxConnection {
             peripheral,
             device_info,
             command_char,
             status_char,
-        })
+            config_char,
+            status_rx,
+            status_task,
+            events_tx,
+            battery_char,
+            battery_tx,
+            battery_task,
+            ack_policy: None,
+            command_timeout: None,
+        };
+        
+        // 機能ディスクリプタを読み出す（非対応の旧ファームウェアは既定値のまま）
+        connection.refresh_capabilities().await?;
+        
+        Ok(connection)
     }
-}
-
-#[async_trait]
-impl Connection for LinuxConnection {
-    async fn send_command(&mut self, command: Command) -> Result<()> {
-        let data = command.encode();
-        
-        // デバッグ: コマンドタイプと最初の数バイトを表示
-        if data.len() >= 3 {
-            let cmd_type = data[0];
-            let payload_len = data[1] as u16 | ((data[2] as u16) << 8);
-            debug!("Sending command to Linux device: type=0x{:02X}, payload_len={}, total_bytes={}", 
-                  cmd_type, payload_len, data.len());
-            
-            // 画像タイルの場合のみログ出力（0x06 = CMD_IMAGE）
-            if cmd_type == 0x06 {
-                debug!("Sending image tile: {} bytes", data.len());
-            }
+    
+    /// 通知の転送タスクを止める（再購読で同じ通知を二重に転送しないよう、購読し直す前に呼ぶ）
+    fn stop_notification_tasks(&mut self) {
+        for task in [self.status_task.take(), self.battery_task.take()].into_iter().flatten() {
+            task.abort();
         }
-        
+    }
+    
+    /// エンコード済みデータをCOMMAND_CHARに書き込む
+    async fn write_data(&self, data: &[u8]) -> Result<()> {
         // WriteType選択: パフォーマンスのためWithoutResponseを使用
         // ただし、重要なコマンドや最後のチャンクはWithResponseを使用
-        let write_type = if data.len() >= 3 && data[0] == 0x06 {
-            // 画像タイルはWithoutResponseで高速送信
+        let write_type = if data.len() >= 3
+            && (command_type::base_of_extended(data[0]).unwrap_or(data[0]) == command_type::IMAGE
+                || data[0] == command_type::SEQUENCED)
+        {
+            // 画像タイル・シーケンス付きフレームはWithoutResponseで高速送信
             WriteType::WithoutResponse
         } else {
             // その他のコマンドはWithResponseで確実性を保つ
@@ -160,7 +222,7 @@ impl Connection for LinuxConnection {
             }
         } else {
             self.peripheral
-                .write(&self.command_char, &data, write_type)
+                .write(&self.command_char, data, write_type)
                 .await
                 .map_err(|e| NotifError::Bluetooth(format!("Write failed: {}", e)))?;
         }
@@ -168,6 +230,244 @@ impl Connection for LinuxConnection {
         Ok(())
     }
     
+    /// 書き込み後、ステータス通知を待って結果を確認する（Busyは再送）
+    async fn write_with_ack(&mut self, data: &[u8], policy: AckPolicy) -> Result<()> {
+        let mut attempt = 0;
+        
+        loop {
+            let Some(status_rx) = self.status_rx.as_mut() else {
+                // ステータス通知非対応のデバイスは書き込み結果のみで判断
+                debug!("Status notifications unavailable for {}, skipping ack", self.device_info.name);
+                return self.write_data(data).await;
+            };
+            
+            // 前のコマンドの遅延通知を破棄
+            while status_rx.try_recv().is_ok() {}
+            
+            self.write_data(data).await?;
+            
+            let status = self.wait_for_status(policy.timeout).await?;
+            match status.into_result(&self.device_info.name) {
+                Err(NotifError::DeviceBusy(_)) if attempt < policy.busy_retries => {
+                    attempt += 1;
+                    warn!("Device {} busy, retrying ({}/{})", self.device_info.name, attempt, policy.busy_retries);
+                    tokio::time::sleep(policy.busy_retry_delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+    
+    /// 未確認フレームの応答を収集し、確認済みのものを`pending`から取り除く
+    /// 
+    /// 全て確認されるか、`timeout`の間に新しい通知が来なければ戻る
+    async fn collect_sequence_acks(&mut self, pending: &mut BTreeMap<u16, Vec<u8>>, timeout: Duration) -> Result<()> {
+        let device_name = self.device_info.name.clone();
+        let status_rx = self.status_rx.as_mut()
+            .ok_or_else(|| NotifError::Bluetooth("Status characteristic not available".to_string()))?;
+        
+        while !pending.is_empty() {
+            let value = match tokio::time::timeout(timeout, status_rx.recv()).await {
+                Ok(Some(value)) => value,
+                Ok(None) => {
+                    return Err(NotifError::Connection(format!(
+                        "Status notification stream closed for {}", device_name
+                    )));
+                }
+                Err(_) => break,
+            };
+            
+            let notification = match StatusNotification::parse(&value) {
+                Ok(notification) => notification,
+                Err(e) => {
+                    warn!("Ignoring status notification from {}: {}", device_name, e);
+                    continue;
+                }
+            };
+            
+            let Some(seq) = notification.seq else {
+                continue;
+            };
+            
+            match notification.status {
+                StatusCode::Success => {
+                    pending.remove(&seq);
+                }
+                // Busyは未確認のまま残し、次のラウンドで再送
+                StatusCode::Busy => debug!("Frame {} rejected as busy by {}", seq, device_name),
+                status => return status.into_result(&device_name),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// ステータス通知を1件待機
+    async fn wait_for_status(&mut self, timeout: Duration) -> Result<StatusCode> {
+        let status_rx = self.status_rx.as_mut()
+            .ok_or_else(|| NotifError::Bluetooth("Status characteristic not available".to_string()))?;
+        
+        match tokio::time::timeout(timeout, status_rx.recv()).await {
+            Ok(Some(value)) => {
+                let byte = value.first().copied()
+                    .ok_or_else(|| NotifError::Bluetooth("Empty status notification".to_string()))?;
+                debug!("Status notification from {}: 0x{:02X}", self.device_info.name, byte);
+                Ok(StatusCode::from_byte(byte))
+            }
+            Ok(None) => Err(NotifError::Connection(format!(
+                "Status notification stream closed for {}", self.device_info.name
+            ))),
+            Err(_) => Err(NotifError::Timeout(format!(
+                "No status notification from {} within {}ms", self.device_info.name, timeout.as_millis()
+            ))),
+        }
+    }
+}
+
+/// CONFIG_CHARから機能ディスクリプタを読み出す
+async fn read_capabilities(peripheral: &Peripheral, config_char: &Characteristic) -> Result<CapabilityDescriptor> {
+    let data = peripheral.read(config_char).await
+        .map_err(|e| NotifError::Bluetooth(format!("Config read failed: {}", e)))?;
+    CapabilityDescriptor::parse(&data)
+}
+
+/// STATUS_CHARを購読し、通知を転送するチャネルと転送タスクを返す
+/// 
+/// ボタンイベントはステータス通知と分けて`events_tx`へ送る
+async fn subscribe_status(
+    peripheral: &Peripheral,
+    status_char: &Characteristic,
+    events_tx: broadcast::Sender<ButtonEvent>,
+) -> Option<(mpsc::UnboundedReceiver<Vec<u8>>, JoinHandle<()>)> {
+    if let Err(e) = peripheral.subscribe(status_char).await {
+        warn!("Failed to subscribe to status notifications: {}", e);
+        return None;
+    }
+    
+    let mut notifications = match peripheral.notifications().await {
+        Ok(stream) => stream,
+        Err(e) => {
+            warn!("Failed to open notification stream: {}", e);
+            return None;
+        }
+    };
+    
+    let (tx, rx) = mpsc::unbounded_channel();
+    let status_uuid = status_char.uuid;
+    let task = tokio::spawn(async move {
+        while let Some(notification) = notifications.next().await {
+            if notification.uuid != status_uuid {
+                continue;
+            }
+            
+            if ButtonEvent::is_event(&notification.value) {
+                match ButtonEvent::parse(&notification.value) {
+                    Some(event) => {
+                        let _ = events_tx.send(event);
+                    }
+                    None => warn!("Malformed button event: {:02X?}", notification.value),
+                }
+            } else if tx.send(notification.value).is_err() {
+                break;
+            }
+        }
+    });
+    
+    Some((rx, task))
+}
+
+/// Battery Service（0x180F）のBattery Levelキャラクタリスティックを探す（非搭載ならNone）
+fn find_battery_char(peripheral: &Peripheral) -> Option<Characteristic> {
+    let service_uuid = Uuid::parse_str(protocol_uuid::BATTERY_SERVICE).ok()?;
+    let level_uuid = Uuid::parse_str(protocol_uuid::BATTERY_LEVEL_CHAR).ok()?;
+    
+    peripheral.services().iter()
+        .find(|s| s.uuid == service_uuid)?
+        .characteristics.iter()
+        .find(|c| c.uuid == level_uuid)
+        .cloned()
+}
+
+/// Battery Levelを読み出し、通知を購読して`battery_tx`に反映する（通知の転送タスクを返す）
+/// 
+/// 読み出し・購読に失敗してもバッテリーレベルが不明になるだけで接続は続ける
+async fn subscribe_battery(
+    peripheral: &Peripheral,
+    battery_char: &Characteristic,
+    battery_tx: Arc<watch::Sender<Option<u8>>>,
+) -> Option<JoinHandle<()>> {
+    match peripheral.read(battery_char).await {
+        Ok(value) => {
+            if let Some(&level) = value.first() {
+                battery_tx.send_replace(Some(level.min(100)));
+            }
+        }
+        Err(e) => warn!("Failed to read battery level: {}", e),
+    }
+    
+    // 通知に対応しないデバイスは初回の値のみ
+    if !battery_char.properties.contains(CharPropFlags::NOTIFY) {
+        debug!("Battery level notifications not supported");
+        return None;
+    }
+    
+    if let Err(e) = peripheral.subscribe(battery_char).await {
+        warn!("Failed to subscribe to battery level notifications: {}", e);
+        return None;
+    }
+    
+    let mut notifications = match peripheral.notifications().await {
+        Ok(stream) => stream,
+        Err(e) => {
+            warn!("Failed to open notification stream: {}", e);
+            return None;
+        }
+    };
+    
+    let battery_uuid = battery_char.uuid;
+    Some(tokio::spawn(async move {
+        while let Some(notification) = notifications.next().await {
+            if notification.uuid != battery_uuid {
+                continue;
+            }
+            
+            if let Some(&level) = notification.value.first() {
+                debug!("Battery level notification: {}%", level);
+                battery_tx.send_replace(Some(level.min(100)));
+            }
+        }
+    }))
+}
+
+#[async_trait]
+impl Connection for LinuxConnection {
+    async fn send_command(&mut self, command: Command) -> Result<()> {
+        // 1フレームに収まらないコマンドは分割して順に送信
+        for data in command.encode_frames()? {
+            // デバッグ: コマンドタイプと最初の数バイトを表示
+            if data.len() >= 3 {
+                let cmd_type = data[0];
+                let payload_len = data[1] as u16 | ((data[2] as u16) << 8);
+                debug!("Sending command to Linux device: type=0x{:02X}, payload_len={}, total_bytes={}", 
+                      cmd_type, payload_len, data.len());
+                
+                // 画像タイルの場合のみログ出力（0x06 = CMD_IMAGE）
+                if cmd_type == 0x06 {
+                    debug!("Sending image tile: {} bytes", data.len());
+                }
+            }
+            
+            // タイムアウトはフレームごと（送信を始めたコマンドを途中で打ち切らない）
+            let timeout = self.command_timeout;
+            match self.ack_policy {
+                Some(policy) => with_frame_timeout(timeout, self.write_with_ack(&data, policy)).await?,
+                None => with_frame_timeout(timeout, self.write_data(&data)).await?,
+            }
+        }
+        
+        Ok(())
+    }
+    
     async fn is_connected(&self) -> bool {
         self.peripheral.is_connected().await.unwrap_or(false)
     }
@@ -175,6 +475,7 @@ impl Connection for LinuxConnection {
     async fn get_device_info(&self) -> DeviceInfo {
         let mut info = self.device_info.clone();
         info.connected = self.is_connected().await;
+        info.battery_level = *self.battery_tx.borrow();
         
         // 最新のRSSIを取得
         if let Ok(Some(props)) = self.peripheral.properties().await {
@@ -200,14 +501,29 @@ impl Connection for LinuxConnection {
             self.peripheral.discover_services().await
                 .map_err(|e| NotifError::Bluetooth(format!("Service rediscovery failed: {}", e)))?;
             
+            // 前回の転送タスクを止めてからステータス通知を再購読
+            self.stop_notification_tasks();
+            if let Some(ref char) = self.status_char {
+                (self.status_rx, self.status_task) =
+                    subscribe_status(&self.peripheral, char, self.events_tx.clone()).await.unzip();
+            }
+            
+            // バッテリーレベルを読み直して再購読
+            self.battery_char = find_battery_char(&self.peripheral);
+            if let Some(ref char) = self.battery_char {
+                self.battery_task = subscribe_battery(&self.peripheral, char, self.battery_tx.clone()).await;
+            }
+            
+            // ファームウェアが更新されている可能性があるため機能を読み直す
+            self.refresh_capabilities().await?;
+            
             self.device_info.connected = true;
         }
         Ok(())
     }
     
     async fn get_battery_level(&self) -> Option<u8> {
-        // TODO: Battery Service (0x180F) から読み取り
-        None
+        *self.battery_tx.borrow()
     }
     
     async fn get_signal_strength(&self) -> Option<i8> {
@@ -217,6 +533,116 @@ impl Connection for LinuxConnection {
             None
         }
     }
+    
+    async fn send_sequenced(&mut self, commands: Vec<Command>, max_rounds: u32) -> Result<()> {
+        if self.status_rx.is_none() {
+            // 応答を受け取れないデバイスは順次送信にフォールバック
+            debug!("Status notifications unavailable for {}, sending sequentially", self.device_info.name);
+            for command in commands {
+                self.send_command(command).await?;
+            }
+            return Ok(());
+        }
+        
+        let timeout = self.ack_policy
+            .map(|policy| policy.timeout)
+            .unwrap_or(DEFAULT_SEQUENCE_ACK_TIMEOUT);
+        
+        let mut frames = Vec::new();
+        for command in commands {
+            frames.extend(command.split_frames()?);
+        }
+        if frames.len() > u16::MAX as usize + 1 {
+            return Err(NotifError::InvalidParameter(format!(
+                "Too many frames for sequenced transfer: {}", frames.len()
+            )));
+        }
+        
+        let mut pending = BTreeMap::new();
+        for (index, command) in frames.into_iter().enumerate() {
+            let seq = index as u16;
+            pending.insert(seq, Command::Sequenced { seq, command: Box::new(command) }.encode()?);
+        }
+        let total = pending.len();
+        
+        // 前のコマンドの遅延通知を破棄
+        if let Some(status_rx) = self.status_rx.as_mut() {
+            while status_rx.try_recv().is_ok() {}
+        }
+        
+        for round in 0..=max_rounds {
+            if round > 0 {
+                warn!("Resending {}/{} unacknowledged frames to {} (round {}/{})",
+                      pending.len(), total, self.device_info.name, round, max_rounds);
+            }
+            
+            for data in pending.values() {
+                with_frame_timeout(self.command_timeout, self.write_data(data)).await?;
+            }
+            
+            self.collect_sequence_acks(&mut pending, timeout).await?;
+            if pending.is_empty() {
+                debug!("All {} sequenced frames acknowledged by {}", total, self.device_info.name);
+                return Ok(());
+            }
+        }
+        
+        Err(NotifError::Timeout(format!(
+            "{} of {} frames unacknowledged by {} after {} retransmissions",
+            pending.len(), total, self.device_info.name, max_rounds
+        )))
+    }
+    
+    fn set_ack_policy(&mut self, policy: Option<AckPolicy>) {
+        self.ack_policy = policy;
+    }
+    
+    fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+        self.command_timeout = timeout;
+    }
+    
+    async fn refresh_capabilities(&mut self) -> Result<()> {
+        let Some(ref config_char) = self.config_char else {
+            return Ok(());
+        };
+        
+        match read_capabilities(&self.peripheral, config_char).await {
+            Ok(descriptor) => {
+                info!(
+                    "{}: firmware {}, {}x{} {}-bit, features 0x{:04X}",
+                    self.device_info.name,
+                    descriptor.firmware_version_string(),
+                    descriptor.display_width,
+                    descriptor.display_height,
+                    descriptor.color_depth,
+                    descriptor.features
+                );
+                self.device_info.capabilities = DeviceCapabilities::from_descriptor(&descriptor);
+                self.device_info.firmware_version = Some(descriptor.firmware_version_string());
+            }
+            Err(e) => warn!("{}: capability handshake failed, using defaults: {}", self.device_info.name, e),
+        }
+        Ok(())
+    }
+    
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<ButtonEvent>> {
+        self.status_char.as_ref().map(|_| self.events_tx.subscribe())
+    }
+    
+    fn subscribe_battery(&self) -> Option<watch::Receiver<Option<u8>>> {
+        self.battery_char.as_ref().map(|_| self.battery_tx.subscribe())
+    }
+    
+    async fn apply_setting(&mut self, setting: DeviceSetting) -> Result<()> {
+        let config_char = self.config_char.as_ref().ok_or_else(|| NotifError::NotImplemented(
+            format!("Config characteristic not available on {}", self.device_info.name)
+        ))?;
+        let data = setting.encode()?;
+        
+        debug!("Writing setting {:?} to {}", setting, self.device_info.name);
+        self.peripheral.write(config_char, &data, WriteType::WithResponse).await
+            .map_err(|e| NotifError::Bluetooth(format!("Config write failed: {}", e)))
+    }
 }
 
 /// Linux Bluetoothスキャナー
@@ -312,9 +738,13 @@ impl Scanner for LinuxScanner {
                                 address: properties.address.to_string(),
                                 connected: false,
                                 number: None,
+                                alias: None,
                                 signal_strength: properties.rssi.map(|r| r as i8),
                                 battery_level: None,
                                 capabilities: DeviceCapabilities::default(),
+                                firmware_version: None,
+                                reconnect: None,
+                                statistics: None,
                             });
                         }
                     }
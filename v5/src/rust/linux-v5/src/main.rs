@@ -7,12 +7,12 @@ use tracing_subscriber::FmtSubscriber;
 
 use notif_common_v5::{
     BluetoothManager, Result, Settings, VERSION,
-    api::{process_v1_send, process_v1_status, process_v2_draw, process_v2_draw_query, process_v2_draw_post, process_v2_devices, process_v2_health, process_v2_batch},
+    api::{process_v1_send, process_v1_status, process_v2_draw, process_v2_draw_query, process_v2_draw_post, process_v2_devices, process_v2_health, process_v2_batch, process_v2_device_settings, process_v2_device_screen, process_v2_device_assignment, process_v2_groups, process_v2_group_update, process_v2_group_delete},
     AppState, SessionManager, mcp_handler,
 };
 
 // v5画像アップロード機能
-use notif_common_v5::api::{upload_image, post_image};
+use notif_common_v5::api::{upload_image, post_image, register_asset, draw_asset};
 
 mod bluetooth_impl;
 mod platform;
@@ -86,6 +86,25 @@ async fn main() -> Result<()> {
     let bt_manager = create_bluetooth_manager().await?;
     info!("Bluetooth manager initialized successfully");
     
+    // 応答確認ポリシーの設定（接続前に適用）
+    bt_manager.set_ack_policy(settings.bluetooth.ack_policy()).await;
+    
+    // 再接続ポリシー・送信タイムアウト・探索ポリシー・接続対象・低バッテリー通知の設定
+    bt_manager.set_reconnect_policy(settings.bluetooth.reconnect_policy()).await;
+    bt_manager.set_command_timeout(settings.bluetooth.command_timeout()).await;
+    bt_manager.set_discovery_policy(settings.bluetooth.discovery_policy()).await;
+    bt_manager.set_device_filter(settings.bluetooth.device_filter()).await;
+    bt_manager.set_low_battery_threshold(settings.bluetooth.low_battery_threshold).await;
+    
+    // デバイス番号・エイリアス・グループの読み込み（設定ファイルの指定を優先）
+    bt_manager.set_state_file(settings.devices.state_file_path()).await?;
+    for (device_name, pin) in &settings.devices.pinned {
+        bt_manager.pin_device(device_name, pin.clone()).await?;
+    }
+    for (group, members) in &settings.devices.groups {
+        bt_manager.set_group(group, members.clone()).await?;
+    }
+    
     // デバイスのスキャンと接続
     info!("Scanning for devices with prefix: {}", settings.bluetooth.device_name_prefix);
     match bt_manager.scan_and_connect_all().await {
@@ -97,11 +116,25 @@ async fn main() -> Result<()> {
         }
     }
     
+    // 仮想ディスプレイの登録（実機なしでの動作確認・デモ用）
+    if settings.bluetooth.virtual_display {
+        let name = format!("{}_virtual", settings.bluetooth.device_name_prefix);
+        bt_manager.add_virtual_display(name.clone()).await?;
+        info!("Virtual display registered: {}", name);
+    }
+    
     // 自動再接続の設定
     bt_manager.set_auto_reconnect(settings.bluetooth.auto_reconnect).await?;
     
     // アプリケーション状態を作成（MCP対応）
     let bt_manager = Arc::new(bt_manager);
+    
+    // デバイスのボタンにアクションを割り当て
+    notif_common_v5::bluetooth::spawn_button_actions(bt_manager.clone(), settings.buttons.clone());
+    
+    // 後から電源が入ったデバイスを拾うバックグラウンドスキャン
+    bt_manager.start_rescan().await;
+    
     let app_state = Arc::new(AppState {
         bt_manager: bt_manager.clone(),
         session_manager: SessionManager::new(),
@@ -172,6 +205,30 @@ async fn main() -> Result<()> {
                 |req: web::Json<notif_common_v5::api::models::v2::BatchRequest>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
                 process_v2_batch(req.into_inner(), bt_manager)
             ))
+            .route("/api/devices/{device}/settings", web::post().to(
+                |path: web::Path<String>, req: web::Json<notif_common_v5::DeviceSettings>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                process_v2_device_settings(path.into_inner(), req.into_inner(), bt_manager)
+            ))
+            .route("/api/devices/{device}/screen.png", web::get().to(
+                |path: web::Path<String>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                process_v2_device_screen(path.into_inner(), bt_manager)
+            ))
+            .route("/api/devices/{device}/assignment", web::put().to(
+                |path: web::Path<String>, req: web::Json<notif_common_v5::DevicePin>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                process_v2_device_assignment(path.into_inner(), req.into_inner(), bt_manager)
+            ))
+            .route("/api/groups", web::get().to(
+                |bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                process_v2_groups(bt_manager)
+            ))
+            .route("/api/groups/{group}", web::put().to(
+                |path: web::Path<String>, req: web::Json<notif_common_v5::api::models::v2::GroupRequest>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                process_v2_group_update(path.into_inner(), req.into_inner(), bt_manager)
+            ))
+            .route("/api/groups/{group}", web::delete().to(
+                |path: web::Path<String>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                process_v2_group_delete(path.into_inner(), bt_manager)
+            ))
             
             // MCP エンドポイント
             .route("/mcp", web::post().to(mcp_handler))
@@ -186,6 +243,16 @@ async fn main() -> Result<()> {
                 |body: web::Bytes, query: web::Query<notif_common_v5::api::ImageUploadParams>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
                 post_image(body, query, bt_manager)
             ))
+            
+            // アセットキャッシュ（登録・描画）
+            .route("/api/assets/{id}", web::post().to(
+                |path: web::Path<u16>, body: web::Bytes, query: web::Query<notif_common_v5::api::AssetUploadParams>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                register_asset(path, body, query, bt_manager)
+            ))
+            .route("/api/assets/{id}/draw", web::post().to(
+                |path: web::Path<u16>, query: web::Query<notif_common_v5::api::AssetDrawParams>, bt_manager: web::Data<Arc<notif_common_v5::CommonBluetoothManager>>| 
+                draw_asset(path, query, bt_manager)
+            ))
     })
     .bind(&bind_address)?
     .run();
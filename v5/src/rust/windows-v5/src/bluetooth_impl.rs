@@ -1,10 +1,11 @@
 //! Windows固有のBluetooth実装
 
 use async_trait::async_trait;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
@@ -19,7 +20,7 @@ use windows::{
         GenericAttributeProfile::{
             GattCharacteristic, GattCommunicationStatus,
             GattWriteOption, GattDeviceService,
-            GattClientCharacteristicConfigurationDescriptorValue,
+            GattClientCharacteristicConfigurationDescriptorValue, GattValueChangedEventArgs,
         },
     },
     Foundation::{EventRegistrationToken, TypedEventHandler},
@@ -27,8 +28,9 @@ use windows::{
 };
 
 use notif_common_v5::{
-    Connection, DeviceCapabilities, DeviceInfo, NotifError, Result, Scanner,
-    Command, protocol::uuid as protocol_uuid,
+    AckPolicy, Connection, DeviceCapabilities, DeviceInfo, NotifError, Result, Scanner, with_frame_timeout,
+    ButtonEvent, Command, DeviceSetting, StatusCode,
+    protocol::{uuid as protocol_uuid, CapabilityDescriptor, StatusNotification},
 };
 
 /// Windows Errorを NotifErrorに変換（v2スタイル）
@@ -36,6 +38,145 @@ fn windows_error_to_notif_error(err: windows::core::Error) -> NotifError {
     NotifError::Bluetooth(format!("Windows API error: {}", err.message()))
 }
 
+/// シーケンス送信でAckPolicy未設定時に使う応答待ちタイムアウト
+const DEFAULT_SEQUENCE_ACK_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// ボタンイベントのバッファ数
+const BUTTON_EVENT_CAPACITY: usize = 16;
+
+/// IBufferの内容をバイト列として読み出す
+fn read_buffer(buffer: &IBuffer) -> Option<Vec<u8>> {
+    let reader = windows::Storage::Streams::DataReader::FromBuffer(buffer).ok()?;
+    let mut data = vec![0u8; reader.UnconsumedBufferLength().ok()? as usize];
+    reader.ReadBytes(&mut data).ok()?;
+    Some(data)
+}
+
+/// CONFIG_CHARから機能ディスクリプタを読み出す
+fn read_capabilities(config_char: &GattCharacteristic) -> Result<CapabilityDescriptor> {
+    let result = config_char.ReadValueAsync()
+        .map_err(windows_error_to_notif_error)?
+        .get()
+        .map_err(windows_error_to_notif_error)?;
+    
+    let status = result.Status().map_err(windows_error_to_notif_error)?;
+    if status != GattCommunicationStatus::Success {
+        return Err(NotifError::Bluetooth(format!("Failed to read config: {:?}", status)));
+    }
+    
+    let value = result.Value().map_err(windows_error_to_notif_error)?;
+    let data = read_buffer(&value)
+        .ok_or_else(|| NotifError::Bluetooth("Failed to read config buffer".to_string()))?;
+    CapabilityDescriptor::parse(&data)
+}
+
+/// STATUS_CHARの通知を転送するハンドラーを登録し、ステータス通知の受信チャネルを返す
+/// 
+/// ボタンイベントはステータス通知と分けて`events_tx`へ送る
+fn subscribe_status(
+    status_char: &GattCharacteristic,
+    events_tx: broadcast::Sender<ButtonEvent>,
+) -> Result<(EventRegistrationToken, mpsc::UnboundedReceiver<Vec<u8>>)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let handler = TypedEventHandler::new(move |_, args: &Option<GattValueChangedEventArgs>| {
+        let Some(data) = args.as_ref()
+            .and_then(|args| args.CharacteristicValue().ok())
+            .and_then(|value| read_buffer(&value))
+        else {
+            return Ok(());
+        };
+        
+        if ButtonEvent::is_event(&data) {
+            match ButtonEvent::parse(&data) {
+                Some(event) => {
+                    let _ = events_tx.send(event);
+                }
+                None => warn!("Malformed button event: {:02X?}", data),
+            }
+        } else {
+            let _ = tx.send(data);
+        }
+        Ok(())
+    });
+    
+    let token = status_char.ValueChanged(&handler).map_err(windows_error_to_notif_error)?;
+    Ok((token, rx))
+}
+
+/// Battery Service（0x180F）のBattery Levelキャラクタリスティックを探す（非搭載ならNone）
+fn find_battery_char(device: &BluetoothLEDevice) -> Option<GattCharacteristic> {
+    let service_uuid = parse_guid(protocol_uuid::BATTERY_SERVICE).ok()?;
+    let level_uuid = parse_guid(protocol_uuid::BATTERY_LEVEL_CHAR).ok()?;
+    
+    let services_result = device.GetGattServicesForUuidAsync(&service_uuid).ok()?.get().ok()?;
+    if services_result.Status().ok()? != GattCommunicationStatus::Success {
+        return None;
+    }
+    let services = services_result.Services().ok()?;
+    if services.Size().ok()? == 0 {
+        return None;
+    }
+    
+    let service = services.GetAt(0).ok()?;
+    let chars_result = service.GetCharacteristicsForUuidAsync(&level_uuid).ok()?.get().ok()?;
+    if chars_result.Status().ok()? != GattCommunicationStatus::Success {
+        return None;
+    }
+    let chars = chars_result.Characteristics().ok()?;
+    if chars.Size().ok()? == 0 {
+        return None;
+    }
+    chars.GetAt(0).ok()
+}
+
+/// Battery Levelを読み出し、通知で`battery_tx`を更新するハンドラーを登録（登録トークンを返す）
+/// 
+/// 読み出し・購読に失敗してもバッテリーレベルが不明になるだけで接続は続ける
+fn subscribe_battery(
+    battery_char: &GattCharacteristic,
+    battery_tx: Arc<watch::Sender<Option<u8>>>,
+) -> Option<EventRegistrationToken> {
+    let initial = battery_char.ReadValueAsync().ok()
+        .and_then(|operation| operation.get().ok())
+        .filter(|result| result.Status().ok() == Some(GattCommunicationStatus::Success))
+        .and_then(|result| result.Value().ok())
+        .and_then(|value| read_buffer(&value));
+    match initial.as_deref().and_then(|data| data.first()) {
+        Some(&level) => {
+            battery_tx.send_replace(Some(level.min(100)));
+        }
+        None => warn!("Failed to read battery level"),
+    }
+    
+    // 通知に対応しないデバイスは初回の値のみ
+    let cccd_value = GattClientCharacteristicConfigurationDescriptorValue::Notify;
+    let enabled = battery_char.WriteClientCharacteristicConfigurationDescriptorAsync(cccd_value).ok()
+        .and_then(|operation| operation.get().ok());
+    if enabled != Some(GattCommunicationStatus::Success) {
+        debug!("Battery level notifications not available");
+        return None;
+    }
+    
+    let handler = TypedEventHandler::new(move |_, args: &Option<GattValueChangedEventArgs>| {
+        if let Some(args) = args {
+            let data = args.CharacteristicValue().ok().and_then(|value| read_buffer(&value));
+            if let Some(&level) = data.as_deref().and_then(|data| data.first()) {
+                debug!("Battery level notification: {}%", level);
+                battery_tx.send_replace(Some(level.min(100)));
+            }
+        }
+        Ok(())
+    });
+    
+    match battery_char.ValueChanged(&handler) {
+        Ok(token) => Some(token),
+        Err(e) => {
+            warn!("Failed to register battery level handler: {}", e.message());
+            None
+        }
+    }
+}
+
 /// Windows固有データ
 #[derive(Clone)]
 pub struct WindowsPlatformData {
@@ -56,10 +197,28 @@ pub struct WindowsConnection {
     device_info: DeviceInfo,
     command_char: GattCharacteristic,
     status_char: Option<GattCharacteristic>,
+    // 設定用キャラクタリスティック（非対応ファームウェアではNone）
+    config_char: Option<GattCharacteristic>,
     service: GattDeviceService,
     // Connection Interval推定用のフィールド
     last_send_time: Option<Instant>,
     send_intervals: Vec<u64>,  // ミリ秒単位の送信間隔を記録
+    // 応答確認ポリシー
+    ack_policy: Option<AckPolicy>,
+    // 1フレームの送信タイムアウト
+    command_timeout: Option<Duration>,
+    // STATUS_CHAR通知の受信チャネル（ハンドラー登録に失敗した場合はNone）
+    status_rx: Option<mpsc::UnboundedReceiver<Vec<u8>>>,
+    // STATUS_CHARのハンドラー登録トークン（再登録時に解除する）
+    status_token: Option<EventRegistrationToken>,
+    // ボタンイベントの送信側（再接続しても購読者はそのまま）
+    events_tx: broadcast::Sender<ButtonEvent>,
+    // Battery Levelキャラクタリスティック（通知を受けるため保持。非搭載ではNone）
+    battery_char: Option<GattCharacteristic>,
+    // 最新のバッテリーレベル（通知で更新し、再接続しても購読者はそのまま）
+    battery_tx: Arc<watch::Sender<Option<u8>>>,
+    // Battery Levelのハンドラー登録トークン（再登録時に解除する）
+    battery_token: Option<EventRegistrationToken>,
 }
 
 impl Debug for WindowsConnection {
@@ -70,7 +229,28 @@ impl Debug for WindowsConnection {
     }
 }
 
+impl Drop for WindowsConnection {
+    fn drop(&mut self) {
+        // ハンドラーが残るとボタンイベントの送信側が閉じず、マネージャー側の購読も終わらない
+        self.remove_notification_handlers();
+    }
+}
+
 impl WindowsConnection {
+    /// 登録済みの通知ハンドラーを解除
+    fn remove_notification_handlers(&mut self) {
+        if let (Some(char), Some(token)) = (&self.status_char, self.status_token.take()) {
+            if let Err(e) = char.RemoveValueChanged(token) {
+                debug!("Failed to remove status notification handler: {}", e.message());
+            }
+        }
+        if let (Some(char), Some(token)) = (&self.battery_char, self.battery_token.take()) {
+            if let Err(e) = char.RemoveValueChanged(token) {
+                debug!("Failed to remove battery level handler: {}", e.message());
+            }
+        }
+    }
+    
     /// 新しい接続を作成
     pub async fn new(device: BluetoothLEDevice, device_name: String) -> Result<Self> {
         Self::new_with_optimization(device, device_name, false).await
@@ -149,6 +329,7 @@ impl WindowsConnection {
         // キャラクタリスティックの取得
         let command_uuid = parse_guid(protocol_uuid::COMMAND_CHAR)?;
         let status_uuid = parse_guid(protocol_uuid::STATUS_CHAR)?;
+        let config_uuid = parse_guid(protocol_uuid::CONFIG_CHAR)?;
         
         let chars_result = service.GetCharacteristicsAsync()
             .map_err(windows_error_to_notif_error)?
@@ -162,6 +343,7 @@ impl WindowsConnection {
         let chars = chars_result.Characteristics().map_err(windows_error_to_notif_error)?;
         let mut command_char = None;
         let mut status_char = None;
+        let mut config_char = None;
         
         for i in 0..chars.Size().map_err(windows_error_to_notif_error)? {
             let char = chars.GetAt(i).map_err(windows_error_to_notif_error)?;
@@ -171,6 +353,8 @@ impl WindowsConnection {
                 command_char = Some(char);
             } else if char_uuid == status_uuid {
                 status_char = Some(char);
+            } else if char_uuid == config_uuid {
+                config_char = Some(char);
             }
         }
         
@@ -200,15 +384,35 @@ impl WindowsConnection {
             return Err(NotifError::Bluetooth("Failed to enable notifications".to_string()));
         }
         
+        // ステータス通知とボタンイベントの受信を開始
+        let events_tx = broadcast::channel(BUTTON_EVENT_CAPACITY).0;
+        let (status_token, status_rx) = match subscribe_status(&status_char, events_tx.clone()) {
+            Ok((token, rx)) => (Some(token), Some(rx)),
+            Err(e) => {
+                warn!("Failed to register status notification handler: {}", e);
+                (None, None)
+            }
+        };
+        
+        // バッテリーレベルの読み出しと通知の購読（Battery Service搭載時のみ）
+        let battery_tx = Arc::new(watch::channel(None).0);
+        let battery_char = find_battery_char(&device);
+        let battery_token = battery_char.as_ref()
+            .and_then(|char| subscribe_battery(char, battery_tx.clone()));
+        
         // デバイス情報を作成
         let device_info = DeviceInfo {
             name: device_name,
             address: format!("{:016X}", device.BluetoothAddress().map_err(windows_error_to_notif_error)?),
             connected: device.ConnectionStatus().map_err(windows_error_to_notif_error)? == BluetoothConnectionStatus::Connected,
             number: None,
+            alias: None,
             signal_strength: None,
-            battery_level: None,
+            battery_level: *battery_tx.borrow(),
             capabilities: DeviceCapabilities::default(),
+            firmware_version: None,
+            reconnect: None,
+            statistics: None,
         };
         
         let mut connection = WindowsConnection {
@@ -216,11 +420,23 @@ impl WindowsConnection {
             device_info,
             command_char,
             status_char: Some(status_char),
+            config_char,
             service,
             last_send_time: None,
             send_intervals: Vec::new(),
+            ack_policy: None,
+            command_timeout: None,
+            status_rx,
+            status_token,
+            events_tx,
+            battery_char,
+            battery_tx,
+            battery_token,
         };
         
+        // 機能ディスクリプタを読み出す（非対応の旧ファームウェアは既定値のまま）
+        connection.refresh_capabilities().await?;
+        
         // 接続最適化が有効な場合
         if optimize {
             info!("接続速度の最適化を開始: {}", device_name);
@@ -231,13 +447,115 @@ impl WindowsConnection {
         
         Ok(connection)
     }
-}
-
-#[async_trait]
-impl Connection for WindowsConnection {
-    async fn send_command(&mut self, command: Command) -> Result<()> {
-        let data = command.encode();
+    
+    /// エンコード済みデータをCOMMAND_CHARに書き込む
+    /// 
+    /// 完了待ち（`.get()`）はブロックするため専用スレッドで待ち、`with_frame_timeout`で打ち切れるようにする
+    async fn write_data(&self, data: &[u8]) -> Result<()> {
+        let command_char = self.command_char.clone();
+        let bytes = data.to_vec();
+        let status = tokio::task::spawn_blocking(move || -> Result<GattCommunicationStatus> {
+            // DataWriterを使用してデータをIBufferに変換
+            let writer = DataWriter::new().map_err(windows_error_to_notif_error)?;
+            writer.WriteBytes(&bytes).map_err(windows_error_to_notif_error)?;
+            let buffer = writer.DetachBuffer().map_err(windows_error_to_notif_error)?;
+            
+            // BLE MTUを考慮（512バイト以下） - v2互換の書き込みメソッドを使用
+            let write_result = command_char
+                .WriteValueWithResultAsync(&buffer)
+                .map_err(windows_error_to_notif_error)?
+                .get()
+                .map_err(windows_error_to_notif_error)?;
+            
+            write_result.Status().map_err(windows_error_to_notif_error)
+        })
+        .await
+        .map_err(|e| NotifError::Bluetooth(format!("Write task failed: {}", e)))??;
         
+        // 送信結果の詳細をログ出力
+        if data.len() > 100 {
+            info!("Write completed for {} bytes, status: {:?}, device: {}", 
+                 data.len(), status, self.device_info.name);
+        } else {
+            debug!("Write result status: {:?}", status);
+        }
+        
+        if status != GattCommunicationStatus::Success {
+            return Err(NotifError::Bluetooth(format!(
+                "Failed to write command: {:?}", status
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// ステータス通知を1件待機
+    async fn wait_for_status(&mut self, timeout: Duration) -> Result<StatusCode> {
+        let status_rx = self.status_rx.as_mut()
+            .ok_or_else(|| NotifError::Bluetooth("Status notifications not available".to_string()))?;
+        
+        match tokio::time::timeout(timeout, status_rx.recv()).await {
+            Ok(Some(value)) => {
+                let byte = value.first().copied()
+                    .ok_or_else(|| NotifError::Bluetooth("Empty status notification".to_string()))?;
+                debug!("Status notification from {}: 0x{:02X}", self.device_info.name, byte);
+                Ok(StatusCode::from_byte(byte))
+            }
+            Ok(None) => Err(NotifError::Connection(format!(
+                "Status notification stream closed for {}", self.device_info.name
+            ))),
+            Err(_) => Err(NotifError::Timeout(format!(
+                "No status notification from {} within {}ms", self.device_info.name, timeout.as_millis()
+            ))),
+        }
+    }
+    
+    /// シーケンス番号付きフレームの応答を集め、確認できたものを`pending`から除く
+    /// 
+    /// タイムアウトまでに届かなかったフレームは`pending`に残る（再送対象）
+    async fn collect_sequence_acks(&mut self, pending: &mut BTreeMap<u16, Vec<u8>>, timeout: Duration) -> Result<()> {
+        let device_name = self.device_info.name.clone();
+        let status_rx = self.status_rx.as_mut()
+            .ok_or_else(|| NotifError::Bluetooth("Status notifications not available".to_string()))?;
+        
+        while !pending.is_empty() {
+            let value = match tokio::time::timeout(timeout, status_rx.recv()).await {
+                Ok(Some(value)) => value,
+                Ok(None) => {
+                    return Err(NotifError::Connection(format!(
+                        "Status notification stream closed for {}", device_name
+                    )));
+                }
+                Err(_) => break,
+            };
+            
+            let notification = match StatusNotification::parse(&value) {
+                Ok(notification) => notification,
+                Err(e) => {
+                    warn!("Ignoring status notification from {}: {}", device_name, e);
+                    continue;
+                }
+            };
+            
+            let Some(seq) = notification.seq else {
+                continue;
+            };
+            
+            match notification.status {
+                StatusCode::Success => {
+                    pending.remove(&seq);
+                }
+                // Busyは未確認のまま残し、次のラウンドで再送
+                StatusCode::Busy => debug!("Frame {} rejected as busy by {}", seq, device_name),
+                status => return status.into_result(&device_name),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 1フレームを送信し、応答確認ポリシーに従ってステータスを確認する
+    async fn send_frame(&mut self, data: &[u8]) -> Result<()> {
         // Connection Interval推定用の送信時刻記録
         let now = Instant::now();
         if let Some(last_time) = self.last_send_time {
@@ -271,56 +589,44 @@ impl Connection for WindowsConnection {
             info!("Large packet transmission: {} bytes to device {}", data.len(), self.device_info.name);
         }
         
-        // DataWriterを使用してデータをIBufferに変換
-        let writer = DataWriter::new().map_err(windows_error_to_notif_error)?;
-        writer.WriteBytes(&data).map_err(windows_error_to_notif_error)?;
-        let buffer = writer.DetachBuffer().map_err(windows_error_to_notif_error)?;
-        
-        // BLE MTUを考慮（512バイト以下） - v2互換の書き込みメソッドを使用
-        let write_result = self.command_char
-            .WriteValueWithResultAsync(&buffer)
-            .map_err(windows_error_to_notif_error)?
-            .get()
-            .map_err(windows_error_to_notif_error)?;
-        
-        let status = write_result.Status().map_err(windows_error_to_notif_error)?;
-        
-        // 送信結果の詳細をログ出力
-        if data.len() > 100 {
-            info!("Write completed for {} bytes, status: {:?}, device: {}", 
-                 data.len(), status, self.device_info.name);
-        } else {
-            debug!("Write result status: {:?}", status);
-        }
-        
-        if status != GattCommunicationStatus::Success {
-            return Err(NotifError::Bluetooth(format!(
-                "Failed to write command: {:?}", status
-            )));
-        }
+        let Some(policy) = self.ack_policy else {
+            return self.write_data(data).await;
+        };
         
-        // ステータス特性がある場合、応答を確認（デバッグ用）
-        if let Some(ref status_char) = self.status_char {
-            // ステータスを読み取ってみる（非同期で待たない）
-            if let Ok(read_result) = status_char.ReadValueAsync() {
-                if let Ok(result) = read_result.get() {
-                    if result.Status().map_err(windows_error_to_notif_error)? == GattCommunicationStatus::Success {
-                        if let Ok(value) = result.Value() {
-                            if let Ok(reader) = windows::Storage::Streams::DataReader::FromBuffer(&value) {
-                                if let Ok(length) = reader.UnconsumedBufferLength() {
-                                    if length > 0 {
-                                        if let Ok(status_byte) = reader.ReadByte() {
-                                            debug!("Device status after command: 0x{:02X}", status_byte);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        let mut attempt = 0;
+        loop {
+            let Some(status_rx) = self.status_rx.as_mut() else {
+                // ステータス通知を受け取れない場合は書き込み結果のみで判断
+                debug!("Status notifications unavailable for {}, skipping ack", self.device_info.name);
+                return self.write_data(data).await;
+            };
+            
+            // 前のフレームの遅延通知を破棄
+            while status_rx.try_recv().is_ok() {}
+            
+            self.write_data(data).await?;
+            
+            let status = self.wait_for_status(policy.timeout).await?;
+            match status.into_result(&self.device_info.name) {
+                Err(NotifError::DeviceBusy(_)) if attempt < policy.busy_retries => {
+                    attempt += 1;
+                    warn!("Device {} busy, retrying ({}/{})", self.device_info.name, attempt, policy.busy_retries);
+                    tokio::time::sleep(policy.busy_retry_delay).await;
                 }
+                result => return result,
             }
         }
-        
+    }
+}
+
+#[async_trait]
+impl Connection for WindowsConnection {
+    async fn send_command(&mut self, command: Command) -> Result<()> {
+        // 1フレームに収まらないコマンドは分割して順に送信（タイムアウトはフレームごと）
+        let timeout = self.command_timeout;
+        for data in command.encode_frames()? {
+            with_frame_timeout(timeout, self.send_frame(&data)).await?;
+        }
         Ok(())
     }
     
@@ -333,6 +639,7 @@ impl Connection for WindowsConnection {
     async fn get_device_info(&self) -> DeviceInfo {
         let mut info = self.device_info.clone();
         info.connected = self.is_connected().await;
+        info.battery_level = *self.battery_tx.borrow();
         info
     }
     
@@ -347,6 +654,9 @@ impl Connection for WindowsConnection {
         if !self.is_connected().await {
             info!("Reconnecting to device: {}", self.device_info.name);
             
+            // 以前のハンドラーを解除してから登録し直す（残すと同じ通知が二重に転送される）
+            self.remove_notification_handlers();
+            
             // Windowsでは再接続は新しいデバイスインスタンスを取得する必要がある
             let address = self.device.BluetoothAddress().map_err(windows_error_to_notif_error)?;
             
@@ -388,6 +698,7 @@ impl Connection for WindowsConnection {
             // キャラクタリスティックも再取得する必要がある
             let command_uuid = parse_guid(protocol_uuid::COMMAND_CHAR)?;
             let status_uuid = parse_guid(protocol_uuid::STATUS_CHAR)?;
+            let config_uuid = parse_guid(protocol_uuid::CONFIG_CHAR)?;
             
             let chars_result = self.service.GetCharacteristicsAsync()
                 .map_err(windows_error_to_notif_error)?
@@ -401,6 +712,7 @@ impl Connection for WindowsConnection {
             let chars = chars_result.Characteristics().map_err(windows_error_to_notif_error)?;
             let mut command_char = None;
             let mut status_char = None;
+            let mut config_char = None;
             
             for i in 0..chars.Size().map_err(windows_error_to_notif_error)? {
                 let char = chars.GetAt(i).map_err(windows_error_to_notif_error)?;
@@ -410,6 +722,8 @@ impl Connection for WindowsConnection {
                     command_char = Some(char);
                 } else if char_uuid == status_uuid {
                     status_char = Some(char);
+                } else if char_uuid == config_uuid {
+                    config_char = Some(char);
                 }
             }
             
@@ -433,9 +747,28 @@ impl Connection for WindowsConnection {
                     warn!("Failed to re-enable notifications on reconnect");
                 }
                 
+                (self.status_token, self.status_rx) = match subscribe_status(&stat_char, self.events_tx.clone()) {
+                    Ok((token, rx)) => (Some(token), Some(rx)),
+                    Err(e) => {
+                        warn!("Failed to re-register status notification handler: {}", e);
+                        (None, None)
+                    }
+                };
+                
                 self.status_char = Some(stat_char);
             }
             
+            // 設定キャラクタリスティックを更新
+            self.config_char = config_char;
+            
+            // バッテリーレベルを読み直して再購読
+            self.battery_char = find_battery_char(&self.device);
+            self.battery_token = self.battery_char.as_ref()
+                .and_then(|char| subscribe_battery(char, self.battery_tx.clone()));
+            
+            // ファームウェアが更新されている可能性があるため機能を読み直す
+            self.refresh_capabilities().await?;
+            
             self.device_info.connected = true;
             info!("Successfully reconnected to device: {}", self.device_info.name);
         }
@@ -443,8 +776,7 @@ impl Connection for WindowsConnection {
     }
     
     async fn get_battery_level(&self) -> Option<u8> {
-        // TODO: Battery Service (0x180F) から読み取り
-        None
+        *self.battery_tx.borrow()
     }
     
     async fn get_signal_strength(&self) -> Option<i8> {
@@ -452,6 +784,132 @@ impl Connection for WindowsConnection {
         None
     }
     
+    async fn send_sequenced(&mut self, commands: Vec<Command>, max_rounds: u32) -> Result<()> {
+        if self.status_rx.is_none() {
+            // 応答を受け取れないデバイスは順次送信にフォールバック
+            debug!("Status notifications unavailable for {}, sending sequentially", self.device_info.name);
+            for command in commands {
+                self.send_command(command).await?;
+            }
+            return Ok(());
+        }
+        
+        let timeout = self.ack_policy
+            .map(|policy| policy.timeout)
+            .unwrap_or(DEFAULT_SEQUENCE_ACK_TIMEOUT);
+        
+        let mut frames = Vec::new();
+        for command in commands {
+            frames.extend(command.split_frames()?);
+        }
+        if frames.len() > u16::MAX as usize + 1 {
+            return Err(NotifError::InvalidParameter(format!(
+                "Too many frames for sequenced transfer: {}", frames.len()
+            )));
+        }
+        
+        let mut pending = BTreeMap::new();
+        for (index, command) in frames.into_iter().enumerate() {
+            let seq = index as u16;
+            pending.insert(seq, Command::Sequenced { seq, command: Box::new(command) }.encode()?);
+        }
+        let total = pending.len();
+        
+        // 前のコマンドの遅延通知を破棄
+        if let Some(status_rx) = self.status_rx.as_mut() {
+            while status_rx.try_recv().is_ok() {}
+        }
+        
+        for round in 0..=max_rounds {
+            if round > 0 {
+                warn!("Resending {}/{} unacknowledged frames to {} (round {}/{})",
+                      pending.len(), total, self.device_info.name, round, max_rounds);
+            }
+            
+            for data in pending.values() {
+                with_frame_timeout(self.command_timeout, self.write_data(data)).await?;
+            }
+            
+            self.collect_sequence_acks(&mut pending, timeout).await?;
+            if pending.is_empty() {
+                debug!("All {} sequenced frames acknowledged by {}", total, self.device_info.name);
+                return Ok(());
+            }
+        }
+        
+        Err(NotifError::Timeout(format!(
+            "{} of {} frames unacknowledged by {} after {} retransmissions",
+            pending.len(), total, self.device_info.name, max_rounds
+        )))
+    }
+    
+    fn set_ack_policy(&mut self, policy: Option<AckPolicy>) {
+        self.ack_policy = policy;
+    }
+    
+    fn set_command_timeout(&mut self, timeout: Option<Duration>) {
+        self.command_timeout = timeout;
+    }
+    
+    async fn refresh_capabilities(&mut self) -> Result<()> {
+        let Some(ref config_char) = self.config_char else {
+            return Ok(());
+        };
+        
+        match read_capabilities(config_char) {
+            Ok(descriptor) => {
+                info!(
+                    "{}: firmware {}, {}x{} {}-bit, features 0x{:04X}",
+                    self.device_info.name,
+                    descriptor.firmware_version_string(),
+                    descriptor.display_width,
+                    descriptor.display_height,
+                    descriptor.color_depth,
+                    descriptor.features
+                );
+                self.device_info.capabilities = DeviceCapabilities::from_descriptor(&descriptor);
+                self.device_info.firmware_version = Some(descriptor.firmware_version_string());
+            }
+            Err(e) => warn!("{}: capability handshake failed, using defaults: {}", self.device_info.name, e),
+        }
+        Ok(())
+    }
+    
+    fn subscribe_events(&self) -> Option<broadcast::Receiver<ButtonEvent>> {
+        self.status_char.as_ref().map(|_| self.events_tx.subscribe())
+    }
+    
+    fn subscribe_battery(&self) -> Option<watch::Receiver<Option<u8>>> {
+        self.battery_char.as_ref().map(|_| self.battery_tx.subscribe())
+    }
+    
+    async fn apply_setting(&mut self, setting: DeviceSetting) -> Result<()> {
+        let config_char = self.config_char.as_ref().ok_or_else(|| NotifError::NotImplemented(
+            format!("Config characteristic not available on {}", self.device_info.name)
+        ))?;
+        let data = setting.encode()?;
+        
+        let writer = DataWriter::new().map_err(windows_error_to_notif_error)?;
+        writer.WriteBytes(&data).map_err(windows_error_to_notif_error)?;
+        let buffer = writer.DetachBuffer().map_err(windows_error_to_notif_error)?;
+        
+        debug!("Writing setting {:?} to {}", setting, self.device_info.name);
+        let status = config_char
+            .WriteValueWithResultAsync(&buffer)
+            .map_err(windows_error_to_notif_error)?
+            .get()
+            .map_err(windows_error_to_notif_error)?
+            .Status()
+            .map_err(windows_error_to_notif_error)?;
+        
+        if status != GattCommunicationStatus::Success {
+            return Err(NotifError::Bluetooth(format!(
+                "Failed to write setting: {:?}", status
+            )));
+        }
+        Ok(())
+    }
+    
     /// 接続速度を測定して最適化を試みる
     async fn optimize_connection_speed(&mut self) -> Result<()> {
         info!("接続速度を測定中: {}", self.device_info.name);
@@ -603,9 +1061,13 @@ impl WindowsScanner {
                         address: format!("{:016X}", address),
                         connected: false,
                         number: None,
+                        alias: None,
                         signal_strength: Some(args.RawSignalStrengthInDBm()? as i8),
                         battery_level: None,
                         capabilities: DeviceCapabilities::default(),
+                        firmware_version: None,
+                        reconnect: None,
+                        statistics: None,
                     });
                 }
             }
@@ -653,9 +1115,13 @@ impl Scanner for WindowsScanner {
                                             address: format!("{:016X}", address),
                                             connected: false,
                                             number: None,
+                                            alias: None,
                                             signal_strength: args.RawSignalStrengthInDBm().ok().map(|r| r as i8),
                                             battery_level: None,
                                             capabilities: DeviceCapabilities::default(),
+                                            firmware_version: None,
+                                            reconnect: None,
+                                            statistics: None,
                                         });
                                     }
                                 }